@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE friends (
+    account_id INTEGER NOT NULL, -- the player who sent the friend request
+    friend_id INTEGER NOT NULL,
+    accepted BOOLEAN NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (account_id, friend_id),
+    FOREIGN KEY (account_id) REFERENCES users (account_id),
+    FOREIGN KEY (friend_id) REFERENCES users (account_id)
+);
+
+CREATE INDEX friends_friend_id ON friends (friend_id);
@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE blocks (
+    account_id INTEGER NOT NULL, -- the player who blocked someone
+    blocked_id INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (account_id, blocked_id),
+    FOREIGN KEY (account_id) REFERENCES users (account_id),
+    FOREIGN KEY (blocked_id) REFERENCES users (account_id)
+);
@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE notices (
+    notice_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    issued_by INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    require_ack BOOLEAN NOT NULL DEFAULT 0,
+    sent_at INTEGER NOT NULL,
+    delivered_at INTEGER, -- NULL until the notice reaches the user
+    acked_at INTEGER,     -- NULL until the user acknowledges the notice
+    FOREIGN KEY (account_id) REFERENCES users (account_id),
+    FOREIGN KEY (issued_by) REFERENCES users (account_id)
+);
+
+CREATE INDEX notices_account_id ON notices (account_id);
@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE role_grants (
+    account_id INTEGER NOT NULL,
+    role_id TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    granted_by INTEGER NOT NULL,
+    granted_at INTEGER NOT NULL,
+    PRIMARY KEY (account_id, role_id),
+    FOREIGN KEY (account_id) REFERENCES users (account_id),
+    FOREIGN KEY (granted_by) REFERENCES users (account_id)
+);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN totp_secret TEXT;
+ALTER TABLE users ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT 0; -- only set once the user confirms the secret with a valid code
+ALTER TABLE users ADD COLUMN totp_last_step INTEGER;                  -- last time step a code was used for, so codes can't be reused
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE sessions (
+    session_id TEXT PRIMARY KEY NOT NULL,
+    account_id INTEGER NOT NULL,
+    issued_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    last_used_at INTEGER
+);
+
+CREATE INDEX sessions_account_id ON sessions (account_id);
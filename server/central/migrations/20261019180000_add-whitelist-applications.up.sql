@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE whitelist_applications (
+    application_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    user_name TEXT NOT NULL,
+    message TEXT NOT NULL,
+    status INTEGER NOT NULL DEFAULT 0, -- 0 = pending, 1 = approved, 2 = denied
+    submitted_at INTEGER NOT NULL,
+    reviewed_by INTEGER,
+    reviewed_at INTEGER,
+    review_reason TEXT
+);
+
+CREATE INDEX whitelist_applications_account_id ON whitelist_applications (account_id);
+
+-- every account can only have one pending application
+CREATE UNIQUE INDEX whitelist_applications_pending ON whitelist_applications (account_id) WHERE status = 0;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE invite_code_uses;
+DROP TABLE invite_codes;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE invite_codes (
+    code TEXT PRIMARY KEY NOT NULL,
+    created_by INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL, -- 0 = never
+    max_uses INTEGER NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    role_id TEXT
+);
+
+CREATE TABLE invite_code_uses (
+    code TEXT NOT NULL,
+    account_id INTEGER NOT NULL,
+    used_at INTEGER NOT NULL,
+    PRIMARY KEY (code, account_id)
+);
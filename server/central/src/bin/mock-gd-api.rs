@@ -0,0 +1,158 @@
+//! A small stand-in for the message endpoints of the GD servers, so that account verification can be tested offline.
+//! Set `gd_api_url` in the central server configuration to the address of this server, send the challenge answer
+//! with `uploadGJMessage20.php` (like the game does), and the verifier will pick it up.
+
+use std::{
+    error::Error,
+    sync::atomic::{AtomicI32, Ordering},
+    time::SystemTime,
+};
+
+use globed_shared::{DEFAULT_CENTRAL_SERVER_PORT, SyncMutex};
+use rocket::{Build, FromForm, Rocket, State, form::Form, post, routes};
+
+struct MockMessage {
+    id: i32,
+    author_id: i32,
+    author_user_id: i32,
+    author_name: String,
+    recipient_id: i32,
+    subject: String, // base64 encoded, as sent by the game
+    sent_at: SystemTime,
+}
+
+struct MockState {
+    messages: SyncMutex<Vec<MockMessage>>,
+    next_id: AtomicI32,
+}
+
+#[derive(FromForm)]
+struct UploadMessageForm {
+    #[field(name = "accountID")]
+    account_id: i32,
+    // the real servers know these from the account, here they can be passed explicitly
+    #[field(name = "userID")]
+    user_id: Option<i32>,
+    #[field(name = "userName")]
+    user_name: Option<String>,
+    #[field(name = "toAccountID")]
+    to_account_id: i32,
+    subject: String,
+}
+
+#[derive(FromForm)]
+struct GetMessagesForm {
+    #[field(name = "accountID")]
+    account_id: i32,
+}
+
+#[derive(FromForm)]
+struct DeleteMessagesForm {
+    #[field(name = "accountID")]
+    account_id: i32,
+    #[field(name = "messageID")]
+    message_id: Option<i32>,
+    messages: Option<String>,
+}
+
+// formats the age of the message the same way the GD servers do
+fn format_age(sent_at: SystemTime) -> String {
+    let secs = sent_at.elapsed().unwrap_or_default().as_secs();
+
+    let (amount, unit) = match secs {
+        0..60 => (secs, "second"),
+        60..3600 => (secs / 60, "minute"),
+        3600..86_400 => (secs / 3600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+
+    format!("{amount} {unit}{}", if amount == 1 { "" } else { "s" })
+}
+
+#[post("/uploadGJMessage20.php", data = "<form>")]
+fn upload_message(state: &State<MockState>, form: Form<UploadMessageForm>) -> &'static str {
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+
+    state.messages.lock().push(MockMessage {
+        id,
+        author_id: form.account_id,
+        author_user_id: form.user_id.unwrap_or(form.account_id),
+        author_name: form.user_name.clone().unwrap_or_else(|| format!("Player{}", form.account_id)),
+        recipient_id: form.to_account_id,
+        subject: form.subject.clone(),
+        sent_at: SystemTime::now(),
+    });
+
+    "1"
+}
+
+#[post("/getGJMessages20.php", data = "<form>")]
+fn get_messages(state: &State<MockState>, form: Form<GetMessagesForm>) -> String {
+    let messages = state.messages.lock();
+
+    let entries = messages
+        .iter()
+        .filter(|msg| msg.recipient_id == form.account_id)
+        .map(|msg| {
+            format!(
+                "6:{}:3:{}:2:{}:1:{}:4:{}:8:0:9:0:7:{}",
+                msg.author_name,
+                msg.author_user_id,
+                msg.author_id,
+                msg.id,
+                msg.subject,
+                format_age(msg.sent_at)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+        return "-2".to_owned();
+    }
+
+    format!("{}#{}:0:10", entries.join("|"), entries.len())
+}
+
+#[post("/deleteGJMessages20.php", data = "<form>")]
+fn delete_messages(state: &State<MockState>, form: Form<DeleteMessagesForm>) -> &'static str {
+    let mut ids = form
+        .messages
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|x| x.parse::<i32>().ok())
+        .collect::<Vec<_>>();
+
+    ids.extend(form.message_id);
+
+    state
+        .messages
+        .lock()
+        .retain(|msg| !(ids.contains(&msg.id) && (msg.recipient_id == form.account_id || msg.author_id == form.account_id)));
+
+    "1"
+}
+
+/// Builds the mock server listening on the given port, also used by the central server tests
+pub fn build(port: u16) -> Rocket<Build> {
+    let figment = rocket::Config::figment().merge(("address", "127.0.0.1")).merge(("port", port));
+
+    rocket::custom(figment)
+        .mount("/", routes![upload_message, get_messages, delete_messages])
+        .manage(MockState {
+            messages: SyncMutex::new(Vec::new()),
+            next_id: AtomicI32::new(1),
+        })
+}
+
+#[rocket::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let port = std::env::var("GLOBED_MOCK_GD_API_PORT")
+        .ok()
+        .and_then(|x| x.parse::<u16>().ok())
+        .unwrap_or(DEFAULT_CENTRAL_SERVER_PORT + 2);
+
+    build(port).launch().await?;
+
+    Ok(())
+}
@@ -6,19 +6,21 @@ use std::{
 use globed_shared::{
     anyhow::{self, anyhow},
     esp::{self, Decodable, Encodable},
-    generate_alphanum_string, Decodable, Encodable, ServerRole, ADMIN_KEY_LENGTH, DEFAULT_GAME_SERVER_PORT,
+    generate_alphanum_string, Announcement, ChatCommand, Decodable, Encodable, PersistentRoom, RetiredKey, ScheduledMaintenance, ServerRole, ADMIN_KEY_LENGTH,
+    DEFAULT_GAME_SERVER_PORT,
 };
 use json_comments::StripComments;
 use serde::{Deserialize, Serialize};
 use serde_json::{ser::PrettyFormatter, Serializer};
 
+use crate::ip_blocker::IpBlocker;
+
 /* stinky serde defaults */
 
 const fn default_false() -> bool {
     false
 }
 
-#[allow(unused)]
 const fn default_true() -> bool {
     true
 }
@@ -35,6 +37,10 @@ fn default_admin_key() -> String {
     generate_alphanum_string(ADMIN_KEY_LENGTH)
 }
 
+fn default_totp_issuer() -> String {
+    "Globed".to_owned()
+}
+
 const fn default_gd_api_account() -> i32 {
     0
 }
@@ -76,6 +82,22 @@ const fn default_chat_burst_interval() -> u32 {
     3000
 }
 
+fn default_persistent_rooms() -> Vec<PersistentRoom> {
+    Vec::new()
+}
+
+fn default_chat_commands() -> Vec<ChatCommand> {
+    Vec::new()
+}
+
+fn default_scheduled_maintenance() -> Option<ScheduledMaintenance> {
+    None
+}
+
+fn default_announcements() -> Vec<Announcement> {
+    Vec::new()
+}
+
 fn default_roles() -> Vec<ServerRole> {
     vec![
         ServerRole {
@@ -96,6 +118,7 @@ fn default_roles() -> Vec<ServerRole> {
             mute: true,
             ban: true,
             edit_role: true,
+            permanent_punishments: true,
             ..Default::default()
         },
     ]
@@ -107,6 +130,18 @@ fn default_secret_key() -> String {
     format!("Insecure-{rand_string}")
 }
 
+fn default_retired_keys() -> Vec<RetiredKey> {
+    Vec::new()
+}
+
+const fn default_proxy_header() -> ProxyHeader {
+    ProxyHeader::None
+}
+
+fn default_trusted_proxies() -> Vec<String> {
+    Vec::new()
+}
+
 const fn default_challenge_expiry() -> u32 {
     30
 }
@@ -115,6 +150,30 @@ const fn default_token_expiry() -> u64 {
     60 * 60 * 24
 }
 
+const fn default_auth_rate_limit_window() -> u64 {
+    60
+}
+
+const fn default_auth_rate_limit_ip() -> u32 {
+    30
+}
+
+const fn default_auth_rate_limit_account() -> u32 {
+    15
+}
+
+const fn default_auth_lockout_threshold() -> u32 {
+    5
+}
+
+const fn default_auth_lockout_base() -> u64 {
+    30
+}
+
+const fn default_auth_lockout_max() -> u64 {
+    60 * 60
+}
+
 /* end stinky serde defaults */
 
 #[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
@@ -128,6 +187,19 @@ pub enum UserlistMode {
     None, // same as blacklist
 }
 
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum ProxyHeader {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "cf-connecting-ip")]
+    CfConnectingIp,
+    #[serde(rename = "x-forwarded-for")]
+    XForwardedFor,
+    #[serde(rename = "x-real-ip")]
+    XRealIp,
+}
+
 #[derive(Serialize, Deserialize, Encodable, Decodable, Default, Clone)]
 pub struct GameServerEntry {
     pub id: String,
@@ -145,12 +217,16 @@ pub struct ServerConfig {
     pub game_servers: Vec<GameServerEntry>,
     #[serde(default = "default_false")]
     pub maintenance: bool,
+    #[serde(default = "default_scheduled_maintenance")]
+    pub scheduled_maintenance: Option<ScheduledMaintenance>,
     #[serde(default = "default_status_print_interval")]
     pub status_print_interval: u64,
 
     // special users and "special" users
     #[serde(default = "default_userlist_mode")]
     pub userlist_mode: UserlistMode,
+    #[serde(default = "default_true")]
+    pub whitelist_applications: bool,
 
     // game stuff
     #[serde(default = "default_tps")]
@@ -173,15 +249,33 @@ pub struct ServerConfig {
     pub chat_burst_limit: u32,
     #[serde(default = "default_chat_burst_interval")]
     pub chat_burst_interval: u32,
+    #[serde(default = "default_false")]
+    pub global_chat: bool,
+    #[serde(default = "default_chat_commands")]
+    pub chat_commands: Vec<ChatCommand>,
+
+    // announcements
+    #[serde(default = "default_string")]
+    pub motd: String,
+    #[serde(default = "default_announcements")]
+    pub announcements: Vec<Announcement>,
 
     // roles
     #[serde(default = "default_roles")]
     pub roles: Vec<ServerRole>,
 
+    // rooms
+    #[serde(default = "default_persistent_rooms")]
+    pub persistent_rooms: Vec<PersistentRoom>,
+
     // security
     #[serde(default = "default_admin_key")]
     pub admin_key: String,
     #[serde(default = "default_false")]
+    pub break_glass_admin_key: bool,
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+    #[serde(default = "default_false")]
     pub use_gd_api: bool,
     #[serde(default = "default_gd_api_account")]
     pub gd_api_account: i32,
@@ -197,14 +291,36 @@ pub struct ServerConfig {
     pub secret_key: String,
     #[serde(default = "default_secret_key")]
     pub secret_key2: String,
+    #[serde(default = "default_retired_keys")]
+    pub retired_secret_keys: Vec<RetiredKey>,
+    #[serde(default = "default_retired_keys")]
+    pub retired_secret_keys2: Vec<RetiredKey>,
     #[serde(default = "default_secret_key")]
     pub game_server_password: String,
     #[serde(default = "default_false")]
     pub cloudflare_protection: bool,
+    #[serde(default = "default_proxy_header")]
+    pub proxy_header: ProxyHeader,
+    #[serde(default = "default_trusted_proxies")]
+    pub trusted_proxies: Vec<String>,
     #[serde(default = "default_challenge_expiry")]
     pub challenge_expiry: u32,
     #[serde(default = "default_token_expiry")]
     pub token_expiry: u64,
+
+    // auth rate limiting
+    #[serde(default = "default_auth_rate_limit_window")]
+    pub auth_rate_limit_window: u64,
+    #[serde(default = "default_auth_rate_limit_ip")]
+    pub auth_rate_limit_ip: u32,
+    #[serde(default = "default_auth_rate_limit_account")]
+    pub auth_rate_limit_account: u32,
+    #[serde(default = "default_auth_lockout_threshold")]
+    pub auth_lockout_threshold: u32,
+    #[serde(default = "default_auth_lockout_base")]
+    pub auth_lockout_base: u64,
+    #[serde(default = "default_auth_lockout_max")]
+    pub auth_lockout_max: u64,
 }
 
 impl ServerConfig {
@@ -227,16 +343,152 @@ impl ServerConfig {
     }
 
     pub fn reload_in_place(&mut self, source: &Path) -> anyhow::Result<()> {
-        let conf = Self::load(source)?;
+        let mut conf = Self::load(source)?;
 
         // Do validation
         if conf.admin_key.len() > ADMIN_KEY_LENGTH {
             return Err(anyhow!("Invalid admin key size, must be {ADMIN_KEY_LENGTH} characters or less"));
         }
 
+        conf.resolve_roles()?;
+        conf.validate_persistent_rooms()?;
+        conf.validate_chat_commands()?;
+        conf.validate_announcements()?;
+        conf.validate_retired_keys()?;
+        conf.validate_auth_rate_limits()?;
+        conf.validate_trusted_proxies()?;
+
+        if conf.scheduled_maintenance.is_some_and(|x| x.starts_at == 0) {
+            return Err(anyhow!("Invalid scheduled maintenance, start time must be set"));
+        }
+
         self.clone_from(&conf);
         Ok(())
     }
+
+    /// Resolves role inheritance, see [`globed_shared::resolve_roles`]
+    pub fn resolve_roles(&mut self) -> anyhow::Result<()> {
+        globed_shared::resolve_roles(&mut self.roles).map_err(|e| anyhow!(e))
+    }
+
+    pub fn validate_persistent_rooms(&self) -> anyhow::Result<()> {
+        for (idx, room) in self.persistent_rooms.iter().enumerate() {
+            // room ids are always 6 digits long
+            if !(100_000..1_000_000).contains(&room.id) {
+                return Err(anyhow!("Invalid persistent room id {}, must be a 6-digit number", room.id));
+            }
+
+            if self.persistent_rooms[..idx].iter().any(|other| other.id == room.id) {
+                return Err(anyhow!("Duplicate persistent room id {}", room.id));
+            }
+
+            if room.name.is_empty() || room.name.len() > 32 {
+                return Err(anyhow!("Invalid name for persistent room {}, must be between 1 and 32 characters", room.id));
+            }
+
+            if room.password.len() > 16 {
+                return Err(anyhow!("Invalid password for persistent room {}, must be 16 characters or less", room.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_chat_commands(&self) -> anyhow::Result<()> {
+        for (idx, command) in self.chat_commands.iter().enumerate() {
+            let valid_chars = command.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+            if command.name.is_empty() || command.name.len() > 32 || !valid_chars {
+                return Err(anyhow!(
+                    "Invalid chat command name '{}', must be between 1 and 32 characters and only contain letters, digits, '_' or '-'",
+                    command.name
+                ));
+            }
+
+            if self.chat_commands[..idx].iter().any(|other| other.name.eq_ignore_ascii_case(&command.name)) {
+                return Err(anyhow!("Duplicate chat command '{}'", command.name));
+            }
+
+            if command.response.is_empty() {
+                return Err(anyhow!("Empty response for chat command '{}'", command.name));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_retired_keys(&self) -> anyhow::Result<()> {
+        for (current, retired) in [
+            (&self.secret_key, &self.retired_secret_keys),
+            (&self.secret_key2, &self.retired_secret_keys2),
+        ] {
+            for key in retired {
+                if key.key.is_empty() {
+                    return Err(anyhow!("Retired secret keys must not be empty"));
+                }
+
+                if key.key == *current {
+                    return Err(anyhow!("A secret key that is currently in use cannot also be retired"));
+                }
+
+                if key.expires_at == 0 {
+                    return Err(anyhow!("Retired secret keys must have an expiration time"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_announcements(&self) -> anyhow::Result<()> {
+        for (idx, announcement) in self.announcements.iter().enumerate() {
+            if announcement.message.is_empty() {
+                return Err(anyhow!("Announcement #{idx} has an empty message"));
+            }
+
+            if announcement.ends_at != 0 && announcement.ends_at <= announcement.starts_at {
+                return Err(anyhow!("Announcement #{idx} ends before it starts"));
+            }
+
+            if let Some(role) = announcement.roles.iter().find(|role| !self.roles.iter().any(|x| x.id == **role)) {
+                return Err(anyhow!("Announcement #{idx} targets role '{role}' which does not exist"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the header that holds the client's IP address, `cloudflare_protection` is kept as a shorthand for `cf-connecting-ip`
+    pub fn effective_proxy_header(&self) -> ProxyHeader {
+        if self.proxy_header == ProxyHeader::None && self.cloudflare_protection {
+            ProxyHeader::CfConnectingIp
+        } else {
+            self.proxy_header
+        }
+    }
+
+    pub fn validate_trusted_proxies(&self) -> anyhow::Result<()> {
+        IpBlocker::from_ranges(&self.trusted_proxies)?;
+
+        let header = self.effective_proxy_header();
+        if self.trusted_proxies.is_empty() && header != ProxyHeader::None && header != ProxyHeader::CfConnectingIp {
+            return Err(anyhow!("A proxy header is set, but there are no trusted proxies to accept it from"));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_auth_rate_limits(&self) -> anyhow::Result<()> {
+        if self.auth_rate_limit_window == 0 {
+            return Err(anyhow!("Auth rate limit window must be at least 1 second"));
+        }
+
+        if self.auth_lockout_base > self.auth_lockout_max {
+            return Err(anyhow!("Base auth lockout duration must not be longer than the maximum lockout duration"));
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for ServerConfig {
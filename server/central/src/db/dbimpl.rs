@@ -1,14 +1,25 @@
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use globed_shared::{AdminPunishUserAction, PunishmentType, ServerUserEntry, UserPunishment};
+use globed_shared::{
+    AdminCreateInviteCodeAction, AdminGrantRoleAction, AdminPunishUserAction, AdminStoreNoticeAction, FriendList, FriendStatus, InviteCode,
+    PunishmentType, RoleGrant, ServerUserEntry, StoredNotice, UserPunishment, UserSession, WhitelistApplication,
+};
 use rocket_db_pools::sqlx::{Result, query_as};
 use serde::Serialize;
 use sqlx::{prelude::*, query, query_scalar, sqlite::SqliteRow};
 
 use super::GlobedDb;
 
+/// Amount of whitelist applications returned by `get_pending_applications` at once
+pub const APPLICATIONS_PAGE_SIZE: usize = 25;
+
 struct UserEntryWrapper(pub ServerUserEntry);
 struct UserPunishmentWrapper(pub UserPunishment);
+struct StoredNoticeWrapper(pub StoredNotice);
+struct RoleGrantWrapper(pub RoleGrant);
+struct UserSessionWrapper(pub UserSession);
+struct WhitelistApplicationWrapper(pub WhitelistApplication);
+struct InviteCodeWrapper(pub InviteCode);
 
 impl<'r> FromRow<'r, SqliteRow> for UserEntryWrapper {
     fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
@@ -25,6 +36,7 @@ impl<'r> FromRow<'r, SqliteRow> for UserEntryWrapper {
         let admin_password_hash = row.try_get("admin_password_hash")?;
         let active_mute = row.try_get("active_mute")?;
         let active_ban = row.try_get("active_ban")?;
+        let totp_enabled = row.try_get("totp_enabled")?;
 
         Ok(UserEntryWrapper(ServerUserEntry {
             account_id,
@@ -36,7 +48,9 @@ impl<'r> FromRow<'r, SqliteRow> for UserEntryWrapper {
             admin_password_hash,
             active_mute,
             active_ban,
-            punishment_count: 0, // this will be initialized later
+            punishment_count: 0,     // this will be initialized later
+            role_grants: Vec::new(), // same here
+            totp_enabled,
         }))
     }
 }
@@ -66,6 +80,81 @@ impl<'r> FromRow<'r, SqliteRow> for UserPunishmentWrapper {
     }
 }
 
+impl<'r> FromRow<'r, SqliteRow> for StoredNoticeWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(StoredNoticeWrapper(StoredNotice {
+            id: row.try_get("notice_id")?,
+            account_id: row.try_get("account_id")?,
+            issued_by: row.try_get("issued_by")?,
+            message: row.try_get("message")?,
+            require_ack: row.try_get("require_ack")?,
+            sent_at: row.try_get("sent_at")?,
+            delivered_at: row.try_get("delivered_at")?,
+            acked_at: row.try_get("acked_at")?,
+        }))
+    }
+}
+
+impl<'r> FromRow<'r, SqliteRow> for RoleGrantWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let expires_at: i64 = row.try_get("expires_at")?;
+
+        Ok(RoleGrantWrapper(RoleGrant {
+            role_id: row.try_get("role_id")?,
+            expires_at: expires_at as u64,
+            granted_by: row.try_get("granted_by")?,
+        }))
+    }
+}
+
+impl<'r> FromRow<'r, SqliteRow> for UserSessionWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let issued_at: i64 = row.try_get("issued_at")?;
+        let expires_at: i64 = row.try_get("expires_at")?;
+        let last_used_at: Option<i64> = row.try_get("last_used_at")?;
+
+        Ok(UserSessionWrapper(UserSession {
+            session_id: row.try_get("session_id")?,
+            issued_at: issued_at as u64,
+            expires_at: expires_at as u64,
+            last_used_at: last_used_at.unwrap_or(0) as u64,
+        }))
+    }
+}
+
+impl<'r> FromRow<'r, SqliteRow> for WhitelistApplicationWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let submitted_at: i64 = row.try_get("submitted_at")?;
+
+        Ok(WhitelistApplicationWrapper(WhitelistApplication {
+            application_id: row.try_get("application_id")?,
+            account_id: row.try_get("account_id")?,
+            user_name: row.try_get("user_name")?,
+            message: row.try_get("message")?,
+            submitted_at: submitted_at as u64,
+        }))
+    }
+}
+
+impl<'r> FromRow<'r, SqliteRow> for InviteCodeWrapper {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let created_at: i64 = row.try_get("created_at")?;
+        let expires_at: i64 = row.try_get("expires_at")?;
+        let max_uses: i64 = row.try_get("max_uses")?;
+        let uses: i64 = row.try_get("uses")?;
+
+        Ok(InviteCodeWrapper(InviteCode {
+            code: row.try_get("code")?,
+            created_by: row.try_get("created_by")?,
+            created_at: created_at as u64,
+            expires_at: expires_at as u64,
+            max_uses: max_uses as u32,
+            uses: uses as u32,
+            role_id: row.try_get("role_id")?,
+        }))
+    }
+}
+
 #[derive(Clone, FromRow, Serialize)]
 pub struct PlayerCountHistoryEntry {
     #[serde(skip_serializing)]
@@ -120,6 +209,15 @@ impl GlobedDb {
         self.unwrap_user(res).await
     }
 
+    pub async fn user_exists(&self, account_id: i32) -> Result<bool> {
+        let count: i64 = query_scalar("SELECT COUNT(*) FROM users WHERE account_id = ?")
+            .bind(account_id)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok(count > 0)
+    }
+
     pub async fn insert_empty_user(&self, account_id: i32) -> Result<()> {
         query!("INSERT INTO users (account_id) VALUES (?) ON CONFLICT DO NOTHING", account_id)
             .execute(&self.0)
@@ -229,6 +327,11 @@ impl GlobedDb {
             self.maybe_expire_punishments(user).await?;
         }
 
+        // strip expired role grants and add the active ones to the user's roles
+        if let Some(user) = user.as_mut() {
+            self.load_role_grants(user).await?;
+        }
+
         // count punishments
         if let Some(user) = user.as_mut() {
             user.punishment_count = query_scalar("SELECT COUNT(*) FROM punishments WHERE account_id = ?")
@@ -259,18 +362,86 @@ impl GlobedDb {
             .map(|_| ())
     }
 
-    pub async fn update_user_roles(&self, account_id: i32, roles: &[String]) -> Result<()> {
-        let joined = roles.join(",");
+    async fn load_role_grants(&self, user: &mut ServerUserEntry) -> Result<()> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        query("DELETE FROM role_grants WHERE account_id = ? AND expires_at <= ?")
+            .bind(user.account_id)
+            .bind(now)
+            .execute(&self.0)
+            .await?;
 
+        user.role_grants = query_as::<_, RoleGrantWrapper>("SELECT * FROM role_grants WHERE account_id = ?")
+            .bind(user.account_id)
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(|x| x.0)
+            .collect();
+
+        for grant in &user.role_grants {
+            if !user.user_roles.contains(&grant.role_id) {
+                user.user_roles.push(grant.role_id.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the roles of the user. Roles that are currently granted temporarily stay temporary,
+    /// and temporary grants for roles that are not in the list get revoked.
+    pub async fn update_user_roles(&self, account_id: i32, roles: &[String]) -> Result<()> {
         // make sure the user exists in the db
         self.insert_empty_user(account_id).await?;
 
+        let granted: Vec<String> = query_scalar("SELECT role_id FROM role_grants WHERE account_id = ?")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await?;
+
+        for role_id in granted.iter().filter(|x| !roles.contains(x)) {
+            query("DELETE FROM role_grants WHERE account_id = ? AND role_id = ?")
+                .bind(account_id)
+                .bind(role_id)
+                .execute(&self.0)
+                .await?;
+        }
+
+        let joined = roles
+            .iter()
+            .filter(|x| !granted.contains(x))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+
         query!("UPDATE users SET user_roles = ? WHERE account_id = ?", joined, account_id)
             .execute(&self.0)
             .await
             .map(|_| ())
     }
 
+    /// Grants a role to the user until `expires_at`, replacing the expiry if they already have this role granted
+    pub async fn grant_role(&self, action: &AdminGrantRoleAction) -> Result<()> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        // make sure both the mod and the user exist in the db
+        self.insert_empty_user(action.account_id).await?;
+        self.insert_empty_user(action.issued_by).await?;
+
+        query(
+            "INSERT INTO role_grants (account_id, role_id, expires_at, granted_by, granted_at) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (account_id, role_id) DO UPDATE SET expires_at = excluded.expires_at, granted_by = excluded.granted_by, granted_at = excluded.granted_at",
+        )
+        .bind(action.account_id)
+        .bind(&action.role_id)
+        .bind(action.expires_at as i64)
+        .bind(action.issued_by)
+        .bind(now)
+        .execute(&self.0)
+        .await
+        .map(|_| ())
+    }
+
     pub async fn punish_user(&self, action: &AdminPunishUserAction) -> Result<i64> {
         let reason = action.reason.try_to_str();
         let r#type = if action.is_ban { "ban" } else { "mute" };
@@ -369,6 +540,523 @@ impl GlobedDb {
         .map(|_| ())
     }
 
+    // Friends
+
+    /// Returns the account IDs of all accepted friends of the user
+    pub async fn get_friends(&self, account_id: i32) -> Result<Vec<i32>> {
+        query_scalar(
+            "SELECT friend_id FROM friends WHERE account_id = ? AND accepted = 1 UNION SELECT account_id FROM friends WHERE friend_id = ? AND accepted = 1",
+        )
+        .bind(account_id)
+        .bind(account_id)
+        .fetch_all(&self.0)
+        .await
+    }
+
+    pub async fn get_friend_list(&self, account_id: i32) -> Result<FriendList> {
+        let friends = self.get_friends(account_id).await?;
+
+        let incoming = query_scalar("SELECT account_id FROM friends WHERE friend_id = ? AND accepted = 0 ORDER BY created_at")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await?;
+
+        let outgoing = query_scalar("SELECT friend_id FROM friends WHERE account_id = ? AND accepted = 0 ORDER BY created_at")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await?;
+
+        Ok(FriendList { friends, incoming, outgoing })
+    }
+
+    pub async fn get_friend_status(&self, account_id: i32, target_id: i32) -> Result<FriendStatus> {
+        let row: Option<(i32, bool)> =
+            query_as("SELECT account_id, accepted FROM friends WHERE (account_id = ? AND friend_id = ?) OR (account_id = ? AND friend_id = ?)")
+                .bind(account_id)
+                .bind(target_id)
+                .bind(target_id)
+                .bind(account_id)
+                .fetch_optional(&self.0)
+                .await?;
+
+        Ok(match row {
+            None => FriendStatus::None,
+            Some((_, true)) => FriendStatus::Friends,
+            Some((sender, false)) if sender == account_id => FriendStatus::RequestSent,
+            Some(_) => FriendStatus::RequestReceived,
+        })
+    }
+
+    /// Returns the amount of friend requests sent by the user that have not been answered yet
+    pub async fn get_pending_friend_request_count(&self, account_id: i32) -> Result<i64> {
+        query_scalar("SELECT COUNT(*) FROM friends WHERE account_id = ? AND accepted = 0")
+            .bind(account_id)
+            .fetch_one(&self.0)
+            .await
+    }
+
+    /// Sends a friend request, or accepts the request if the target has already sent one. Returns the new status.
+    /// The target must already exist in the database.
+    pub async fn send_friend_request(&self, account_id: i32, target_id: i32) -> Result<FriendStatus> {
+        // silently drop requests from players that the target has blocked
+        if self.is_blocked(target_id, account_id).await? {
+            return Ok(FriendStatus::None);
+        }
+
+        match self.get_friend_status(account_id, target_id).await? {
+            FriendStatus::RequestReceived => self.respond_friend_request(account_id, target_id, true).await,
+            FriendStatus::None => {
+                self.insert_empty_user(account_id).await?;
+
+                query("INSERT INTO friends (account_id, friend_id, accepted, created_at) VALUES (?, ?, 0, ?)")
+                    .bind(account_id)
+                    .bind(target_id)
+                    .bind(i64::try_from(UNIX_EPOCH.elapsed().unwrap().as_secs()).unwrap_or(0))
+                    .execute(&self.0)
+                    .await?;
+
+                Ok(FriendStatus::RequestSent)
+            }
+            status => Ok(status),
+        }
+    }
+
+    /// Accepts or declines a pending friend request sent by `target_id`. Returns the new status.
+    pub async fn respond_friend_request(&self, account_id: i32, target_id: i32, accept: bool) -> Result<FriendStatus> {
+        let status = self.get_friend_status(account_id, target_id).await?;
+        if status != FriendStatus::RequestReceived {
+            return Ok(status);
+        }
+
+        if accept {
+            query("UPDATE friends SET accepted = 1 WHERE account_id = ? AND friend_id = ?")
+                .bind(target_id)
+                .bind(account_id)
+                .execute(&self.0)
+                .await?;
+
+            Ok(FriendStatus::Friends)
+        } else {
+            query("DELETE FROM friends WHERE account_id = ? AND friend_id = ?")
+                .bind(target_id)
+                .bind(account_id)
+                .execute(&self.0)
+                .await?;
+
+            Ok(FriendStatus::None)
+        }
+    }
+
+    /// Removes a friend, or cancels a pending friend request in either direction
+    pub async fn remove_friend(&self, account_id: i32, target_id: i32) -> Result<()> {
+        query("DELETE FROM friends WHERE (account_id = ? AND friend_id = ?) OR (account_id = ? AND friend_id = ?)")
+            .bind(account_id)
+            .bind(target_id)
+            .bind(target_id)
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    // Block lists
+
+    /// Returns the account IDs of all players blocked by the user
+    pub async fn get_blocked_users(&self, account_id: i32) -> Result<Vec<i32>> {
+        query_scalar("SELECT blocked_id FROM blocks WHERE account_id = ? ORDER BY created_at")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await
+    }
+
+    /// Returns whether `account_id` has blocked `target_id`
+    pub async fn is_blocked(&self, account_id: i32, target_id: i32) -> Result<bool> {
+        let count: i64 = query_scalar("SELECT COUNT(*) FROM blocks WHERE account_id = ? AND blocked_id = ?")
+            .bind(account_id)
+            .bind(target_id)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Blocks a player, which also removes them from friends and cancels any pending friend requests.
+    /// The target must already exist in the database.
+    pub async fn block_user(&self, account_id: i32, target_id: i32) -> Result<()> {
+        self.insert_empty_user(account_id).await?;
+
+        self.remove_friend(account_id, target_id).await?;
+
+        query("INSERT INTO blocks (account_id, blocked_id, created_at) VALUES (?, ?, ?) ON CONFLICT DO NOTHING")
+            .bind(account_id)
+            .bind(target_id)
+            .bind(i64::try_from(UNIX_EPOCH.elapsed().unwrap().as_secs()).unwrap_or(0))
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn unblock_user(&self, account_id: i32, target_id: i32) -> Result<()> {
+        query("DELETE FROM blocks WHERE account_id = ? AND blocked_id = ?")
+            .bind(account_id)
+            .bind(target_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    // TOTP
+
+    /// Stores a new secret for the user, TOTP stays disabled until they confirm it with `enable_totp`
+    pub async fn set_totp_secret(&self, account_id: i32, secret: &str) -> Result<()> {
+        self.insert_empty_user(account_id).await?;
+
+        query("UPDATE users SET totp_secret = ?, totp_enabled = 0, totp_last_step = NULL WHERE account_id = ?")
+            .bind(secret)
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn enable_totp(&self, account_id: i32) -> Result<()> {
+        query("UPDATE users SET totp_enabled = 1 WHERE account_id = ? AND totp_secret IS NOT NULL")
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn disable_totp(&self, account_id: i32) -> Result<()> {
+        query("UPDATE users SET totp_secret = NULL, totp_enabled = 0, totp_last_step = NULL WHERE account_id = ?")
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    /// Returns the secret of the user and the last time step a code was used for
+    pub async fn get_totp_secret(&self, account_id: i32) -> Result<Option<(String, Option<i64>)>> {
+        query_as("SELECT totp_secret, totp_last_step FROM users WHERE account_id = ? AND totp_secret IS NOT NULL")
+            .bind(account_id)
+            .fetch_optional(&self.0)
+            .await
+    }
+
+    /// Marks the time step as used, returns `false` if it (or a later one) has already been used
+    pub async fn use_totp_step(&self, account_id: i32, step: i64) -> Result<bool> {
+        query("UPDATE users SET totp_last_step = ? WHERE account_id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)")
+            .bind(step)
+            .bind(account_id)
+            .bind(step)
+            .execute(&self.0)
+            .await
+            .map(|r| r.rows_affected() > 0)
+    }
+
+    // Sessions
+
+    pub async fn create_session(&self, account_id: i32, session_id: &str, expires_at: u64) -> Result<()> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        // take the chance to clean up sessions that expired on their own
+        query("DELETE FROM sessions WHERE expires_at <= ?").bind(now).execute(&self.0).await?;
+
+        query("INSERT INTO sessions (session_id, account_id, issued_at, expires_at) VALUES (?, ?, ?, ?)")
+            .bind(session_id)
+            .bind(account_id)
+            .bind(now)
+            .bind(expires_at as i64)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    /// Returns all sessions of the user that have not expired or been revoked, newest first
+    pub async fn get_sessions(&self, account_id: i32) -> Result<Vec<UserSession>> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        query_as::<_, UserSessionWrapper>("SELECT * FROM sessions WHERE account_id = ? AND expires_at > ? ORDER BY issued_at DESC")
+            .bind(account_id)
+            .bind(now)
+            .fetch_all(&self.0)
+            .await
+            .map(|x| x.into_iter().map(|x| x.0).collect())
+    }
+
+    /// Marks the session as used, returns `false` if it does not exist (was revoked or expired)
+    pub async fn use_session(&self, account_id: i32, session_id: &str) -> Result<bool> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        query("UPDATE sessions SET last_used_at = ? WHERE session_id = ? AND account_id = ? AND expires_at > ?")
+            .bind(now)
+            .bind(session_id)
+            .bind(account_id)
+            .bind(now)
+            .execute(&self.0)
+            .await
+            .map(|x| x.rows_affected() != 0)
+    }
+
+    pub async fn revoke_session(&self, account_id: i32, session_id: &str) -> Result<()> {
+        query("DELETE FROM sessions WHERE session_id = ? AND account_id = ?")
+            .bind(session_id)
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn revoke_all_sessions(&self, account_id: i32) -> Result<()> {
+        query("DELETE FROM sessions WHERE account_id = ?")
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
+    // Whitelist applications
+
+    /// Submits a new application, returns `false` if the user already has a pending one
+    pub async fn create_whitelist_application(&self, account_id: i32, user_name: &str, message: &str) -> Result<bool> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        query("INSERT INTO whitelist_applications (account_id, user_name, message, submitted_at) VALUES (?, ?, ?, ?) ON CONFLICT DO NOTHING")
+            .bind(account_id)
+            .bind(user_name)
+            .bind(message)
+            .bind(now)
+            .execute(&self.0)
+            .await
+            .map(|x| x.rows_affected() != 0)
+    }
+
+    pub async fn get_pending_application(&self, account_id: i32) -> Result<Option<WhitelistApplication>> {
+        query_as::<_, WhitelistApplicationWrapper>("SELECT * FROM whitelist_applications WHERE account_id = ? AND status = 0")
+            .bind(account_id)
+            .fetch_optional(&self.0)
+            .await
+            .map(|x| x.map(|x| x.0))
+    }
+
+    /// Returns a page of the applications that have not been reviewed yet, oldest first
+    pub async fn get_pending_applications(&self, page: usize) -> Result<Vec<WhitelistApplication>> {
+        query_as::<_, WhitelistApplicationWrapper>(
+            "SELECT * FROM whitelist_applications WHERE status = 0 ORDER BY submitted_at ASC, application_id ASC LIMIT ? OFFSET ?",
+        )
+        .bind(APPLICATIONS_PAGE_SIZE as i64)
+        .bind((page * APPLICATIONS_PAGE_SIZE) as i64)
+        .fetch_all(&self.0)
+        .await
+        .map(|x| x.into_iter().map(|x| x.0).collect())
+    }
+
+    /// Approves or denies the pending application of the user, whitelisting them if it was approved.
+    /// Returns `false` if the user has no pending application.
+    pub async fn review_whitelist_application(&self, account_id: i32, reviewed_by: i32, approve: bool, reason: &str) -> Result<bool> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        let reviewed = query(
+            "UPDATE whitelist_applications SET status = ?, reviewed_by = ?, reviewed_at = ?, review_reason = ? WHERE account_id = ? AND status = 0",
+        )
+        .bind(if approve { 1 } else { 2 })
+        .bind(reviewed_by)
+        .bind(now)
+        .bind(reason)
+        .bind(account_id)
+        .execute(&self.0)
+        .await?
+        .rows_affected()
+            != 0;
+
+        if reviewed && approve {
+            self.whitelist_user(account_id, true).await?;
+        }
+
+        Ok(reviewed)
+    }
+
+    // Invite codes
+
+    pub async fn create_invite_code(&self, code: &str, action: &AdminCreateInviteCodeAction) -> Result<InviteCode> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        query("INSERT INTO invite_codes (code, created_by, created_at, expires_at, max_uses, role_id) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(code)
+            .bind(action.issued_by)
+            .bind(now)
+            .bind(action.expires_at as i64)
+            .bind(i64::from(action.max_uses))
+            .bind(action.role_id.as_deref())
+            .execute(&self.0)
+            .await?;
+
+        Ok(InviteCode {
+            code: code.to_owned(),
+            created_by: action.issued_by,
+            created_at: now as u64,
+            expires_at: action.expires_at,
+            max_uses: action.max_uses,
+            uses: 0,
+            role_id: action.role_id.clone(),
+        })
+    }
+
+    pub async fn get_invite_code(&self, code: &str) -> Result<Option<InviteCode>> {
+        query_as::<_, InviteCodeWrapper>("SELECT * FROM invite_codes WHERE code = ?")
+            .bind(code)
+            .fetch_optional(&self.0)
+            .await
+            .map(|x| x.map(|x| x.0))
+    }
+
+    /// Returns all invite codes, newest first
+    pub async fn get_invite_codes(&self) -> Result<Vec<InviteCode>> {
+        query_as::<_, InviteCodeWrapper>("SELECT * FROM invite_codes ORDER BY created_at DESC")
+            .fetch_all(&self.0)
+            .await
+            .map(|x| x.into_iter().map(|x| x.0).collect())
+    }
+
+    /// Deletes the invite code, returns `false` if it did not exist. Users that already redeemed it stay whitelisted.
+    pub async fn delete_invite_code(&self, code: &str) -> Result<bool> {
+        query("DELETE FROM invite_codes WHERE code = ?")
+            .bind(code)
+            .execute(&self.0)
+            .await
+            .map(|x| x.rows_affected() != 0)
+    }
+
+    /// Uses up one use of the invite code, whitelists the user and gives them the role of the code.
+    /// Returns `false` if the code is not usable anymore or the user has already redeemed it.
+    pub async fn redeem_invite_code(&self, code: &str, account_id: i32) -> Result<bool> {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
+
+        // everything is done in one transaction, dropping it without committing rolls back all changes
+        let mut tx = self.0.begin().await?;
+
+        // the primary key makes sure that a user can only redeem a code once, even if they try to do it concurrently
+        let inserted = query("INSERT INTO invite_code_uses (code, account_id, used_at) VALUES (?, ?, ?) ON CONFLICT DO NOTHING")
+            .bind(code)
+            .bind(account_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+            != 0;
+
+        if !inserted {
+            return Ok(false);
+        }
+
+        // the conditions are checked in the same query, so that a code can't be used more times than allowed
+        let role_id: Option<Option<String>> = query_scalar(
+            "UPDATE invite_codes SET uses = uses + 1 WHERE code = ? AND uses < max_uses AND (expires_at = 0 OR expires_at > ?) RETURNING role_id",
+        )
+        .bind(code)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(role_id) = role_id else {
+            return Ok(false);
+        };
+
+        query("INSERT INTO users (account_id) VALUES (?) ON CONFLICT DO NOTHING")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+
+        query("UPDATE users SET is_whitelisted = 1 WHERE account_id = ?")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if let Some(role_id) = role_id {
+            // appends the role in a single query, unless the user already has it
+            query(
+                "UPDATE users SET user_roles = CASE WHEN IFNULL(user_roles, '') = '' THEN ? ELSE user_roles || ',' || ? END
+                WHERE account_id = ? AND instr(',' || IFNULL(user_roles, '') || ',', ',' || ? || ',') = 0",
+            )
+            .bind(&role_id)
+            .bind(&role_id)
+            .bind(account_id)
+            .bind(&role_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    // Notices
+
+    pub async fn store_notice(&self, action: &AdminStoreNoticeAction) -> Result<StoredNotice> {
+        let now = i64::try_from(UNIX_EPOCH.elapsed().unwrap().as_secs()).unwrap_or(0);
+
+        // make sure both the admin and the user exist in the db
+        self.insert_empty_user(action.account_id).await?;
+        self.insert_empty_user(action.issued_by).await?;
+
+        query_as::<_, StoredNoticeWrapper>(
+            "INSERT INTO notices (account_id, issued_by, message, require_ack, sent_at, delivered_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING *",
+        )
+        .bind(action.account_id)
+        .bind(action.issued_by)
+        .bind(&action.message)
+        .bind(action.require_ack)
+        .bind(now)
+        .bind(action.delivered.then_some(now))
+        .fetch_one(&self.0)
+        .await
+        .map(|x| x.0)
+    }
+
+    /// Returns all notices sent to the user, newest first
+    pub async fn get_notices(&self, account_id: i32) -> Result<Vec<StoredNotice>> {
+        query_as::<_, StoredNoticeWrapper>("SELECT * FROM notices WHERE account_id = ? ORDER BY notice_id DESC")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await
+            .map(|x| x.into_iter().map(|x| x.0).collect())
+    }
+
+    pub async fn get_undelivered_notices(&self, account_id: i32) -> Result<Vec<StoredNotice>> {
+        query_as::<_, StoredNoticeWrapper>("SELECT * FROM notices WHERE account_id = ? AND delivered_at IS NULL ORDER BY notice_id")
+            .bind(account_id)
+            .fetch_all(&self.0)
+            .await
+            .map(|x| x.into_iter().map(|x| x.0).collect())
+    }
+
+    pub async fn mark_notices_delivered(&self, account_id: i32, ids: &[i64]) -> Result<()> {
+        let now = i64::try_from(UNIX_EPOCH.elapsed().unwrap().as_secs()).unwrap_or(0);
+
+        for id in ids {
+            query("UPDATE notices SET delivered_at = ? WHERE notice_id = ? AND account_id = ? AND delivered_at IS NULL")
+                .bind(now)
+                .bind(id)
+                .bind(account_id)
+                .execute(&self.0)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Acknowledges a notice, only works if the notice was sent to this user and requires an acknowledgement
+    pub async fn acknowledge_notice(&self, account_id: i32, id: i64) -> Result<()> {
+        query("UPDATE notices SET acked_at = ? WHERE notice_id = ? AND account_id = ? AND require_ack = 1 AND acked_at IS NULL")
+            .bind(i64::try_from(UNIX_EPOCH.elapsed().unwrap().as_secs()).unwrap_or(0))
+            .bind(id)
+            .bind(account_id)
+            .execute(&self.0)
+            .await
+            .map(|_| ())
+    }
+
     // Misc
 
     pub async fn insert_player_count_history(&self, entries: &[(SystemTime, u32)]) -> Result<()> {
@@ -462,3 +1150,172 @@ impl GlobedDb {
         Ok(count > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    async fn test_db() -> GlobedDb {
+        // every connection to an in-memory database gets its own database, so only use one
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        GlobedDb(pool)
+    }
+
+    #[tokio::test]
+    async fn whitelist_application_dedup() {
+        let db = test_db().await;
+
+        assert!(db.create_whitelist_application(7, "Tester", "first").await.unwrap());
+        assert!(!db.create_whitelist_application(7, "Tester", "second").await.unwrap());
+
+        let pending = db.get_pending_application(7).await.unwrap().unwrap();
+        assert_eq!(pending.message, "first");
+
+        // other accounts are not affected
+        assert!(db.create_whitelist_application(8, "Other", "hi").await.unwrap());
+
+        // after a review, the user can apply again
+        assert!(db.review_whitelist_application(7, 1, false, "no").await.unwrap());
+        assert!(!db.review_whitelist_application(7, 1, false, "no").await.unwrap());
+        assert!(db.get_pending_application(7).await.unwrap().is_none());
+        assert!(db.create_whitelist_application(7, "Tester", "third").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn pending_applications_are_paginated() {
+        let db = test_db().await;
+
+        for account_id in 0..30 {
+            db.create_whitelist_application(account_id, "Tester", "hi").await.unwrap();
+        }
+
+        db.review_whitelist_application(0, 1, true, "ok").await.unwrap();
+        assert!(db.get_user(0).await.unwrap().unwrap().is_whitelisted);
+
+        let first = db.get_pending_applications(0).await.unwrap();
+        let second = db.get_pending_applications(1).await.unwrap();
+
+        assert_eq!(first.len(), APPLICATIONS_PAGE_SIZE);
+        assert_eq!(second.len(), 29 - APPLICATIONS_PAGE_SIZE);
+        assert!(db.get_pending_applications(2).await.unwrap().is_empty());
+
+        let ids = first.iter().chain(second.iter()).map(|x| x.account_id).collect::<Vec<_>>();
+        assert_eq!(ids, (1..30).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn role_grants_expire() {
+        let db = test_db().await;
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        let grant = |role_id: &str, expires_at: u64| AdminGrantRoleAction {
+            issued_by: 1,
+            account_id: 10,
+            role_id: role_id.to_owned(),
+            expires_at,
+        };
+
+        db.update_user_roles(10, &["vip".to_owned()]).await.unwrap();
+        db.grant_role(&grant("helper", now + 3600)).await.unwrap();
+        db.grant_role(&grant("mod", now - 1)).await.unwrap();
+
+        // expired grants are removed when the user is loaded
+        let user = db.get_user(10).await.unwrap().unwrap();
+        assert_eq!(user.user_roles, ["vip", "helper"]);
+        assert_eq!(user.role_grants.len(), 1);
+        assert_eq!(user.role_grants[0].role_id, "helper");
+
+        // granting the same role again replaces the expiry
+        db.grant_role(&grant("helper", now + 7200)).await.unwrap();
+        let user = db.get_user(10).await.unwrap().unwrap();
+        assert_eq!(user.role_grants.len(), 1);
+        assert_eq!(user.role_grants[0].expires_at, now + 7200);
+    }
+
+    #[tokio::test]
+    async fn update_user_roles_keeps_grants_temporary() {
+        let db = test_db().await;
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        db.grant_role(&AdminGrantRoleAction {
+            issued_by: 1,
+            account_id: 10,
+            role_id: "helper".to_owned(),
+            expires_at: now + 3600,
+        })
+        .await
+        .unwrap();
+
+        // the granted role must not become permanent when the roles are saved
+        db.update_user_roles(10, &["helper".to_owned(), "vip".to_owned()]).await.unwrap();
+        let user_roles: Option<String> = query_scalar("SELECT user_roles FROM users WHERE account_id = 10")
+            .fetch_one(&db.0)
+            .await
+            .unwrap();
+        assert_eq!(user_roles.as_deref(), Some("vip"));
+        assert_eq!(db.get_user(10).await.unwrap().unwrap().user_roles, ["vip", "helper"]);
+
+        // and leaving it out revokes the grant
+        db.update_user_roles(10, &["vip".to_owned()]).await.unwrap();
+        let user = db.get_user(10).await.unwrap().unwrap();
+        assert_eq!(user.user_roles, ["vip"]);
+        assert!(user.role_grants.is_empty());
+    }
+
+    fn invite_code_action(max_uses: u32, expires_at: u64, role_id: Option<&str>) -> AdminCreateInviteCodeAction {
+        AdminCreateInviteCodeAction {
+            issued_by: 1,
+            max_uses,
+            expires_at,
+            role_id: role_id.map(ToOwned::to_owned),
+        }
+    }
+
+    #[tokio::test]
+    async fn invite_code_max_uses() {
+        let db = test_db().await;
+        db.create_invite_code("ABCD", &invite_code_action(2, 0, None)).await.unwrap();
+
+        assert!(db.redeem_invite_code("ABCD", 10).await.unwrap());
+        assert!(db.redeem_invite_code("ABCD", 11).await.unwrap());
+        assert!(!db.redeem_invite_code("ABCD", 12).await.unwrap());
+
+        assert_eq!(db.get_invite_code("ABCD").await.unwrap().unwrap().uses, 2);
+        assert!(db.get_user(11).await.unwrap().unwrap().is_whitelisted);
+
+        // a failed redemption must not leave anything behind
+        assert!(db.get_user(12).await.unwrap().is_none());
+        assert!(!db.redeem_invite_code("NOPE", 12).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn invite_code_expiry() {
+        let db = test_db().await;
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        db.create_invite_code("OLD", &invite_code_action(5, now - 1, None)).await.unwrap();
+        db.create_invite_code("NEW", &invite_code_action(5, now + 3600, None)).await.unwrap();
+
+        assert!(!db.redeem_invite_code("OLD", 10).await.unwrap());
+        assert_eq!(db.get_invite_code("OLD").await.unwrap().unwrap().uses, 0);
+        assert!(db.redeem_invite_code("NEW", 10).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn invite_code_redeemed_twice() {
+        let db = test_db().await;
+        db.create_invite_code("ABCD", &invite_code_action(5, 0, Some("tester"))).await.unwrap();
+        db.update_user_roles(10, &["mod".to_owned()]).await.unwrap();
+
+        assert!(db.redeem_invite_code("ABCD", 10).await.unwrap());
+        assert!(!db.redeem_invite_code("ABCD", 10).await.unwrap());
+
+        // the second attempt must not use up a slot or add the role again
+        assert_eq!(db.get_invite_code("ABCD").await.unwrap().unwrap().uses, 1);
+        assert_eq!(db.get_user(10).await.unwrap().unwrap().user_roles, ["mod", "tester"]);
+    }
+}
@@ -1,6 +1,7 @@
-use std::{net::IpAddr, sync::OnceLock};
+use std::net::IpAddr;
 
-use ipnet::{Ipv4Net, Ipv6Net};
+use globed_shared::anyhow::{self, anyhow};
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
 use iprange::IpRange;
 
 pub struct IpBlocker {
@@ -9,32 +10,30 @@ pub struct IpBlocker {
 }
 
 impl IpBlocker {
-    pub fn instance() -> &'static Self {
-        static INSTANCE: OnceLock<IpBlocker> = OnceLock::new();
-        INSTANCE.get_or_init(|| {
-            let contents = include_str!("allowed_ranges.txt");
-            let mut v4 = Vec::new();
-            let mut v6 = Vec::new();
-
-            for line in contents.lines() {
-                let line = line.trim().to_lowercase();
-                if line.is_empty() || line.starts_with('#') || !line.contains(' ') {
-                    continue;
-                }
+    /// Cloudflare's IP ranges, used when the Cloudflare header is enabled and no trusted proxies are configured
+    pub fn cloudflare() -> Self {
+        let contents = include_str!("allowed_ranges.txt");
+        let mut v4 = Vec::new();
+        let mut v6 = Vec::new();
+
+        for line in contents.lines() {
+            let line = line.trim().to_lowercase();
+            if line.is_empty() || line.starts_with('#') || !line.contains(' ') {
+                continue;
+            }
 
-                let (proto, range) = line.split_once(' ').unwrap();
+            let (proto, range) = line.split_once(' ').unwrap();
 
-                if proto == "v4" {
-                    v4.push(range.to_string());
-                } else if proto == "v6" {
-                    v6.push(range.to_string());
-                } else {
-                    eprintln!("ignoring invalid IP address entry: {line}");
-                }
+            if proto == "v4" {
+                v4.push(range.to_string());
+            } else if proto == "v6" {
+                v6.push(range.to_string());
+            } else {
+                eprintln!("ignoring invalid IP address entry: {line}");
             }
+        }
 
-            Self::new(&v4, &v6)
-        })
+        Self::new(&v4, &v6)
     }
 
     pub fn new(v4: &[String], v6: &[String]) -> Self {
@@ -44,6 +43,34 @@ impl IpBlocker {
         Self { range_v4, range_v6 }
     }
 
+    /// Builds a blocker from a list of IPv4 or IPv6 ranges in CIDR notation, single addresses are also accepted
+    pub fn from_ranges(ranges: &[String]) -> anyhow::Result<Self> {
+        let mut range_v4 = IpRange::new();
+        let mut range_v6 = IpRange::new();
+
+        for range in ranges {
+            let range = range.trim();
+            let net = range
+                .parse::<IpNet>()
+                .or_else(|_| range.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| anyhow!("invalid IP range: '{range}'"))?;
+
+            match net {
+                IpNet::V4(net) => {
+                    range_v4.add(net);
+                }
+                IpNet::V6(net) => {
+                    range_v6.add(net);
+                }
+            }
+        }
+
+        range_v4.simplify();
+        range_v6.simplify();
+
+        Ok(Self { range_v4, range_v6 })
+    }
+
     pub fn is_allowed(&self, address: &IpAddr) -> bool {
         match address {
             IpAddr::V4(addr) => self.range_v4.contains(addr),
@@ -33,10 +33,16 @@ pub mod config;
 pub mod db;
 pub mod game_pinger;
 pub mod ip_blocker;
+pub mod rate_limiter;
 pub mod state;
 pub mod verifier;
 pub mod web;
 
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "bin/mock-gd-api.rs"]
+mod mock_gd_api;
+
 fn abort_misconfig() -> ! {
     error!("aborting launch due to misconfiguration.");
     std::process::exit(1);
@@ -81,7 +87,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         config_path = config_path.join("central-conf.json");
     }
 
-    let config = if config_path.exists() && config_path.is_file() {
+    let mut config = if config_path.exists() && config_path.is_file() {
         match ServerConfig::load(&config_path) {
             Ok(x) => x,
             Err(err) => {
@@ -116,15 +122,47 @@ async fn main() -> Result<(), Box<dyn Error>> {
         abort_misconfig();
     }
 
+    if let Err(e) = config.resolve_roles() {
+        error!("invalid role found in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_persistent_rooms() {
+        error!("invalid persistent room found in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_chat_commands() {
+        error!("invalid chat command found in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_announcements() {
+        error!("invalid announcement found in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_retired_keys() {
+        error!("invalid retired key found in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_trusted_proxies() {
+        error!("invalid trusted proxies in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
+    if let Err(e) = config.validate_auth_rate_limits() {
+        error!("invalid auth rate limits in central-conf.json: {e}");
+        abort_misconfig();
+    }
+
     // stupid rust
 
     let mnt_point = config.web_mountpoint.clone();
 
-    let state_skey = config.secret_key.clone();
-    let state_skey2 = config.secret_key2.clone();
-
     let pinger = GameServerPinger::new(&config.game_servers).await;
-    let ssd = ServerStateData::new(config_path.clone(), config, &state_skey, &state_skey2);
+    let ssd = ServerStateData::new(config_path.clone(), config);
     let state = ServerState::new(ssd, pinger);
 
     // config file watcher
@@ -143,7 +181,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     info!("Successfully reloaded the configuration");
                     // set the maintenance flag appropriately
                     watcher_state.set_maintenance(state.config.maintenance);
+                    watcher_state.set_scheduled_maintenance(state.config.scheduled_maintenance);
                     watcher_state.inner.verifier.set_enabled(state.config.use_gd_api);
+                    state.update_signing_keys();
+                    state.update_trusted_proxies();
                 }
                 Err(err) => {
                     warn!("Failed to reload configuration: {}", err.to_string());
@@ -154,13 +195,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // account verification stuff
     let av_state = state.inner.clone();
-    let av_state2 = state.inner.clone();
-    tokio::spawn(async move {
-        av_state.verifier.run_refresher().await;
-    });
-
     tokio::spawn(async move {
-        av_state2.verifier.run_deleter().await;
+        av_state.verifier.run().await;
     });
 
     // woo
@@ -180,6 +216,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             interval.tick().await;
             let mut mtx = challenge_deleter_s.data.write().await;
             mtx.clear_outdated_challenges();
+            challenge_deleter_s.rate_limiter.clear_outdated(&mtx.config);
         }
     });
 
@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use globed_shared::SyncMutex;
+
+use crate::config::ServerConfig;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RateLimitKey {
+    Ip(IpAddr),
+    Account(i32),
+    /// An account, but only when used from a specific IP address
+    IpAccount(IpAddr, i32),
+}
+
+impl Display for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "IP {ip}"),
+            Self::Account(account_id) => write!(f, "account {account_id}"),
+            Self::IpAccount(ip, account_id) => write!(f, "account {account_id} from IP {ip}"),
+        }
+    }
+}
+
+pub struct RateLimited {
+    pub key: RateLimitKey,
+    pub retry_after: u64, // seconds
+    /// Whether this is the first rejected request since the limit was hit, so that it only gets reported once
+    pub first: bool,
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+struct RateLimitEntry {
+    window_start: Instant,
+    requests: u32,
+    failures: u32,
+    locked_until: Option<Instant>,
+    last_seen: Instant,
+}
+
+impl RateLimitEntry {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            requests: 0,
+            failures: 0,
+            locked_until: None,
+            last_seen: now,
+        }
+    }
+}
+
+/// Limits how often the auth routes can be used by a single IP address or account,
+/// and locks them out for an exponentially growing duration after repeated failed attempts.
+///
+/// Accounts themselves are never locked out, because anyone can claim to be any account before they are authenticated.
+/// Failed attempts are counted against the IP address and the pair of IP address and account instead.
+pub struct AuthRateLimiter {
+    entries: SyncMutex<HashMap<RateLimitKey, RateLimitEntry>>,
+}
+
+impl AuthRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            entries: SyncMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request for every given key, returns an error if any of them is over the limit or locked out.
+    /// If several keys are limited, the one that has to wait the longest is returned.
+    pub fn hit(&self, config: &ServerConfig, keys: &[RateLimitKey]) -> Result<(), RateLimited> {
+        self._hit(config, keys, Instant::now())
+    }
+
+    fn _hit(&self, config: &ServerConfig, keys: &[RateLimitKey], now: Instant) -> Result<(), RateLimited> {
+        let window = Duration::from_secs(config.auth_rate_limit_window);
+        let mut entries = self.entries.lock();
+        let mut result: Option<RateLimited> = None;
+
+        for key in keys {
+            let limit = match key {
+                RateLimitKey::Ip(_) => config.auth_rate_limit_ip,
+                RateLimitKey::Account(_) | RateLimitKey::IpAccount(..) => config.auth_rate_limit_account,
+            };
+
+            let entry = entries.entry(*key).or_insert_with(|| RateLimitEntry::new(now));
+            entry.last_seen = now;
+
+            let limited = if let Some(locked_until) = entry.locked_until
+                && locked_until > now
+            {
+                Some(RateLimited {
+                    key: *key,
+                    retry_after: ceil_secs(locked_until - now),
+                    first: false,
+                })
+            } else {
+                if now.duration_since(entry.window_start) >= window {
+                    entry.window_start = now;
+                    entry.requests = 0;
+                }
+
+                entry.requests = entry.requests.saturating_add(1);
+
+                // 0 means no limit
+                (limit != 0 && entry.requests > limit).then(|| RateLimited {
+                    key: *key,
+                    retry_after: ceil_secs(window.saturating_sub(now.duration_since(entry.window_start))),
+                    first: entry.requests == limit + 1,
+                })
+            };
+
+            if let Some(limited) = limited
+                && result.as_ref().is_none_or(|x| limited.retry_after > x.retry_after)
+            {
+                result = Some(limited);
+            }
+        }
+
+        result.map_or(Ok(()), Err)
+    }
+
+    /// Records a failed attempt for every given key, except for `Account` keys. Returns the key and the lockout duration
+    /// (in seconds) if this failure caused a new lockout.
+    pub fn record_failure(&self, config: &ServerConfig, keys: &[RateLimitKey]) -> Option<(RateLimitKey, u64)> {
+        self._record_failure(config, keys, Instant::now())
+    }
+
+    fn _record_failure(&self, config: &ServerConfig, keys: &[RateLimitKey], now: Instant) -> Option<(RateLimitKey, u64)> {
+        let mut entries = self.entries.lock();
+        let mut result = None;
+
+        for key in keys.iter().filter(|key| !matches!(key, RateLimitKey::Account(_))) {
+            let entry = entries.entry(*key).or_insert_with(|| RateLimitEntry::new(now));
+            entry.last_seen = now;
+            entry.failures = entry.failures.saturating_add(1);
+
+            if config.auth_lockout_threshold == 0 || entry.failures < config.auth_lockout_threshold {
+                continue;
+            }
+
+            // double the lockout with every failure past the threshold
+            let exponent = (entry.failures - config.auth_lockout_threshold).min(31);
+            let lockout = config.auth_lockout_base.saturating_mul(1 << exponent).min(config.auth_lockout_max);
+
+            entry.locked_until = Some(now + Duration::from_secs(lockout));
+
+            if result.as_ref().is_none_or(|(_, longest)| lockout > *longest) {
+                result = Some((*key, lockout));
+            }
+        }
+
+        result
+    }
+
+    /// Resets the failed attempts of every given key after a successful attempt
+    pub fn record_success(&self, keys: &[RateLimitKey]) {
+        let mut entries = self.entries.lock();
+
+        for key in keys {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.failures = 0;
+                entry.locked_until = None;
+            }
+        }
+    }
+
+    pub fn clear_outdated(&self, config: &ServerConfig) {
+        let now = Instant::now();
+        let retention = Duration::from_secs(config.auth_rate_limit_window.max(config.auth_lockout_max));
+
+        self.entries
+            .lock()
+            .retain(|_, entry| entry.locked_until.is_some_and(|x| x > now) || now.duration_since(entry.last_seen) < retention);
+    }
+}
+
+impl Default for AuthRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const IP: RateLimitKey = RateLimitKey::Ip(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+    const ACCOUNT: RateLimitKey = RateLimitKey::Account(7);
+    const IP_ACCOUNT: RateLimitKey = RateLimitKey::IpAccount(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)), 7);
+
+    fn config() -> ServerConfig {
+        ServerConfig {
+            auth_rate_limit_window: 60,
+            auth_rate_limit_ip: 3,
+            auth_rate_limit_account: 2,
+            auth_lockout_threshold: 2,
+            auth_lockout_base: 30,
+            auth_lockout_max: 100,
+            ..ServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn window_expiry() {
+        let config = config();
+        let limiter = AuthRateLimiter::new();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter._hit(&config, &[IP], start).is_ok());
+        }
+
+        let limited = limiter._hit(&config, &[IP], start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(limited.key, IP);
+        assert_eq!(limited.retry_after, 40);
+        assert!(limited.first);
+
+        // only the first rejection gets reported
+        assert!(!limiter._hit(&config, &[IP], start + Duration::from_secs(21)).unwrap_err().first);
+
+        // a new window starts once the old one is over
+        assert!(limiter._hit(&config, &[IP], start + Duration::from_secs(60)).is_ok());
+    }
+
+    #[test]
+    fn hit_checks_every_key() {
+        let config = config();
+        let limiter = AuthRateLimiter::new();
+        let now = Instant::now();
+
+        assert!(limiter._hit(&config, &[IP, ACCOUNT], now).is_ok());
+        assert!(limiter._hit(&config, &[IP, ACCOUNT], now).is_ok());
+
+        // the account is over its limit, the request still counts for the IP
+        assert_eq!(limiter._hit(&config, &[IP, ACCOUNT], now).unwrap_err().key, ACCOUNT);
+        assert_eq!(limiter._hit(&config, &[IP], now).unwrap_err().key, IP);
+
+        // the key with the longest wait is reported
+        for _ in 0..4 {
+            limiter._record_failure(&config, &[IP_ACCOUNT], now);
+        }
+
+        assert_eq!(limiter._hit(&config, &[IP, ACCOUNT, IP_ACCOUNT], now).unwrap_err().key, IP_ACCOUNT);
+    }
+
+    #[test]
+    fn lockout() {
+        let config = config();
+        let limiter = AuthRateLimiter::new();
+        let now = Instant::now();
+
+        assert_eq!(limiter._record_failure(&config, &[IP, IP_ACCOUNT], now), None);
+        assert_eq!(limiter._record_failure(&config, &[IP_ACCOUNT], now), Some((IP_ACCOUNT, 30)));
+
+        let limited = limiter._hit(&config, &[IP_ACCOUNT], now + Duration::from_secs(10)).unwrap_err();
+        assert_eq!(limited.key, IP_ACCOUNT);
+        assert_eq!(limited.retry_after, 20);
+        assert!(!limited.first);
+
+        // the lockout doubles with every further failure, up to the maximum
+        assert_eq!(limiter._record_failure(&config, &[IP, IP_ACCOUNT], now), Some((IP_ACCOUNT, 60)));
+        assert_eq!(limiter._record_failure(&config, &[IP_ACCOUNT], now), Some((IP_ACCOUNT, 100)));
+
+        // and expires after that
+        assert!(limiter._hit(&config, &[IP_ACCOUNT], now + Duration::from_secs(100)).is_ok());
+    }
+
+    #[test]
+    fn accounts_are_not_locked_out() {
+        let config = config();
+        let limiter = AuthRateLimiter::new();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(limiter._record_failure(&config, &[ACCOUNT], now), None);
+        }
+
+        assert!(limiter._hit(&config, &[ACCOUNT], now).is_ok());
+    }
+
+    #[test]
+    fn lockout_disabled() {
+        let config = ServerConfig {
+            auth_lockout_threshold: 0,
+            ..config()
+        };
+
+        let limiter = AuthRateLimiter::new();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(limiter._record_failure(&config, &[IP], now), None);
+        }
+
+        assert!(limiter._hit(&config, &[IP], now).is_ok());
+    }
+
+    #[test]
+    fn reset_after_success() {
+        let config = config();
+        let limiter = AuthRateLimiter::new();
+        let now = Instant::now();
+
+        limiter._record_failure(&config, &[IP], now);
+        limiter._record_failure(&config, &[IP], now);
+        assert!(limiter._hit(&config, &[IP], now).is_err());
+
+        limiter.record_success(&[IP]);
+        assert!(limiter._hit(&config, &[IP], now).is_ok());
+
+        // the failures start counting from zero again
+        assert_eq!(limiter._record_failure(&config, &[IP], now), None);
+        assert_eq!(limiter._record_failure(&config, &[IP], now), Some((IP, 30)));
+    }
+}
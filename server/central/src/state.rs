@@ -7,11 +7,11 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
-    time::{Duration, SystemTime},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use globed_shared::{
-    TokenIssuer, anyhow,
+    ScheduledMaintenance, SyncMutex, TokenIssuer, anyhow,
     base64::{Engine, engine::general_purpose::STANDARD as b64e},
     crypto_box::aead::{AeadMutInPlace, generic_array::GenericArray},
     crypto_secretbox::{KeyInit as _, XSalsa20Poly1305},
@@ -22,7 +22,14 @@ use globed_shared::{
 };
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use crate::{config::ServerConfig, db::GlobedDb, game_pinger::GameServerPinger, verifier::AccountVerifier};
+use crate::{
+    config::{ProxyHeader, ServerConfig},
+    db::GlobedDb,
+    game_pinger::GameServerPinger,
+    ip_blocker::IpBlocker,
+    rate_limiter::AuthRateLimiter,
+    verifier::{AccountVerifier, GdMessageBackend},
+};
 use blake2::{Blake2b, Digest};
 use digest::consts::U32;
 
@@ -48,19 +55,19 @@ pub struct ServerStateData {
     pub config_path: PathBuf,
     pub config: ServerConfig,
     pub hmac: Hmac<Sha256>,
+    pub retired_hmacs: Vec<(Hmac<Sha256>, u64)>, // hmac, expires at
     pub token_issuer: TokenIssuer,
     pub active_challenges: HashMap<IpAddr, ActiveChallenge>,
     pub challenge_pubkey: GenericArray<u8, U32>,
     pub challenge_box: XSalsa20Poly1305,
+    pub trusted_proxies: IpBlocker,
     pub http_client: reqwest::Client,
     pub last_logins: HashMap<u64, LoginEntry>, // { hash of lowercase username : entry }
 }
 
 impl ServerStateData {
-    pub fn new(config_path: PathBuf, config: ServerConfig, secret_key: &str, token_secret_key: &str) -> Self {
-        let skey_bytes = secret_key.as_bytes();
-
-        let hmac = Hmac::<Sha256>::new_from_slice(skey_bytes).unwrap();
+    pub fn new(config_path: PathBuf, config: ServerConfig) -> Self {
+        let hmac = Hmac::<Sha256>::new_from_slice(config.secret_key.as_bytes()).unwrap();
         let mut challenge_pubkey = GenericArray::<u8, U32>::default();
         rand::rng().fill_bytes(&mut challenge_pubkey);
         let challenge_box = XSalsa20Poly1305::new(&challenge_pubkey);
@@ -74,17 +81,50 @@ impl ServerStateData {
             .build()
             .unwrap();
 
-        Self {
+        let token_issuer = TokenIssuer::new(&config.secret_key2, token_expiry);
+
+        let mut state = Self {
             config_path,
             config,
             hmac,
-            token_issuer: TokenIssuer::new(token_secret_key, token_expiry),
+            retired_hmacs: Vec::new(),
+            token_issuer,
             active_challenges: HashMap::new(),
             challenge_pubkey,
             challenge_box,
+            trusted_proxies: IpBlocker::new(&[], &[]),
             http_client,
             last_logins: HashMap::new(),
-        }
+        };
+
+        state.update_signing_keys();
+        state.update_trusted_proxies();
+        state
+    }
+
+    /// Applies the signing keys and token expiry from the config, so that keys can be rotated without a restart
+    pub fn update_signing_keys(&mut self) {
+        self.hmac = Hmac::<Sha256>::new_from_slice(self.config.secret_key.as_bytes()).unwrap();
+        self.retired_hmacs = self
+            .config
+            .retired_secret_keys
+            .iter()
+            .map(|x| (Hmac::<Sha256>::new_from_slice(x.key.as_bytes()).unwrap(), x.expires_at))
+            .collect();
+
+        self.token_issuer.set_secret_key(&self.config.secret_key2);
+        self.token_issuer.set_retired_keys(&self.config.retired_secret_keys2);
+        self.token_issuer.set_expiration_period(Duration::from_secs(self.config.token_expiry));
+    }
+
+    /// Rebuilds the list of trusted proxies from the config, Cloudflare's ranges are used if none are set
+    pub fn update_trusted_proxies(&mut self) {
+        self.trusted_proxies = if self.config.trusted_proxies.is_empty() && self.config.effective_proxy_header() == ProxyHeader::CfConnectingIp {
+            IpBlocker::cloudflare()
+        } else {
+            // ranges are validated when the config is loaded
+            IpBlocker::from_ranges(&self.config.trusted_proxies).unwrap()
+        };
     }
 
     /// create a new challenge, return the rand string the user must use to solve the challenge
@@ -135,19 +175,23 @@ impl ServerStateData {
 
     // uses hmac-sha256 to derive an auth key from user's account ID and name
     pub fn generate_authkey(&self, account_id: i32, user_id: i32, account_name: &str) -> Vec<u8> {
+        Self::_generate_authkey(&self.hmac, account_id, user_id, account_name)
+    }
+
+    fn _generate_authkey(hmac: &Hmac<Sha256>, account_id: i32, user_id: i32, account_name: &str) -> Vec<u8> {
         use globed_shared::hmac::Mac;
 
         let val = format!("{account_id}:{user_id}:{account_name}");
 
-        let mut hmac: Hmac<Sha256> = self.hmac.clone();
+        let mut hmac: Hmac<Sha256> = hmac.clone();
         hmac.update(val.as_bytes());
         let res = hmac.finalize();
         res.into_bytes().to_vec()
     }
 
     /// `generate_authkey` plus blake2b on top
-    pub fn generate_hashed_authkey(&self, account_id: i32, user_id: i32, account_name: &str) -> [u8; 32] {
-        let ak = self.generate_authkey(account_id, user_id, account_name);
+    fn _generate_hashed_authkey(hmac: &Hmac<Sha256>, account_id: i32, user_id: i32, account_name: &str) -> [u8; 32] {
+        let ak = Self::_generate_authkey(hmac, account_id, user_id, account_name);
 
         let mut hasher = Blake2b::<U32>::new();
         hasher.update(&ak);
@@ -156,6 +200,23 @@ impl ServerStateData {
         output.into()
     }
 
+    /// Checks the hashed authkey against the current key, and the retired keys that have not expired yet
+    pub fn verify_hashed_authkey(&self, account_id: i32, user_id: i32, account_name: &str, authkey: &[u8]) -> bool {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        let retired = self
+            .retired_hmacs
+            .iter()
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(hmac, _)| hmac);
+
+        std::iter::once(&self.hmac).chain(retired).any(|hmac| {
+            let valid_authkey = Self::_generate_hashed_authkey(hmac, account_id, user_id, account_name);
+
+            authkey.len() == valid_authkey.len() && authkey.iter().zip(valid_authkey.iter()).all(|(c1, c2)| *c1 == *c2)
+        })
+    }
+
     pub fn verify_challenge(&self, orig_value: &ActiveChallenge, answer: &str) -> bool {
         orig_value.answer == answer
     }
@@ -255,12 +316,15 @@ impl ServerStateData {
 pub struct InnerServerState {
     pub data: RwLock<ServerStateData>,
     pub maintenance: AtomicBool,
+    pub scheduled_maintenance: SyncMutex<Option<ScheduledMaintenance>>,
     pub verifier: AccountVerifier,
     pub pinger: GameServerPinger,
+    pub rate_limiter: AuthRateLimiter,
 }
 
 impl InnerServerState {
     pub fn new(ssd: ServerStateData, pinger: GameServerPinger, maintenance: bool) -> Self {
+        let scheduled_maintenance = ssd.config.scheduled_maintenance;
         let gd_api_account = ssd.config.gd_api_account;
         let gd_api_gjp = ssd.config.gd_api_gjp.clone();
         let base_api_url = ssd.config.gd_api_url.clone();
@@ -268,13 +332,16 @@ impl InnerServerState {
         let ignore_name_mismatch = ssd.config.skip_name_check;
         let flush_period = Duration::from_millis(ssd.config.refresh_interval);
 
-        let verifier = AccountVerifier::new(gd_api_account, gd_api_gjp, base_api_url, use_gd_api, ignore_name_mismatch, flush_period);
+        let backend = GdMessageBackend::new(gd_api_account, gd_api_gjp, base_api_url, ignore_name_mismatch, flush_period);
+        let verifier = AccountVerifier::new(Box::new(backend), use_gd_api);
 
         Self {
             data: RwLock::new(ssd),
             maintenance: AtomicBool::new(maintenance),
+            scheduled_maintenance: SyncMutex::new(scheduled_maintenance),
             verifier,
             pinger,
+            rate_limiter: AuthRateLimiter::new(),
         }
     }
 
@@ -307,14 +374,35 @@ impl ServerState {
         self.inner.state_write().await
     }
 
+    /// Returns whether the server is under maintenance, either manually or because of a scheduled maintenance window
     pub fn maintenance(&self) -> bool {
-        self.inner.maintenance.load(Ordering::SeqCst)
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        self.inner.maintenance.load(Ordering::SeqCst) || self.scheduled_maintenance().is_some_and(|x| x.is_active(now))
     }
 
     pub fn set_maintenance(&self, state: bool) {
         self.inner.maintenance.store(state, Ordering::SeqCst);
     }
 
+    pub fn scheduled_maintenance(&self) -> Option<ScheduledMaintenance> {
+        *self.inner.scheduled_maintenance.lock()
+    }
+
+    pub fn set_scheduled_maintenance(&self, maintenance: Option<ScheduledMaintenance>) {
+        *self.inner.scheduled_maintenance.lock() = maintenance;
+    }
+
+    pub fn maintenance_message(&self) -> String {
+        let mut message = "The server is currently under maintenance, please try connecting again later".to_owned();
+
+        if let Some(end) = self.scheduled_maintenance().and_then(|x| x.describe_end(UNIX_EPOCH.elapsed().unwrap().as_secs())) {
+            message.push_str(&format!(" (it is {end})"));
+        }
+
+        message
+    }
+
     pub fn get_verifier(&self) -> &AccountVerifier {
         &self.inner.verifier
     }
@@ -10,6 +10,8 @@ use globed_shared::{
     *,
 };
 
+use super::{BoxFuture, VerificationBackend};
+
 const MICRO_SLEEP_PERIOD: Duration = Duration::from_millis(250);
 
 #[derive(Clone)]
@@ -23,7 +25,9 @@ struct AccountEntry {
     pub age: String,
 }
 
-pub struct AccountVerifier {
+/// Verifies accounts by checking the messages sent to a bot account on the GD servers (or a GDPS).
+/// The user proves they own the account by sending a message with the challenge answer to the bot.
+pub struct GdMessageBackend {
     http_client: reqwest::Client,
     account_id: i32,
     account_gjp: String,
@@ -31,21 +35,13 @@ pub struct AccountVerifier {
     message_cache: SyncMutex<Vec<AccountEntry>>,
     last_update: SyncMutex<SystemTime>,
     outdated_messages: SyncMutex<IntSet<i32>>,
-    is_enabled: AtomicBool,
     ignore_name_mismatch: bool,
     flush_period: Duration,
     delete_period: Duration,
 }
 
-impl AccountVerifier {
-    pub fn new(
-        account_id: i32,
-        account_gjp: String,
-        mut base_api_url: String,
-        enabled: bool,
-        ignore_name_mismatch: bool,
-        flush_period: Duration,
-    ) -> Self {
+impl GdMessageBackend {
+    pub fn new(account_id: i32, account_gjp: String, mut base_api_url: String, ignore_name_mismatch: bool, flush_period: Duration) -> Self {
         let http_client = reqwest::ClientBuilder::new()
             .use_rustls_tls()
             .danger_accept_invalid_certs(true)
@@ -66,22 +62,13 @@ impl AccountVerifier {
             message_cache: SyncMutex::new(Vec::new()),
             last_update: SyncMutex::new(SystemTime::now()),
             outdated_messages: SyncMutex::new(IntSet::default()),
-            is_enabled: AtomicBool::new(enabled),
             ignore_name_mismatch,
             flush_period,
             delete_period: flush_period * 15,
         }
     }
 
-    pub fn set_enabled(&self, state: bool) {
-        self.is_enabled.store(state, Ordering::Relaxed);
-    }
-
-    pub async fn verify_account(&self, account_id: i32, user_id: i32, account_name: &str, authcode: &str) -> Result<i32, String> {
-        if !self.is_enabled.load(Ordering::Relaxed) {
-            return Ok(0);
-        }
-
+    async fn _verify_account(&self, account_id: i32, user_id: i32, account_name: &str, authcode: &str) -> Result<Option<i32>, String> {
         let request_time = SystemTime::now();
 
         let mut passed_time = Duration::new(0, 0);
@@ -123,7 +110,7 @@ impl AccountVerifier {
                 if msg.account_id == account_id && msg.user_id == user_id {
                     has_matching_ids = true;
                     if self.ignore_name_mismatch || msg.name.eq_ignore_ascii_case(account_name) {
-                        return Ok(msg.message_id);
+                        return Ok(Some(msg.message_id));
                     }
 
                     // if the name didnt match, set the mismatched name
@@ -149,7 +136,7 @@ impl AccountVerifier {
         }
     }
 
-    pub async fn run_refresher(&self) {
+    async fn run_refresher(&self, enabled: &AtomicBool) {
         let mut interval = tokio::time::interval(self.flush_period);
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Burst);
         interval.tick().await;
@@ -157,7 +144,7 @@ impl AccountVerifier {
         loop {
             interval.tick().await;
 
-            if !self.is_enabled.load(Ordering::Relaxed) {
+            if !enabled.load(Ordering::Relaxed) {
                 continue;
             }
 
@@ -197,14 +184,14 @@ impl AccountVerifier {
     }
 
     // run the task that periodically deletes the stale messages
-    pub async fn run_deleter(&self) {
+    async fn run_deleter(&self, enabled: &AtomicBool) {
         let mut interval = tokio::time::interval(self.delete_period);
         interval.tick().await;
 
         loop {
             interval.tick().await;
 
-            if !self.is_enabled.load(Ordering::Relaxed) {
+            if !enabled.load(Ordering::Relaxed) {
                 continue;
             }
 
@@ -374,6 +361,24 @@ impl AccountVerifier {
     }
 }
 
+impl VerificationBackend for GdMessageBackend {
+    fn verify_account<'a>(
+        &'a self,
+        account_id: i32,
+        user_id: i32,
+        account_name: &'a str,
+        authcode: &'a str,
+    ) -> BoxFuture<'a, Result<Option<i32>, String>> {
+        Box::pin(self._verify_account(account_id, user_id, account_name, authcode))
+    }
+
+    fn run<'a>(&'a self, enabled: &'a AtomicBool) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            tokio::join!(self.run_refresher(enabled), self.run_deleter(enabled));
+        })
+    }
+}
+
 fn parse_robtop_string(data: &str, separator: char) -> HashMap<&str, &str> {
     let pairs: Vec<&str> = data.split(separator).collect();
     let mut map = HashMap::new();
@@ -0,0 +1,59 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+mod gd_messages;
+
+pub use gd_messages::GdMessageBackend;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A method of verifying that a user owns the account they are logging in as.
+/// The user is given an authcode (the challenge answer), and has to prove they have it through some other channel.
+pub trait VerificationBackend: Send + Sync {
+    /// Waits for the proof and checks it. On success, optionally returns a message ID that the client should delete afterwards.
+    fn verify_account<'a>(
+        &'a self,
+        account_id: i32,
+        user_id: i32,
+        account_name: &'a str,
+        authcode: &'a str,
+    ) -> BoxFuture<'a, Result<Option<i32>, String>>;
+
+    /// Runs the background tasks of this backend, if it has any. Should never return.
+    fn run<'a>(&'a self, _enabled: &'a AtomicBool) -> BoxFuture<'a, ()> {
+        Box::pin(std::future::pending())
+    }
+}
+
+pub struct AccountVerifier {
+    backend: Box<dyn VerificationBackend>,
+    is_enabled: AtomicBool,
+}
+
+impl AccountVerifier {
+    pub fn new(backend: Box<dyn VerificationBackend>, enabled: bool) -> Self {
+        Self {
+            backend,
+            is_enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    pub fn set_enabled(&self, state: bool) {
+        self.is_enabled.store(state, Ordering::Relaxed);
+    }
+
+    pub async fn verify_account(&self, account_id: i32, user_id: i32, account_name: &str, authcode: &str) -> Result<Option<i32>, String> {
+        if !self.is_enabled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        self.backend.verify_account(account_id, user_id, account_name, authcode).await
+    }
+
+    pub async fn run(&self) {
+        self.backend.run(&self.is_enabled).await;
+    }
+}
@@ -11,7 +11,7 @@ use tokio::io::AsyncReadExt;
 
 // this isnt really meant to be secret but yeah
 // the skids can have fun with this :D
-const FUNNY_STRING: &str = "bff252d2731a6c6ca26d7f5144bc750fd6723316619f86c8636ebdc13bf3214c";
+pub(crate) const FUNNY_STRING: &str = "bff252d2731a6c6ca26d7f5144bc750fd6723316619f86c8636ebdc13bf3214c";
 
 // Request guard for JSON data that is encrypted
 
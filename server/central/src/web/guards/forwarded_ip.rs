@@ -0,0 +1,27 @@
+use super::*;
+
+// raw values of the headers that reverse proxies use to pass the client ip address,
+// they can only be trusted if the request came from a trusted proxy
+pub struct ForwardedIpGuard {
+    pub cf_connecting_ip: Option<String>,
+    pub x_forwarded_for: Option<String>,
+    pub x_real_ip: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ForwardedIpGuard {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+
+        // proxies may add the header multiple times instead of appending to the existing one
+        let x_forwarded_for = headers.get("X-Forwarded-For").collect::<Vec<_>>();
+
+        Outcome::Success(ForwardedIpGuard {
+            cf_connecting_ip: headers.get_one("CF-Connecting-IP").map(ToOwned::to_owned),
+            x_forwarded_for: (!x_forwarded_for.is_empty()).then(|| x_forwarded_for.join(",")),
+            x_real_ip: headers.get_one("X-Real-IP").map(ToOwned::to_owned),
+        })
+    }
+}
@@ -1,5 +1,3 @@
-use std::net::IpAddr;
-
 use rocket::{
     http::Status,
     request::{FromRequest, Outcome},
@@ -12,9 +10,6 @@ pub(crate) use check_protocol_::check_protocol;
 pub mod client_user_agent;
 pub use client_user_agent::ClientUserAgentGuard;
 
-pub mod cloudflare_ip;
-pub use cloudflare_ip::CloudflareIPGuard;
-
 pub mod game_server_password;
 pub use game_server_password::GameServerPasswordGuard;
 
@@ -26,3 +21,6 @@ pub use decodable::{CheckedDecodableGuard, DecodableGuard};
 
 pub mod encrypted_json;
 pub use encrypted_json::EncryptedJsonGuard;
+
+pub mod forwarded_ip;
+pub use forwarded_ip::ForwardedIpGuard;
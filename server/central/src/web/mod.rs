@@ -24,12 +24,17 @@ pub fn query_string(_req: &Request) -> &'static str {
 }
 
 pub mod routes {
+    pub mod admin_totp;
     pub mod auth;
     pub mod featured;
+    pub mod friends;
     pub mod game_server;
+    pub mod invites;
     pub mod meta;
+    pub mod notices;
     pub mod public;
     pub mod user;
+    pub mod whitelist;
 
     pub use super::*;
     pub use crate::{db::GlobedDb, state::ServerState};
@@ -47,6 +52,7 @@ pub mod routes {
             auth::totp_login,
             auth::challenge_new,
             auth::challenge_verify,
+            auth::whitelist_apply,
             featured::current,
             featured::history,
             featured::replace,
@@ -57,15 +63,38 @@ pub mod routes {
             user::update_username,
             user::update_name_color,
             user::update_roles,
+            user::update_grant_role,
             user::update_punish,
             user::update_unpunish,
             user::update_whitelist,
             user::update_admin_password,
             user::update_edit_punishment,
             user::get_punishment_history,
+            user::get_sessions,
+            user::update_revoke_sessions,
+            user::get_whitelist_applications,
+            user::update_review_application,
             user::get_many_user_names,
             user::p_user_lookup,
             user::p_sync_roles,
+            friends::get_friend_list,
+            friends::send_request,
+            friends::respond_request,
+            friends::remove,
+            friends::update_block,
+            notices::get_notices,
+            notices::store_notice,
+            notices::mark_delivered,
+            notices::acknowledge,
+            admin_totp::enroll,
+            admin_totp::confirm,
+            admin_totp::disable,
+            admin_totp::verify,
+            whitelist::applications,
+            whitelist::review,
+            invites::get_invite_codes,
+            invites::create_invite_code,
+            invites::delete_invite_code,
         ]
     }
 
@@ -73,7 +102,7 @@ pub mod routes {
         ($ctx:expr) => {
             if $ctx.maintenance() {
                 return Err(crate::web::MaintenanceResponder {
-                    inner: "The server is currently under maintenance, please try connecting again later",
+                    inner: $ctx.maintenance_message(),
                 }
                 .into());
             }
@@ -16,6 +16,7 @@ impl From<BadRequestResponder> for GenericErrorResponder<String> {
     fn from(value: BadRequestResponder) -> Self {
         GenericErrorResponder {
             inner: (Status::BadRequest, value.inner),
+            retry_after: None,
         }
     }
 }
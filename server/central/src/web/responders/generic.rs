@@ -1,8 +1,20 @@
 use super::*;
 
-#[derive(Responder)]
 pub struct GenericErrorResponder<T> {
     pub inner: (Status, T),
+    pub retry_after: Option<u64>,
+}
+
+impl<'r, 'o: 'r, T: Responder<'r, 'o>> Responder<'r, 'o> for GenericErrorResponder<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        let mut response = self.inner.respond_to(request)?;
+
+        if let Some(retry_after) = self.retry_after {
+            response.set_raw_header("Retry-After", retry_after.to_string());
+        }
+
+        Ok(response)
+    }
 }
 
 impl<T> From<T> for GenericErrorResponder<String>
@@ -12,6 +24,7 @@ where
     fn from(value: T) -> Self {
         GenericErrorResponder {
             inner: (Status::InternalServerError, value.to_string()),
+            retry_after: None,
         }
     }
 }
@@ -3,13 +3,14 @@ use super::*;
 #[derive(Responder)]
 #[response(status = 503, content_type = "text")]
 pub struct MaintenanceResponder {
-    pub inner: &'static str,
+    pub inner: String,
 }
 
 impl From<MaintenanceResponder> for GenericErrorResponder<String> {
     fn from(value: MaintenanceResponder) -> Self {
         GenericErrorResponder {
-            inner: (Status::ServiceUnavailable, value.inner.to_string()),
+            inner: (Status::ServiceUnavailable, value.inner),
+            retry_after: None,
         }
     }
 }
@@ -24,5 +24,8 @@ pub mod not_found_;
 pub(crate) use not_found_::not_found;
 pub use not_found_::NotFoundResponder;
 
+pub mod too_many_requests;
+pub use too_many_requests::TooManyRequestsResponder;
+
 pub mod encodable;
 pub use encodable::{CheckedEncodableResponder, EncodableResponder};
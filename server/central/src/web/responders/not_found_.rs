@@ -23,6 +23,7 @@ impl From<NotFoundResponder> for GenericErrorResponder<String> {
     fn from(value: NotFoundResponder) -> Self {
         GenericErrorResponder {
             inner: (Status::NotFound, value.inner),
+            retry_after: None,
         }
     }
 }
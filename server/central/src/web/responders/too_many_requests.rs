@@ -0,0 +1,24 @@
+use super::*;
+
+pub struct TooManyRequestsResponder {
+    pub inner: String,
+    pub retry_after: u64,
+}
+
+impl TooManyRequestsResponder {
+    pub fn new(inner: &str, retry_after: u64) -> Self {
+        Self {
+            inner: inner.to_owned(),
+            retry_after,
+        }
+    }
+}
+
+impl From<TooManyRequestsResponder> for GenericErrorResponder<String> {
+    fn from(value: TooManyRequestsResponder) -> Self {
+        GenericErrorResponder {
+            inner: (Status::TooManyRequests, value.inner),
+            retry_after: Some(value.retry_after),
+        }
+    }
+}
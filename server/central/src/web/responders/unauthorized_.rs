@@ -16,6 +16,7 @@ impl From<UnauthorizedResponder> for GenericErrorResponder<String> {
     fn from(value: UnauthorizedResponder) -> Self {
         GenericErrorResponder {
             inner: (Status::Unauthorized, value.inner),
+            retry_after: None,
         }
     }
 }
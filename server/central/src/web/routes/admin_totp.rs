@@ -0,0 +1,139 @@
+use std::time::UNIX_EPOCH;
+
+use globed_shared::{TotpVerifyData, logger::*, totp};
+use rocket::{State, post, serde::json::Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{db::GlobedDb, state::ServerState, web::*};
+
+// TOTP for logging into the admin panel. The global admin key cannot be used for any of these.
+
+#[derive(Deserialize)]
+pub struct TotpEnrollData {
+    account_id: i32,
+    admin_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct TotpCodeData {
+    account_id: i32,
+    admin_password: String,
+    code: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollResponse {
+    secret: String,
+    uri: String,
+}
+
+async fn _verify_admin_password(db: &GlobedDb, account_id: i32, password: &str) -> WebResult<()> {
+    let Some(user) = db.get_user(account_id).await? else {
+        unauthorized!("unauthorized (account)");
+    };
+
+    if !user.verify_password(password).unwrap_or(false) {
+        unauthorized!("unauthorized (password)");
+    }
+
+    Ok(())
+}
+
+// checks the code and makes sure it can't be used again
+pub(crate) async fn _check_code(db: &GlobedDb, account_id: i32, code: &str) -> WebResult<bool> {
+    let Some((secret, last_step)) = db.get_totp_secret(account_id).await? else {
+        return Ok(false);
+    };
+
+    let Some(step) = totp::verify(&secret, code, UNIX_EPOCH.elapsed().unwrap().as_secs()) else {
+        return Ok(false);
+    };
+
+    let step = step as i64;
+    if last_step.is_some_and(|x| x >= step) {
+        return Ok(false);
+    }
+
+    // the update only goes through if no other request used this step in the meantime
+    Ok(db.use_totp_step(account_id, step).await?)
+}
+
+/// Generates a new secret for the user, which has to be confirmed with a code before it is used
+#[post("/admin/totp/enroll", data = "<data>")]
+pub async fn enroll(
+    state: &State<ServerState>,
+    db: &GlobedDb,
+    _user_agent: ClientUserAgentGuard<'_>,
+    data: EncryptedJsonGuard<TotpEnrollData>,
+) -> WebResult<Json<TotpEnrollResponse>> {
+    _verify_admin_password(db, data.0.account_id, &data.0.admin_password).await?;
+
+    let Some(user) = db.get_user(data.0.account_id).await? else {
+        unauthorized!("unauthorized (account)");
+    };
+
+    if user.totp_enabled {
+        bad_request!("two-factor authentication is already enabled, disable it first");
+    }
+
+    let secret = totp::generate_secret();
+    db.set_totp_secret(data.0.account_id, &secret).await?;
+
+    let issuer = state.state_read().await.config.totp_issuer.clone();
+    let name = user.user_name.unwrap_or_else(|| data.0.account_id.to_string());
+    let uri = totp::provisioning_uri(&secret, &name, &issuer);
+
+    info!("{} ({}) started enrolling two-factor authentication", name, data.0.account_id);
+
+    Ok(Json(TotpEnrollResponse { secret, uri }))
+}
+
+#[post("/admin/totp/confirm", data = "<data>")]
+pub async fn confirm(db: &GlobedDb, _user_agent: ClientUserAgentGuard<'_>, data: EncryptedJsonGuard<TotpCodeData>) -> WebResult<()> {
+    _verify_admin_password(db, data.0.account_id, &data.0.admin_password).await?;
+
+    if !_check_code(db, data.0.account_id, &data.0.code).await? {
+        unauthorized!("invalid code");
+    }
+
+    db.enable_totp(data.0.account_id).await?;
+
+    info!("{} enabled two-factor authentication", data.0.account_id);
+
+    Ok(())
+}
+
+#[post("/admin/totp/disable", data = "<data>")]
+pub async fn disable(db: &GlobedDb, _user_agent: ClientUserAgentGuard<'_>, data: EncryptedJsonGuard<TotpCodeData>) -> WebResult<()> {
+    _verify_admin_password(db, data.0.account_id, &data.0.admin_password).await?;
+
+    if !_check_code(db, data.0.account_id, &data.0.code).await? {
+        unauthorized!("invalid code");
+    }
+
+    db.disable_totp(data.0.account_id).await?;
+
+    info!("{} disabled two-factor authentication", data.0.account_id);
+
+    Ok(())
+}
+
+/// Verifies a code for a user logging into the admin panel on a game server
+#[post("/gs/totp/verify", data = "<data>")]
+pub async fn verify(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    db: &GlobedDb,
+    data: CheckedDecodableGuard<TotpVerifyData>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let enabled = db.get_user(data.0.account_id).await?.is_some_and(|x| x.totp_enabled);
+    let valid = enabled && _check_code(db, data.0.account_id, &data.0.code).await?;
+
+    Ok(CheckedEncodableResponder::new(valid))
+}
@@ -1,25 +1,37 @@
-use std::{net::IpAddr, time::SystemTime};
+use std::{
+    net::IpAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use rocket::{State, post};
 use serde::Deserialize;
 
 use globed_shared::{
     MIN_CLIENT_VERSION,
-    anyhow::{self, anyhow},
     base64::{Engine as _, engine::general_purpose as b64e},
     crypto_box::aead::Aead,
     crypto_secretbox::{KeyInit, XSalsa20Poly1305},
+    generate_alphanum_string,
     logger::*,
+    webhook::{self, WebhookMessage},
 };
 
 use super::*;
-use crate::{config::UserlistMode, ip_blocker::IpBlocker, state::ActiveChallenge};
+use crate::{
+    config::{ProxyHeader, UserlistMode},
+    rate_limiter::{AuthRateLimiter, RateLimitKey},
+    state::{ActiveChallenge, ServerStateData},
+};
+
+const SESSION_ID_LENGTH: usize = 16;
+const MAX_APPLICATION_MESSAGE_LENGTH: usize = 200;
 
 #[derive(Debug, Deserialize)]
 pub struct AccountData {
     account_id: i32,
     user_id: i32,
     username: String,
+    invite_code: Option<String>, // used to join a whitelisted server without being whitelisted first
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +40,13 @@ pub struct TotpLoginData {
     authkey: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct WhitelistApplyData {
+    account_data: AccountData,
+    answer: String, // answer to a challenge started with `challenge_new`
+    message: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ChallengeFinishData {
     account_data: AccountData,
@@ -35,22 +54,219 @@ pub struct ChallengeFinishData {
     trust_token: Option<String>,
 }
 
-fn check_ip(ip: IpAddr, cfip: &CloudflareIPGuard, cloudflare: bool) -> WebResult<IpAddr> {
-    let user_ip: anyhow::Result<IpAddr> = if cloudflare && !cfg!(debug_assertions) {
-        // verify if the actual peer is cloudflare
-        if !IpBlocker::instance().is_allowed(&ip) {
-            warn!("blocking unknown non-cloudflare address: {}", ip);
-            unauthorized!("access is denied from this IP address");
+fn parse_ip(value: &str, header: &str) -> WebResult<IpAddr> {
+    match value.trim().parse::<IpAddr>() {
+        Ok(x) => Ok(x),
+        Err(_) => bad_request!(&format!("failed to parse the {header} header")),
+    }
+}
+
+fn check_ip(ip: IpAddr, headers: &ForwardedIpGuard, state: &ServerStateData) -> WebResult<IpAddr> {
+    // in debug builds requests are accepted from any peer, to make testing behind a local proxy easier
+    _check_ip(ip, headers, state, !cfg!(debug_assertions))
+}
+
+fn _check_ip(ip: IpAddr, headers: &ForwardedIpGuard, state: &ServerStateData, verify_peer: bool) -> WebResult<IpAddr> {
+    let proxy_header = state.config.effective_proxy_header();
+
+    if proxy_header == ProxyHeader::None {
+        return Ok(ip);
+    }
+
+    // verify if the actual peer is a trusted proxy
+    if verify_peer && !state.trusted_proxies.is_allowed(&ip) {
+        warn!("blocking request from an untrusted proxy: {}", ip);
+        unauthorized!("access is denied from this IP address");
+    }
+
+    match proxy_header {
+        ProxyHeader::None => Ok(ip),
+        ProxyHeader::CfConnectingIp => match &headers.cf_connecting_ip {
+            Some(x) => parse_ip(x, "CF-Connecting-IP"),
+            None => bad_request!("missing the CF-Connecting-IP header"),
+        },
+        ProxyHeader::XRealIp => match &headers.x_real_ip {
+            Some(x) => parse_ip(x, "X-Real-IP"),
+            None => bad_request!("missing the X-Real-IP header"),
+        },
+        ProxyHeader::XForwardedFor => {
+            let Some(forwarded_for) = &headers.x_forwarded_for else {
+                bad_request!("missing the X-Forwarded-For header");
+            };
+
+            // every proxy appends the address it received the request from, so the client is the rightmost untrusted address
+            let mut client_ip = ip;
+            for addr in forwarded_for.rsplit(',') {
+                client_ip = parse_ip(addr, "X-Forwarded-For")?;
+
+                if !state.trusted_proxies.is_allowed(&client_ip) {
+                    break;
+                }
+            }
+
+            Ok(client_ip)
         }
+    }
+}
 
-        cfip.0.ok_or(anyhow!("failed to parse the IP header from Cloudflare"))
-    } else {
-        Ok(cfip.0.unwrap_or(ip))
+fn send_admin_webhook(state: &ServerStateData, message: WebhookMessage) {
+    let webhook_url = state.config.admin_webhook_url.clone();
+    if webhook_url.is_empty() {
+        return;
+    }
+
+    let http_client = state.http_client.clone();
+
+    // don't make the client wait for the webhook
+    tokio::spawn(async move {
+        if let Err(e) = webhook::send_webhook_messages(http_client, &webhook_url, &[message], None).await {
+            warn!("error sending webhook message: {e:?}");
+        }
+    });
+}
+
+fn check_rate_limit(rate_limiter: &AuthRateLimiter, state: &ServerStateData, keys: &[RateLimitKey]) -> WebResult<()> {
+    let Err(limited) = rate_limiter.hit(&state.config, keys) else {
+        return Ok(());
     };
 
-    match user_ip {
-        Ok(x) => Ok(x),
-        Err(err) => bad_request!(&err.to_string()),
+    if limited.first {
+        warn!("rate limiting auth requests from {}", limited.key);
+        send_admin_webhook(
+            state,
+            WebhookMessage::AuthRateLimited(limited.key.to_string(), "Too many requests".to_owned(), limited.retry_after),
+        );
+    }
+
+    Err(TooManyRequestsResponder::new(
+        &format!("too many attempts, please try again in {} seconds", limited.retry_after),
+        limited.retry_after,
+    )
+    .into())
+}
+
+fn record_auth_failure(rate_limiter: &AuthRateLimiter, state: &ServerStateData, keys: &[RateLimitKey]) {
+    if let Some((key, lockout)) = rate_limiter.record_failure(&state.config, keys) {
+        warn!("locking out {key} for {lockout} seconds after repeated failed auth attempts");
+        send_admin_webhook(
+            state,
+            WebhookMessage::AuthRateLimited(key.to_string(), "Repeated failed attempts".to_owned(), lockout),
+        );
+    }
+}
+
+/// Checks whether the user can log into a whitelisted server, either by being whitelisted or with an invite code.
+/// Returns the invite code that has to be redeemed once the user has proven that they own the account.
+async fn check_whitelist(
+    db: &GlobedDb,
+    rate_limiter: &AuthRateLimiter,
+    state: &ServerStateData,
+    account_data: &AccountData,
+    keys: &[RateLimitKey],
+) -> WebResult<Option<String>> {
+    if db.get_user(account_data.account_id).await?.is_some_and(|x| x.is_whitelisted) {
+        return Ok(None);
+    }
+
+    let code = account_data
+        .invite_code
+        .as_deref()
+        .map(|x| x.trim().to_uppercase())
+        .filter(|x| !x.is_empty());
+
+    let Some(code) = code else {
+        unauthorized!("This server has whitelist enabled and your account has not been approved.");
+    };
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+    if !db.get_invite_code(&code).await?.is_some_and(|x| x.is_usable(now)) {
+        // guessing invite codes is not tied to an account, so only count it against the IP address
+        let ip_keys = keys.iter().filter(|x| matches!(x, RateLimitKey::Ip(_))).copied().collect::<Vec<_>>();
+        record_auth_failure(rate_limiter, state, &ip_keys);
+        unauthorized!("This invite code is invalid or has expired.");
+    }
+
+    Ok(Some(code))
+}
+
+async fn redeem_invite_code(db: &GlobedDb, account_data: &AccountData, code: &str) -> WebResult<()> {
+    if !db.redeem_invite_code(code, account_data.account_id).await? {
+        unauthorized!("This invite code is invalid or has expired.");
+    }
+
+    info!(
+        "{} ({}) joined the whitelist with invite code {}",
+        account_data.username, account_data.account_id, code
+    );
+
+    Ok(())
+}
+
+/// Checks the answer to the challenge that was requested from this IP address, and returns the challenge if it is correct
+fn check_challenge_answer(
+    rate_limiter: &AuthRateLimiter,
+    state: &ServerStateData,
+    user_ip: IpAddr,
+    account_data: &AccountData,
+    answer: &str,
+    keys: &[RateLimitKey],
+) -> WebResult<ActiveChallenge> {
+    let challenge: ActiveChallenge = match state.active_challenges.get(&user_ip) {
+        None => {
+            unauthorized!("challenge does not exist for this IP address");
+        }
+        Some(x) => x,
+    }
+    .clone();
+
+    if challenge.account_id != account_data.account_id {
+        warn!(
+            "failed to validate challenge: requested for accountid {} but {} completed",
+            challenge.account_id, account_data.account_id
+        );
+        unauthorized!("challenge was requested for a different account id, not validating");
+    }
+
+    if challenge.user_id != account_data.user_id {
+        warn!(
+            "failed to validate challenge ({}): requested for userid {} but {} completed",
+            account_data.account_id, challenge.user_id, account_data.user_id
+        );
+        unauthorized!("challenge was requested for a different user id, not validating");
+    }
+
+    if !challenge.name.eq_ignore_ascii_case(&account_data.username) {
+        warn!(
+            "failed to validate challenge ({}): requested for {} but {} completed",
+            account_data.account_id, challenge.name, account_data.username
+        );
+        unauthorized!("challenge was requested for a different account name, not validating");
+    }
+
+    if !state.verify_challenge(&challenge, answer) {
+        record_auth_failure(rate_limiter, state, keys);
+        unauthorized!("incorrect challenge solution was provided");
+    }
+
+    Ok(challenge)
+}
+
+/// Waits for the proof that the user owns the account, if account verification is enabled.
+/// Returns the ID of the message that the client should delete afterwards.
+async fn verify_account_ownership(state: &ServerState, use_gd_api: bool, account_data: &AccountData, answer: &str) -> WebResult<Option<i32>> {
+    if !use_gd_api {
+        return Ok(None);
+    }
+
+    let result = state
+        .inner
+        .verifier
+        .verify_account(account_data.account_id, account_data.user_id, &account_data.username, answer)
+        .await;
+
+    match result {
+        Ok(id) => Ok(id),
+        Err(err) => unauthorized!(&err),
     }
 }
 
@@ -65,7 +281,7 @@ pub async fn totp_login(
     state: &State<ServerState>,
     db: &GlobedDb,
     ip: IpAddr,
-    cfip: CloudflareIPGuard,
+    forwarded_ip: ForwardedIpGuard,
     _user_agent: ClientUserAgentGuard<'_>,
     mut post_data: EncryptedJsonGuard<TotpLoginData>,
     protocol: u16,
@@ -79,12 +295,19 @@ pub async fn totp_login(
     let state_ = state.state_read().await;
     let account_data = &post_data.0.account_data;
 
-    check_ip(ip, &cfip, state_.config.cloudflare_protection)?;
+    let user_ip = check_ip(ip, &forwarded_ip, &state_)?;
 
+    let rate_limiter = &state.inner.rate_limiter;
+    let rl_keys = [
+        RateLimitKey::Ip(user_ip),
+        RateLimitKey::Account(account_data.account_id),
+        RateLimitKey::IpAccount(user_ip, account_data.account_id),
+    ];
+    check_rate_limit(rate_limiter, &state_, &rl_keys)?;
+
+    let mut invite_code = None;
     if state_.config.userlist_mode == UserlistMode::Whitelist {
-        if !db.get_user(account_data.account_id).await?.is_some_and(|x| x.is_whitelisted) {
-            unauthorized!("This server has whitelist enabled and your account has not been approved.");
-        }
+        invite_code = check_whitelist(db, rate_limiter, &state_, account_data, &rl_keys).await?;
     } else {
         let ban_reason = state_.is_banned(db, account_data.account_id).await;
         if let Err(err) = ban_reason {
@@ -97,17 +320,27 @@ pub async fn totp_login(
     }
 
     let uak_decoded = b64e::URL_SAFE.decode(post_data.0.authkey)?;
-    let valid_authkey = state_.generate_hashed_authkey(account_data.account_id, account_data.user_id, &account_data.username);
-
-    let valid = uak_decoded.len() == valid_authkey.len() && uak_decoded.iter().zip(valid_authkey.iter()).all(|(c1, c2)| *c1 == *c2);
+    let valid = state_.verify_hashed_authkey(account_data.account_id, account_data.user_id, &account_data.username, &uak_decoded);
 
     if !valid {
+        record_auth_failure(rate_limiter, &state_, &rl_keys);
         unauthorized!("login failed");
     }
 
+    rate_limiter.record_success(&rl_keys);
+
+    if let Some(code) = invite_code {
+        redeem_invite_code(db, account_data, &code).await?;
+    }
+
+    // every token gets its own session, so that it can be revoked later
+    let session_id = generate_alphanum_string(SESSION_ID_LENGTH);
+    let expires_at = UNIX_EPOCH.elapsed().unwrap().as_secs() + state_.config.token_expiry;
+    db.create_session(account_data.account_id, &session_id, expires_at).await?;
+
     let token = state_
         .token_issuer
-        .generate(account_data.account_id, account_data.user_id, &account_data.username);
+        .generate(account_data.account_id, account_data.user_id, &account_data.username, &session_id);
 
     debug!("totp login from {} ({}) successful", account_data.account_id, &account_data.username);
 
@@ -120,7 +353,7 @@ pub async fn challenge_new(
     state: &State<ServerState>,
     db: &GlobedDb,
     ip: IpAddr,
-    cfip: CloudflareIPGuard,
+    forwarded_ip: ForwardedIpGuard,
     _user_agent: ClientUserAgentGuard<'_>,
     mut post_data: EncryptedJsonGuard<AccountData>,
     protocol: u16,
@@ -131,14 +364,25 @@ pub async fn challenge_new(
     // trim spaces at the end of the name
     trim_name(&mut post_data.0);
 
+    let rate_limiter = &state.inner.rate_limiter;
     let mut state = state.state_write().await;
     let account_data = &post_data.0;
 
-    let user_ip = check_ip(ip, &cfip, state.config.cloudflare_protection)?;
+    let user_ip = check_ip(ip, &forwarded_ip, &state)?;
+
+    let rl_keys = [
+        RateLimitKey::Ip(user_ip),
+        RateLimitKey::Account(account_data.account_id),
+        RateLimitKey::IpAccount(user_ip, account_data.account_id),
+    ];
+    check_rate_limit(rate_limiter, &state, &rl_keys)?;
 
     if state.config.userlist_mode == UserlistMode::Whitelist {
-        if !db.get_user(account_data.account_id).await?.is_some_and(|x| x.is_whitelisted) {
-            unauthorized!("This server has whitelist enabled and your account has not been approved.");
+        // users that are not whitelisted yet need a challenge to apply for the whitelist,
+        // in that case the whitelist is only checked once the challenge is completed
+        if !state.config.whitelist_applications {
+            // the code is only redeemed once the challenge is completed
+            check_whitelist(db, rate_limiter, &state, account_data, &rl_keys).await?;
         }
     } else {
         let ban_reason = state.is_banned(db, account_data.account_id).await;
@@ -242,8 +486,9 @@ fn decrypt_trust_token(token: &str, key: &str) -> WebResult<Vec<u8>> {
 #[post("/v2/challenge/verify", data = "<post_data>")]
 pub async fn challenge_verify(
     state: &State<ServerState>,
+    db: &GlobedDb,
     ip: IpAddr,
-    cfip: CloudflareIPGuard,
+    forwarded_ip: ForwardedIpGuard,
     _user_agent: ClientUserAgentGuard<'_>,
     mut post_data: EncryptedJsonGuard<ChallengeFinishData>,
 ) -> WebResult<String> {
@@ -255,68 +500,33 @@ pub async fn challenge_verify(
     let state_ = state.state_read().await;
     let account_data = &post_data.0.account_data;
 
-    let user_ip = check_ip(ip, &cfip, state_.config.cloudflare_protection)?;
+    let user_ip = check_ip(ip, &forwarded_ip, &state_)?;
 
-    trace!("challenge finish: {:?}", post_data.0);
+    let rate_limiter = &state.inner.rate_limiter;
+    let rl_keys = [
+        RateLimitKey::Ip(user_ip),
+        RateLimitKey::Account(account_data.account_id),
+        RateLimitKey::IpAccount(user_ip, account_data.account_id),
+    ];
+    check_rate_limit(rate_limiter, &state_, &rl_keys)?;
 
-    let challenge: ActiveChallenge = match state_.active_challenges.get(&user_ip) {
-        None => {
-            unauthorized!("challenge does not exist for this IP address");
-        }
-        Some(x) => x,
-    }
-    .clone();
-
-    if challenge.account_id != account_data.account_id {
-        warn!(
-            "failed to validate challenge: requested for accountid {} but {} completed",
-            challenge.account_id, account_data.account_id
-        );
-        unauthorized!("challenge was requested for a different account id, not validating");
-    }
-
-    if challenge.user_id != account_data.user_id {
-        warn!(
-            "failed to validate challenge ({}): requested for userid {} but {} completed",
-            account_data.account_id, challenge.user_id, account_data.user_id
-        );
-        unauthorized!("challenge was requested for a different user id, not validating");
-    }
-
-    if !challenge.name.eq_ignore_ascii_case(&account_data.username) {
-        warn!(
-            "failed to validate challenge ({}): requested for {} but {} completed",
-            account_data.account_id, challenge.name, account_data.username
-        );
-        unauthorized!("challenge was requested for a different account name, not validating");
-    }
-
-    let result = state_.verify_challenge(&challenge, &post_data.0.answer);
+    trace!("challenge finish: {:?}", post_data.0);
 
-    if !result {
-        unauthorized!("incorrect challenge solution was provided");
-    }
+    let challenge = check_challenge_answer(rate_limiter, &state_, user_ip, account_data, &post_data.0.answer, &rl_keys)?;
 
     let use_gd_api = state_.config.use_gd_api;
 
-    // drop the state because `verify_account` can intentionally block for a few seconds
-    drop(state_);
-
-    let message_id = if use_gd_api {
-        let result = state
-            .inner
-            .verifier
-            .verify_account(account_data.account_id, account_data.user_id, &account_data.username, &post_data.0.answer)
-            .await;
-
-        match result {
-            Ok(id) => Some(id),
-            Err(err) => unauthorized!(&err),
-        }
+    let invite_code = if state_.config.userlist_mode == UserlistMode::Whitelist {
+        check_whitelist(db, rate_limiter, &state_, account_data, &rl_keys).await?
     } else {
         None
     };
 
+    // drop the state because `verify_account` can intentionally block for a few seconds
+    drop(state_);
+
+    let message_id = verify_account_ownership(state, use_gd_api, account_data, &post_data.0.answer).await?;
+
     // verify trust token
     let trust_token = if let Ok(sm_key) = std::env::var("GLOBED_GS_SECURE_MODE_KEY") {
         if let Some(trust_token) = &post_data.0.trust_token {
@@ -349,6 +559,12 @@ pub async fn challenge_verify(
         );
     }
 
+    rate_limiter.record_success(&rl_keys);
+
+    if let Some(code) = invite_code {
+        redeem_invite_code(db, account_data, &code).await?;
+    }
+
     let mut state_ = state.state_write().await;
     state_.active_challenges.remove(&user_ip);
     let authkey = state_.generate_authkey(account_data.account_id, account_data.user_id, &account_data.username);
@@ -359,3 +575,410 @@ pub async fn challenge_verify(
         b64e::STANDARD.encode(authkey)
     ))
 }
+
+/// Lets users that are not whitelisted ask to be added to the whitelist.
+/// The user has to complete a challenge (started with `challenge_new`) to prove that they own the account.
+#[allow(clippy::similar_names, clippy::no_effect_underscore_binding)]
+#[post("/v2/whitelist/apply", data = "<post_data>")]
+pub async fn whitelist_apply(
+    state: &State<ServerState>,
+    db: &GlobedDb,
+    ip: IpAddr,
+    forwarded_ip: ForwardedIpGuard,
+    _user_agent: ClientUserAgentGuard<'_>,
+    mut post_data: EncryptedJsonGuard<WhitelistApplyData>,
+) -> WebResult<String> {
+    check_maintenance!(state);
+
+    // trim spaces at the end of the name
+    trim_name(&mut post_data.0.account_data);
+
+    let state_ = state.state_read().await;
+    let account_data = &post_data.0.account_data;
+
+    let user_ip = check_ip(ip, &forwarded_ip, &state_)?;
+
+    let rate_limiter = &state.inner.rate_limiter;
+    let rl_keys = [
+        RateLimitKey::Ip(user_ip),
+        RateLimitKey::Account(account_data.account_id),
+        RateLimitKey::IpAccount(user_ip, account_data.account_id),
+    ];
+    check_rate_limit(rate_limiter, &state_, &rl_keys)?;
+
+    if state_.config.userlist_mode != UserlistMode::Whitelist || !state_.config.whitelist_applications {
+        bad_request!("This server does not accept whitelist applications.");
+    }
+
+    let message = post_data.0.message.trim();
+    if message.chars().count() > MAX_APPLICATION_MESSAGE_LENGTH {
+        bad_request!(&format!(
+            "The message is too long, it can be at most {MAX_APPLICATION_MESSAGE_LENGTH} characters."
+        ));
+    }
+
+    if db.get_user(account_data.account_id).await?.is_some_and(|x| x.is_whitelisted) {
+        bad_request!("Your account is already whitelisted.");
+    }
+
+    match state_.is_banned(db, account_data.account_id).await {
+        Ok(Some(reason)) => unauthorized!(&format!("Banned from the server: {reason}")),
+        Ok(None) => {}
+        Err(err) => bad_request!(&format!("server error: {err}")),
+    }
+
+    if db.get_pending_application(account_data.account_id).await?.is_some() {
+        bad_request!("You already have a pending application, please wait until it is reviewed.");
+    }
+
+    check_challenge_answer(rate_limiter, &state_, user_ip, account_data, &post_data.0.answer, &rl_keys)?;
+
+    let use_gd_api = state_.config.use_gd_api;
+
+    // drop the state because `verify_account` can intentionally block for a few seconds
+    drop(state_);
+
+    let message_id = verify_account_ownership(state, use_gd_api, account_data, &post_data.0.answer).await?;
+
+    rate_limiter.record_success(&rl_keys);
+
+    // the challenge is used up either way
+    state.state_write().await.active_challenges.remove(&user_ip);
+
+    if !db
+        .create_whitelist_application(account_data.account_id, &account_data.username, message)
+        .await?
+    {
+        bad_request!("You already have a pending application, please wait until it is reviewed.");
+    }
+
+    info!(
+        "whitelist application submitted by {} ({})",
+        account_data.username, account_data.account_id
+    );
+
+    send_admin_webhook(
+        &*state.state_read().await,
+        WebhookMessage::WhitelistApplicationSubmitted(account_data.username.clone(), account_data.account_id, message.to_owned()),
+    );
+
+    Ok(message_id.map_or_else(|| "none".to_owned(), |x| x.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+        path::PathBuf,
+        time::Duration,
+    };
+
+    use globed_shared::{CryptoBox, SUPPORTED_PROTOCOLS, crypto_box::aead::AeadInPlace, reqwest};
+    use rocket::{
+        http::{Header, Status},
+        local::asynchronous::Client,
+    };
+    use rocket_db_pools::Database;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        config::ServerConfig,
+        db::migration_fairing,
+        game_pinger::GameServerPinger,
+        mock_gd_api,
+        state::{ServerState, ServerStateData},
+        web::{guards::encrypted_json::FUNNY_STRING, routes::build_router},
+    };
+
+    const PEER: ([u8; 4], u16) = ([203, 0, 113, 7], 50000);
+    const GD_API_ACCOUNT: i32 = 1000;
+
+    fn free_port() -> u16 {
+        TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port()
+    }
+
+    // starts the mock GD api in the background and returns its url
+    async fn start_mock_gd_api() -> String {
+        let port = free_port();
+        tokio::spawn(mock_gd_api::build(port).launch());
+
+        for _ in 0..100 {
+            if TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_ok() {
+                return format!("http://127.0.0.1:{port}");
+            }
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        panic!("mock GD api did not start");
+    }
+
+    async fn start_central(config: ServerConfig) -> (Client, ServerState) {
+        let config = ServerConfig {
+            cloudflare_protection: false,
+            proxy_header: ProxyHeader::None,
+            ..config
+        };
+
+        let state = ServerState::new(ServerStateData::new(PathBuf::new(), config), GameServerPinger::new(&[]).await);
+
+        let verifier_state = state.inner.clone();
+        tokio::spawn(async move { verifier_state.verifier.run().await });
+
+        // every connection to an in-memory database gets its own database, so only use one
+        let figment = rocket::Config::figment()
+            .merge(("port", free_port()))
+            .merge(("databases.globed_db.url", "sqlite::memory:"))
+            .merge(("databases.globed_db.max_connections", 1));
+
+        let rocket = rocket::custom(figment)
+            .mount("/", build_router())
+            .manage(state.clone())
+            .attach(GlobedDb::init())
+            .attach(migration_fairing());
+
+        (Client::tracked(rocket).await.unwrap(), state)
+    }
+
+    async fn post_encrypted(client: &Client, uri: String, body: &serde_json::Value) -> (Status, String) {
+        let crypto_box = CryptoBox::new_secret(&hex::decode(FUNNY_STRING).unwrap());
+        let data = crypto_box.encrypt(body.to_string().as_bytes()).unwrap();
+
+        let response = client
+            .post(uri)
+            .remote(SocketAddr::from(PEER))
+            .header(Header::new("User-Agent", "globed-geode-xd/test"))
+            .body(data)
+            .dispatch()
+            .await;
+
+        (response.status(), response.into_string().await.unwrap_or_default())
+    }
+
+    // the challenge is the answer encrypted with the public key that is sent alongside it, same as the client does it
+    fn solve_challenge(challenge: &str, pubkey: &str) -> String {
+        let challenge = b64e::STANDARD.decode(challenge).unwrap();
+        let pubkey = b64e::STANDARD.decode(pubkey).unwrap();
+
+        let (nonce, rest) = challenge.split_at(XSalsa20Poly1305::NONCE_SIZE);
+        let (tag, data) = rest.split_at(XSalsa20Poly1305::TAG_SIZE);
+        let mut data = data.to_vec();
+
+        XSalsa20Poly1305::new_from_slice(&pubkey)
+            .unwrap()
+            .decrypt_in_place_detached(nonce.into(), b"", &mut data, tag.into())
+            .unwrap();
+
+        String::from_utf8(data).unwrap()
+    }
+
+    #[tokio::test]
+    async fn challenge_round_trip() {
+        let gd_api_url = start_mock_gd_api().await;
+        let (client, state) = start_central(ServerConfig {
+            use_gd_api: true,
+            gd_api_account: GD_API_ACCOUNT,
+            gd_api_url: gd_api_url.clone(),
+            refresh_interval: 100,
+            ..ServerConfig::default()
+        })
+        .await;
+
+        let account_data = json!({ "account_id": 7, "user_id": 8, "username": "Tester" });
+
+        let (status, response) = post_encrypted(&client, format!("/v2/challenge/new?protocol={}", SUPPORTED_PROTOCOLS[0]), &account_data).await;
+
+        assert_eq!(status, Status::Ok, "{response}");
+
+        let parts = response.split(':').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], GD_API_ACCOUNT.to_string());
+
+        let answer = solve_challenge(parts[1], parts[2]);
+        let verify_data = json!({ "account_data": account_data, "answer": answer, "trust_token": null });
+
+        // the answer is correct, but the proof has not been sent to the GD servers yet
+        let (status, response) = post_encrypted(&client, "/v2/challenge/verify".to_owned(), &verify_data).await;
+        assert_eq!(status, Status::Unauthorized);
+        assert!(response.contains("proof was not found"), "{response}");
+
+        // send the answer as a message to the bot account, like the game does
+        reqwest::Client::new()
+            .post(format!("{gd_api_url}/uploadGJMessage20.php"))
+            .form(&[
+                ("accountID", "7".to_owned()),
+                ("userID", "8".to_owned()),
+                ("userName", "Tester".to_owned()),
+                ("toAccountID", GD_API_ACCOUNT.to_string()),
+                ("subject", b64e::URL_SAFE.encode(format!("##c## {answer}"))),
+            ])
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+
+        let (status, response) = post_encrypted(&client, "/v2/challenge/verify".to_owned(), &verify_data).await;
+        assert_eq!(status, Status::Ok, "{response}");
+
+        let (message_id, authkey) = response.split_once(':').unwrap();
+        assert_eq!(message_id, "1");
+
+        let expected = state.state_read().await.generate_authkey(7, 8, "Tester");
+        assert_eq!(b64e::STANDARD.decode(authkey).unwrap(), expected);
+
+        // the challenge can only be completed once
+        let (status, _) = post_encrypted(&client, "/v2/challenge/verify".to_owned(), &verify_data).await;
+        assert_eq!(status, Status::Unauthorized);
+    }
+
+    #[tokio::test]
+    async fn whitelist_apply_requires_challenge() {
+        let (client, _state) = start_central(ServerConfig {
+            userlist_mode: UserlistMode::Whitelist,
+            whitelist_applications: true,
+            ..ServerConfig::default()
+        })
+        .await;
+
+        let account_data = json!({ "account_id": 7, "user_id": 8, "username": "Tester" });
+        let apply = |answer: &str| json!({ "account_data": account_data, "answer": answer, "message": "let me in" });
+
+        // no challenge was requested yet
+        let (status, _) = post_encrypted(&client, "/v2/whitelist/apply".to_owned(), &apply("guess")).await;
+        assert_eq!(status, Status::Unauthorized);
+
+        // users that are not whitelisted can request a challenge in order to apply
+        let (status, response) = post_encrypted(&client, format!("/v2/challenge/new?protocol={}", SUPPORTED_PROTOCOLS[0]), &account_data).await;
+        assert_eq!(status, Status::Ok, "{response}");
+
+        let parts = response.split(':').collect::<Vec<_>>();
+        let answer = solve_challenge(parts[1], parts[2]);
+
+        let (status, _) = post_encrypted(&client, "/v2/whitelist/apply".to_owned(), &apply("guess")).await;
+        assert_eq!(status, Status::Unauthorized);
+
+        let (status, response) = post_encrypted(&client, "/v2/whitelist/apply".to_owned(), &apply(&answer)).await;
+        assert_eq!(status, Status::Ok, "{response}");
+        assert_eq!(response, "none");
+
+        // only one pending application per account
+        let (status, _) = post_encrypted(&client, "/v2/whitelist/apply".to_owned(), &apply(&answer)).await;
+        assert_eq!(status, Status::BadRequest);
+
+        // and the challenge cannot be used to log in without being whitelisted
+        let verify_data = json!({ "account_data": account_data, "answer": answer, "trust_token": null });
+        let (status, _) = post_encrypted(&client, "/v2/challenge/verify".to_owned(), &verify_data).await;
+        assert_eq!(status, Status::Unauthorized);
+    }
+
+    fn proxy_state(proxy_header: ProxyHeader, trusted_proxies: &[&str]) -> ServerStateData {
+        let config = ServerConfig {
+            cloudflare_protection: false,
+            proxy_header,
+            trusted_proxies: trusted_proxies.iter().map(ToString::to_string).collect(),
+            ..ServerConfig::default()
+        };
+
+        ServerStateData::new(PathBuf::new(), config)
+    }
+
+    fn forwarded(cf_connecting_ip: Option<&str>, x_forwarded_for: Option<&str>, x_real_ip: Option<&str>) -> ForwardedIpGuard {
+        ForwardedIpGuard {
+            cf_connecting_ip: cf_connecting_ip.map(ToOwned::to_owned),
+            x_forwarded_for: x_forwarded_for.map(ToOwned::to_owned),
+            x_real_ip: x_real_ip.map(ToOwned::to_owned),
+        }
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn check_ip_status(peer: &str, headers: &ForwardedIpGuard, state: &ServerStateData) -> Status {
+        match _check_ip(ip(peer), headers, state, true) {
+            Ok(_) => Status::Ok,
+            Err(err) => err.inner.0,
+        }
+    }
+
+    #[test]
+    fn check_ip_without_proxy_ignores_headers() {
+        let state = proxy_state(ProxyHeader::None, &[]);
+        let headers = forwarded(Some("198.51.100.1"), Some("198.51.100.2"), Some("198.51.100.3"));
+
+        assert_eq!(_check_ip(ip("203.0.113.7"), &headers, &state, true).ok(), Some(ip("203.0.113.7")));
+    }
+
+    #[test]
+    fn check_ip_untrusted_peer() {
+        let headers = forwarded(Some("198.51.100.1"), Some("198.51.100.1"), Some("198.51.100.1"));
+
+        for header in [ProxyHeader::CfConnectingIp, ProxyHeader::XForwardedFor, ProxyHeader::XRealIp] {
+            let state = proxy_state(header, &["10.0.0.0/8"]);
+            assert_eq!(check_ip_status("203.0.113.7", &headers, &state), Status::Unauthorized);
+        }
+    }
+
+    #[test]
+    fn check_ip_single_header() {
+        let headers = forwarded(Some("198.51.100.1"), None, Some("198.51.100.2"));
+
+        let state = proxy_state(ProxyHeader::CfConnectingIp, &["10.0.0.0/8"]);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("198.51.100.1")));
+
+        let state = proxy_state(ProxyHeader::XRealIp, &["10.0.0.0/8"]);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("198.51.100.2")));
+
+        // the configured header has to be present
+        let state = proxy_state(ProxyHeader::XForwardedFor, &["10.0.0.0/8"]);
+        assert_eq!(check_ip_status("10.0.0.1", &headers, &state), Status::BadRequest);
+    }
+
+    #[test]
+    fn check_ip_trusted_proxy_chain() {
+        let state = proxy_state(ProxyHeader::XForwardedFor, &["10.0.0.0/8", "192.0.2.0/24"]);
+
+        // the client can prepend anything it wants, only the rightmost untrusted address counts
+        let headers = forwarded(None, Some("198.51.100.66, 203.0.113.7, 192.0.2.10,10.0.0.2"), None);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("203.0.113.7")));
+
+        // if every address is a trusted proxy, the leftmost one is the client
+        let headers = forwarded(None, Some("10.1.1.1, 192.0.2.10"), None);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("10.1.1.1")));
+    }
+
+    #[test]
+    fn check_ip_malformed_entry() {
+        let state = proxy_state(ProxyHeader::XForwardedFor, &["10.0.0.0/8"]);
+
+        // garbage left of the client address is never looked at
+        let headers = forwarded(None, Some("not an ip, 203.0.113.7"), None);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("203.0.113.7")));
+
+        let headers = forwarded(None, Some("203.0.113.7, not an ip"), None);
+        assert_eq!(check_ip_status("10.0.0.1", &headers, &state), Status::BadRequest);
+
+        let headers = forwarded(None, Some("203.0.113.7,"), None);
+        assert_eq!(check_ip_status("10.0.0.1", &headers, &state), Status::BadRequest);
+
+        let state = proxy_state(ProxyHeader::XRealIp, &["10.0.0.0/8"]);
+        let headers = forwarded(None, None, Some("203.0.113.7:1234"));
+        assert_eq!(check_ip_status("10.0.0.1", &headers, &state), Status::BadRequest);
+    }
+
+    #[test]
+    fn check_ip_ipv6() {
+        let state = proxy_state(ProxyHeader::XForwardedFor, &["2001:db8::/32", "10.0.0.0/8"]);
+
+        let headers = forwarded(None, Some("2a00:1450:4001::1, 2001:db8:ffff::2"), None);
+        assert_eq!(_check_ip(ip("2001:db8::1"), &headers, &state, true).ok(), Some(ip("2a00:1450:4001::1")));
+
+        // mixed address families
+        let headers = forwarded(None, Some(" 203.0.113.7 , 2001:db8::2"), None);
+        assert_eq!(_check_ip(ip("10.0.0.1"), &headers, &state, true).ok(), Some(ip("203.0.113.7")));
+
+        assert_eq!(check_ip_status("2a00:1450:4001::1", &headers, &state), Status::Unauthorized);
+    }
+}
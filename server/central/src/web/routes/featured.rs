@@ -1,7 +1,8 @@
-use globed_shared::{warn, webhook};
+use globed_shared::{permissions, warn, webhook};
 use rocket::{get, post, serde::json::Json, State};
 use serde::Deserialize;
 
+use super::admin_totp::_check_code;
 use crate::{
     db::{dbimpl::FeaturedLevelPage, FeaturedLevel, GlobedDb},
     state::ServerState,
@@ -14,6 +15,7 @@ pub struct LevelReplaceData {
     rate_tier: i32,
     account_id: i32,
     admin_password: String,
+    totp_code: Option<String>, // required if the user has two-factor authentication enabled
     level_name: String,
     level_author: String,
     difficulty: i32,
@@ -42,19 +44,40 @@ async fn do_flevel_replace(state: &State<ServerState>, db: &GlobedDb, data: Leve
         unauthorized!("unauthorized (account)")
     };
 
-    let valid_global_pwd = state.state_read().await.config.admin_key == data.admin_password;
+    let valid_global_pwd = {
+        let state = state.state_read().await;
+        state.config.break_glass_admin_key && state.config.admin_key == data.admin_password
+    };
+
+    if valid_global_pwd {
+        warn!(
+            "{} ({}) is using the global admin key to change the featured level",
+            user.user_name.as_deref().unwrap_or("<unknown>"),
+            data.account_id
+        );
+    }
     let pwd_valid = user.verify_password(&data.admin_password).unwrap_or(false) || valid_global_pwd;
 
     if !pwd_valid {
         unauthorized!("unauthorized (password)");
     }
 
+    if !valid_global_pwd && user.totp_enabled {
+        let Some(code) = &data.totp_code else {
+            unauthorized!("unauthorized (two-factor code required)");
+        };
+
+        if !_check_code(db, data.account_id, code).await? {
+            unauthorized!("unauthorized (invalid two-factor code)");
+        }
+    }
+
     // check if any of the user's roles allow editing featured levels
     let state = state.state_read().await;
     let has_perm = valid_global_pwd
         || user.user_roles.iter().any(|role| {
             if let Some(matched_role) = state.config.roles.iter().find(|r| r.id == *role) {
-                matched_role.edit_featured_levels || matched_role.admin
+                matched_role.has_permission(permissions::EDIT_FEATURED_LEVELS)
             } else {
                 false
             }
@@ -0,0 +1,139 @@
+use globed_shared::{BlockActionData, FriendActionData, FriendRequestResponseData, FriendStatus, logger::debug};
+
+use rocket::{State, get, post};
+
+use crate::{db::GlobedDb, state::ServerState, web::*};
+
+/// maximum amount of friend requests a user can have sent without them being answered
+const MAX_PENDING_FRIEND_REQUESTS: i64 = 100;
+/// maximum amount of players a user can block
+const MAX_BLOCKED_USERS: usize = 500;
+
+#[get("/gs/friends/<account_id>")]
+pub async fn get_friend_list(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    account_id: i32,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let list = database.get_friend_list(account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(list))
+}
+
+#[post("/gs/friends/request", data = "<data>")]
+pub async fn send_request(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<FriendActionData>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    if data.0.account_id == data.0.target_id {
+        bad_request!("cannot send a friend request to yourself");
+    }
+
+    if data.0.target_id <= 0 || !database.user_exists(data.0.target_id).await? {
+        bad_request!("no such user");
+    }
+
+    // answering a request from the target is always allowed, only new requests count towards the limit
+    if database.get_friend_status(data.0.account_id, data.0.target_id).await? == FriendStatus::None
+        && database.get_pending_friend_request_count(data.0.account_id).await? >= MAX_PENDING_FRIEND_REQUESTS
+    {
+        bad_request!("too many pending friend requests");
+    }
+
+    debug!("{} is sending a friend request to {}", data.0.account_id, data.0.target_id);
+
+    let status = database.send_friend_request(data.0.account_id, data.0.target_id).await?;
+
+    Ok(CheckedEncodableResponder::new(status))
+}
+
+#[post("/gs/friends/respond", data = "<data>")]
+pub async fn respond_request(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<FriendRequestResponseData>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let status = database
+        .respond_friend_request(data.0.account_id, data.0.target_id, data.0.accept)
+        .await?;
+
+    Ok(CheckedEncodableResponder::new(status))
+}
+
+#[post("/gs/friends/remove", data = "<data>")]
+pub async fn remove(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<FriendActionData>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    database.remove_friend(data.0.account_id, data.0.target_id).await?;
+
+    Ok(CheckedEncodableResponder::new(FriendStatus::None))
+}
+
+#[post("/gs/blocks/update", data = "<data>")]
+pub async fn update_block(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<BlockActionData>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    if data.0.account_id == data.0.target_id {
+        bad_request!("cannot block yourself");
+    }
+
+    if data.0.blocked {
+        if data.0.target_id <= 0 || !database.user_exists(data.0.target_id).await? {
+            bad_request!("no such user");
+        }
+
+        if !database.is_blocked(data.0.account_id, data.0.target_id).await?
+            && database.get_blocked_users(data.0.account_id).await?.len() >= MAX_BLOCKED_USERS
+        {
+            bad_request!("too many blocked users");
+        }
+
+        database.block_user(data.0.account_id, data.0.target_id).await?;
+    } else {
+        database.unblock_user(data.0.account_id, data.0.target_id).await?;
+    }
+
+    let blocked = database.get_blocked_users(data.0.account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(blocked))
+}
@@ -30,10 +30,17 @@ pub async fn boot(
         protocol: MAX_SUPPORTED_PROTOCOL,
         tps: config.tps,
         maintenance: config.maintenance,
+        scheduled_maintenance: config.scheduled_maintenance,
         secret_key2: config.secret_key2.clone(),
+        retired_secret_keys2: config.retired_secret_keys2.clone(),
         token_expiry: config.token_expiry,
         status_print_interval: config.status_print_interval,
-        admin_key: FastString::new(&config.admin_key),
+        // the global key is only given to game servers if it's allowed to be used
+        admin_key: if config.break_glass_admin_key {
+            FastString::new(&config.admin_key)
+        } else {
+            FastString::default()
+        },
         whitelist: config.userlist_mode == UserlistMode::Whitelist,
         admin_webhook_url: config.admin_webhook_url.clone(),
         rate_suggestion_webhook_url: config.rate_suggestion_webhook_url.clone(),
@@ -41,7 +48,12 @@ pub async fn boot(
         room_webhook_url: config.room_webhook_url.clone(),
         chat_burst_limit: config.chat_burst_limit,
         chat_burst_interval: config.chat_burst_interval,
+        global_chat: config.global_chat,
         roles: config.roles.clone(),
+        persistent_rooms: config.persistent_rooms.clone(),
+        chat_commands: config.chat_commands.clone(),
+        motd: config.motd.clone(),
+        announcements: config.announcements.clone(),
     };
 
     debug!("boot data request from game server {} at {}", user_agent.0, ip_address);
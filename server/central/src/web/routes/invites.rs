@@ -0,0 +1,81 @@
+use globed_shared::{AdminCreateInviteCodeAction, AdminDeleteInviteCodeAction, generate_alphanum_string, logger::debug};
+
+use rocket::{State, get, post};
+
+use crate::{db::GlobedDb, state::ServerState, web::*};
+
+const INVITE_CODE_LENGTH: usize = 12;
+
+#[get("/gs/invites")]
+pub async fn get_invite_codes(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let codes = database.get_invite_codes().await?;
+
+    Ok(CheckedEncodableResponder::new(codes))
+}
+
+#[post("/gs/invites/create", data = "<data>")]
+pub async fn create_invite_code(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<AdminCreateInviteCodeAction>,
+) -> WebResult<CheckedEncodableResponder> {
+    let state = state.state_read().await;
+
+    if !password.verify(&state.config.game_server_password) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    if data.0.max_uses == 0 {
+        bad_request!("invite code must have at least one use");
+    }
+
+    if let Some(role_id) = &data.0.role_id
+        && !state.config.roles.iter().any(|x| x.id == *role_id)
+    {
+        bad_request!("invite code grants a role that does not exist");
+    }
+
+    drop(state);
+
+    // uppercase only, so the code is easier to type in
+    let code = generate_alphanum_string(INVITE_CODE_LENGTH).to_uppercase();
+
+    debug!("{} is creating invite code {} ({} uses)", data.0.issued_by, code, data.0.max_uses);
+
+    let invite = database.create_invite_code(&code, &data.0).await?;
+
+    Ok(CheckedEncodableResponder::new(invite))
+}
+
+#[post("/gs/invites/delete", data = "<data>")]
+pub async fn delete_invite_code(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<AdminDeleteInviteCodeAction>,
+) -> WebResult<()> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    debug!("{} is deleting invite code {}", data.0.issued_by, data.0.code);
+
+    if !database.delete_invite_code(&data.0.code).await? {
+        not_found!("invite code does not exist");
+    }
+
+    Ok(())
+}
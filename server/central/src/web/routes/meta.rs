@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::{sync::OnceLock, time::UNIX_EPOCH};
 
 use globed_shared::{
     MAX_SUPPORTED_PROTOCOL, MIN_CLIENT_VERSION, MIN_GD_VERSION, MIN_SUPPORTED_PROTOCOL, SERVER_MAGIC,
@@ -25,21 +25,46 @@ pub fn version() -> String {
     MIN_SUPPORTED_PROTOCOL.to_string()
 }
 
+#[derive(Serialize)]
+pub struct MaintenanceInfo {
+    pub active: bool,
+    pub starts_at: u64,       // 0 if the maintenance was not scheduled
+    pub ends_at: Option<u64>, // expected end, null if unknown
+}
+
 #[derive(Serialize)]
 pub struct VersionCheckResponse {
     pub pmin: u16,
     pub pmax: u16,
     pub gdmin: &'static str,
     pub globedmin: &'static str,
+    pub maintenance: Option<MaintenanceInfo>,
 }
 
 #[get("/versioncheck?<gd>&<globed>&<protocol>")]
-pub fn versioncheck(gd: &str, globed: &str, protocol: u16) -> WebResult<Json<VersionCheckResponse>> {
+pub fn versioncheck(state: &State<ServerState>, gd: &str, globed: &str, protocol: u16) -> WebResult<Json<VersionCheckResponse>> {
+    // upcoming or ongoing maintenance, so that clients can show when the server will be back
+    let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+    let maintenance = match state.scheduled_maintenance() {
+        Some(x) if x.ends_at().is_none_or(|end| now < end) => Some(MaintenanceInfo {
+            active: state.maintenance(),
+            starts_at: x.starts_at,
+            ends_at: x.ends_at(),
+        }),
+        _ if state.maintenance() => Some(MaintenanceInfo {
+            active: true,
+            starts_at: 0,
+            ends_at: None,
+        }),
+        _ => None,
+    };
+
     let resp = || VersionCheckResponse {
         pmin: MIN_SUPPORTED_PROTOCOL,
         pmax: MAX_SUPPORTED_PROTOCOL,
         gdmin: MIN_GD_VERSION,
         globedmin: MIN_CLIENT_VERSION,
+        maintenance,
     };
 
     if protocol == u16::MAX {
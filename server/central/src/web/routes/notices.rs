@@ -0,0 +1,83 @@
+use globed_shared::{AdminStoreNoticeAction, NoticeAckData, NoticeDeliveryData, logger::debug};
+
+use rocket::{State, get, post};
+
+use crate::{db::GlobedDb, state::ServerState, web::*};
+
+#[get("/gs/notices/<account_id>")]
+pub async fn get_notices(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    account_id: i32,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let notices = database.get_notices(account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(notices))
+}
+
+#[post("/gs/notices/store", data = "<data>")]
+pub async fn store_notice(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<AdminStoreNoticeAction>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    if data.0.message.is_empty() {
+        bad_request!("notice message cannot be empty");
+    }
+
+    debug!("{} is storing a notice for {}", data.0.issued_by, data.0.account_id);
+
+    let notice = database.store_notice(&data.0).await?;
+
+    Ok(CheckedEncodableResponder::new(notice))
+}
+
+#[post("/gs/notices/delivered", data = "<data>")]
+pub async fn mark_delivered(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<NoticeDeliveryData>,
+) -> WebResult<()> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    database.mark_notices_delivered(data.0.account_id, &data.0.ids).await?;
+
+    Ok(())
+}
+
+#[post("/gs/notices/ack", data = "<data>")]
+pub async fn acknowledge(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    data: CheckedDecodableGuard<NoticeAckData>,
+) -> WebResult<()> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    database.acknowledge_notice(data.0.account_id, data.0.id).await?;
+
+    Ok(())
+}
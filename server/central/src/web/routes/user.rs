@@ -1,3 +1,5 @@
+use std::time::UNIX_EPOCH;
+
 use globed_shared::{
     ServerUserEntry, UserLoginData, UserLoginResponse,
     data::*,
@@ -47,6 +49,66 @@ async fn _user_entry_from_server(user: ServerUserEntry, database: &GlobedDb) ->
     Ok(user.to_user_entry(punishments[0].take(), punishments[1].take()))
 }
 
+/// Enforces the punishment limits of the roles of the moderator who issued a punishment action.
+/// `expires_at` is `None` when removing a punishment.
+async fn _check_punishment_limits(
+    state: &ServerState,
+    database: &GlobedDb,
+    issued_by: i32,
+    account_id: i32,
+    is_ban: bool,
+    expires_at: Option<u64>,
+) -> WebResult<()> {
+    let issuer = _get_user_by_id(database, issued_by).await?;
+    let roles = state.state_read().await.config.roles.clone();
+
+    let issuer_roles = roles.iter().filter(|role| issuer.user_roles.contains(&role.id));
+
+    // if none of the roles give the permission, this was done by someone logged in with the global admin key
+    let Some(limits) = PunishmentLimits::from_roles(issuer_roles, is_ban) else {
+        return Ok(());
+    };
+
+    if let Some(expires_at) = expires_at
+        && let Err(msg) = limits.check_expiry(expires_at, UNIX_EPOCH.elapsed().unwrap().as_secs())
+    {
+        bad_request!(&msg);
+    }
+
+    if limits.edit_higher || issued_by == account_id {
+        return Ok(());
+    }
+
+    // check who issued the currently active punishment, if there is one
+    let user = _get_user_by_id(database, account_id).await?;
+    let punishments = database.get_users_punishments(&user).await?;
+
+    let Some(original_issuer) = punishments[usize::from(!is_ban)].as_ref().and_then(|p| p.issued_by) else {
+        return Ok(());
+    };
+
+    if original_issuer == issued_by {
+        return Ok(());
+    }
+
+    let original_issuer = _get_user_by_id(database, original_issuer).await?;
+
+    let priority_of = |user_roles: &[String]| {
+        roles
+            .iter()
+            .filter(|role| user_roles.contains(&role.id))
+            .map(|role| role.priority)
+            .max()
+            .unwrap_or(i32::MIN)
+    };
+
+    if priority_of(&original_issuer.user_roles) > priority_of(&issuer.user_roles) {
+        bad_request!("cannot change a punishment issued by someone above your permission level");
+    }
+
+    Ok(())
+}
+
 /* User lookup & sync roles routes (for discord bot) */
 
 #[derive(Serialize)]
@@ -189,6 +251,21 @@ pub async fn user_login(
         unauthorized!("invalid gameserver credentials");
     }
 
+    // tokens issued before sessions were tracked have no session id and stay valid until they expire
+    if let Some(session_id) = &userdata.0.session_id
+        && !database.use_session(userdata.0.account_id, session_id).await?
+    {
+        return Ok(CheckedEncodableResponder::new(UserLoginResponse {
+            user_entry: ServerUserEntry::new(userdata.0.account_id),
+            ban: None,
+            link_code: 0,
+            friends: Vec::new(),
+            blocked: Vec::new(),
+            notices: Vec::new(),
+            session_revoked: true,
+        }));
+    }
+
     let link_code = rand::rng().random_range(1000..10_000);
 
     // store login attempt
@@ -205,10 +282,18 @@ pub async fn user_login(
         None
     };
 
+    let friends = database.get_friends(userdata.0.account_id).await?;
+    let blocked = database.get_blocked_users(userdata.0.account_id).await?;
+    let notices = database.get_undelivered_notices(userdata.0.account_id).await?;
+
     let resp = UserLoginResponse {
         user_entry: user,
         ban,
         link_code,
+        friends,
+        blocked,
+        notices,
+        session_revoked: false,
     };
 
     Ok(CheckedEncodableResponder::new(resp))
@@ -312,6 +397,45 @@ pub async fn update_roles(
     Ok(CheckedEncodableResponder::new(user))
 }
 
+#[post("/user/update/grantrole", data = "<userdata>")]
+pub async fn update_grant_role(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    userdata: CheckedDecodableGuard<AdminGrantRoleAction>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    if !state.state_read().await.config.roles.iter().any(|x| x.id == userdata.0.role_id) {
+        bad_request!("attempting to grant an invalid role");
+    }
+
+    if userdata.0.expires_at <= UNIX_EPOCH.elapsed().unwrap().as_secs() {
+        bad_request!("invalid expiration date");
+    }
+
+    let user = _get_user_by_id(database, userdata.0.account_id).await?;
+
+    if user.user_roles.contains(&userdata.0.role_id) && !user.role_grants.iter().any(|x| x.role_id == userdata.0.role_id) {
+        bad_request!("the user already has this role permanently");
+    }
+
+    debug!(
+        "Granting role {} to {} (expires at = {})",
+        userdata.0.role_id, userdata.0.account_id, userdata.0.expires_at
+    );
+
+    database.grant_role(&userdata.0).await?;
+
+    let user = _get_user_by_id(database, userdata.0.account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(user))
+}
+
 #[post("/user/update/punish", data = "<userdata>")]
 pub async fn update_punish(
     state: &State<ServerState>,
@@ -330,11 +454,26 @@ pub async fn update_punish(
         userdata.0.account_id, userdata.0.is_ban, userdata.0.expires_at, userdata.0.reason
     );
 
+    _check_punishment_limits(
+        state,
+        database,
+        userdata.0.issued_by,
+        userdata.0.account_id,
+        userdata.0.is_ban,
+        Some(userdata.0.expires_at),
+    )
+    .await?;
+
     // insert empty user in case it does not exist
     database.insert_empty_user(userdata.0.account_id).await?;
 
     database.punish_user(&userdata.0).await?;
 
+    // a banned user should not be able to keep using their tokens after the ban ends
+    if userdata.0.is_ban {
+        database.revoke_all_sessions(userdata.0.account_id).await?;
+    }
+
     _return_user_and_punishments(database, userdata.0.account_id).await
 }
 
@@ -353,6 +492,16 @@ pub async fn update_unpunish(
 
     debug!("Removing punishment from {} (ban = {}", userdata.0.account_id, userdata.0.is_ban);
 
+    _check_punishment_limits(
+        state,
+        database,
+        userdata.0.issued_by,
+        userdata.0.account_id,
+        userdata.0.is_ban,
+        None,
+    )
+    .await?;
+
     database.unpunish_user(userdata.0.account_id, userdata.0.is_ban).await?;
 
     _return_user_and_punishments(database, userdata.0.account_id).await
@@ -419,6 +568,16 @@ pub async fn update_edit_punishment(
         unauthorized!("invalid gameserver credentials");
     }
 
+    _check_punishment_limits(
+        state,
+        database,
+        userdata.0.issued_by,
+        userdata.0.account_id,
+        userdata.0.is_ban,
+        Some(userdata.0.expires_at),
+    )
+    .await?;
+
     database
         .edit_punishment(
             userdata.0.account_id,
@@ -449,6 +608,102 @@ pub async fn get_punishment_history(
     Ok(CheckedEncodableResponder::new(punishments))
 }
 
+#[get("/user/sessions?<account_id>")]
+pub async fn get_sessions(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    account_id: i32,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let sessions = database.get_sessions(account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(sessions))
+}
+
+#[post("/user/update/revokesessions", data = "<userdata>")]
+pub async fn update_revoke_sessions(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    userdata: CheckedDecodableGuard<AdminRevokeSessionsAction>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    debug!(
+        "Revoking sessions of {} (session = '{}', issued by {})",
+        userdata.0.account_id, userdata.0.session_id, userdata.0.issued_by
+    );
+
+    if userdata.0.session_id.is_empty() {
+        database.revoke_all_sessions(userdata.0.account_id).await?;
+    } else {
+        database.revoke_session(userdata.0.account_id, &userdata.0.session_id).await?;
+    }
+
+    let user = _get_user_by_id(database, userdata.0.account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(user))
+}
+
+#[get("/user/whitelist_applications?<page>")]
+pub async fn get_whitelist_applications(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    page: usize,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    let applications = database.get_pending_applications(page).await?;
+
+    Ok(CheckedEncodableResponder::new(applications))
+}
+
+#[post("/user/update/reviewapplication", data = "<userdata>")]
+pub async fn update_review_application(
+    state: &State<ServerState>,
+    password: GameServerPasswordGuard,
+    database: &GlobedDb,
+    userdata: CheckedDecodableGuard<AdminReviewApplicationAction>,
+) -> WebResult<CheckedEncodableResponder> {
+    let correct = state.state_read().await.config.game_server_password.clone();
+
+    if !password.verify(&correct) {
+        unauthorized!("invalid gameserver credentials");
+    }
+
+    debug!(
+        "Reviewing whitelist application of {} (approve = {}, issued by {})",
+        userdata.0.account_id, userdata.0.approve, userdata.0.issued_by
+    );
+
+    let reviewed = database
+        .review_whitelist_application(userdata.0.account_id, userdata.0.issued_by, userdata.0.approve, &userdata.0.reason)
+        .await?;
+
+    if !reviewed {
+        not_found!("this user has no pending whitelist application");
+    }
+
+    let user = _get_user_by_id(database, userdata.0.account_id).await?;
+
+    Ok(CheckedEncodableResponder::new(user))
+}
+
 #[get("/user_names?<ids>")]
 pub async fn get_many_user_names(_password: GameServerPasswordGuard, database: &GlobedDb, ids: String) -> WebResult<CheckedEncodableResponder> {
     let mut vec = Vec::<(i32, String)>::new();
@@ -0,0 +1,141 @@
+use globed_shared::{ServerUserEntry, WhitelistApplication, logger::*, permissions, webhook};
+use rocket::{State, post, serde::json::Json};
+use serde::Deserialize;
+
+use super::admin_totp::_check_code;
+use crate::{db::GlobedDb, state::ServerState, web::*};
+
+// Reviewing whitelist applications over HTTP, the same can be done in-game through the admin panel
+
+#[derive(Deserialize)]
+pub struct AdminCredentials {
+    account_id: i32,
+    admin_password: String,
+    totp_code: Option<String>, // required if the user has two-factor authentication enabled
+}
+
+#[derive(Deserialize)]
+pub struct ApplicationReviewData {
+    #[serde(flatten)]
+    credentials: AdminCredentials,
+    target_account_id: i32,
+    approve: bool,
+    reason: String,
+}
+
+// checks the credentials and returns the user if they are allowed to manage the whitelist
+async fn _verify_admin(state: &ServerState, db: &GlobedDb, credentials: &AdminCredentials) -> WebResult<ServerUserEntry> {
+    let Some(user) = db.get_user(credentials.account_id).await? else {
+        unauthorized!("unauthorized (account)")
+    };
+
+    let valid_global_pwd = {
+        let state = state.state_read().await;
+        state.config.break_glass_admin_key && state.config.admin_key == credentials.admin_password
+    };
+
+    if valid_global_pwd {
+        warn!(
+            "{} ({}) is using the global admin key to manage whitelist applications",
+            user.user_name.as_deref().unwrap_or("<unknown>"),
+            credentials.account_id
+        );
+
+        return Ok(user);
+    }
+
+    if !user.verify_password(&credentials.admin_password).unwrap_or(false) {
+        unauthorized!("unauthorized (password)");
+    }
+
+    if user.totp_enabled {
+        let Some(code) = &credentials.totp_code else {
+            unauthorized!("unauthorized (two-factor code required)");
+        };
+
+        if !_check_code(db, credentials.account_id, code).await? {
+            unauthorized!("unauthorized (invalid two-factor code)");
+        }
+    }
+
+    let state = state.state_read().await;
+    let has_perm = user.user_roles.iter().any(|role| {
+        state
+            .config
+            .roles
+            .iter()
+            .find(|r| r.id == *role)
+            .is_some_and(|r| r.has_permission(permissions::BAN))
+    });
+
+    if !has_perm {
+        unauthorized!("unauthorized (perms)");
+    }
+
+    Ok(user)
+}
+
+#[post("/v2/whitelist/applications?<page>", data = "<data>")]
+pub async fn applications(
+    state: &State<ServerState>,
+    db: &GlobedDb,
+    _user_agent: ClientUserAgentGuard<'_>,
+    data: EncryptedJsonGuard<AdminCredentials>,
+    page: Option<usize>,
+) -> WebResult<Json<Vec<WhitelistApplication>>> {
+    _verify_admin(state, db, &data.0).await?;
+
+    Ok(Json(db.get_pending_applications(page.unwrap_or(0)).await?))
+}
+
+#[post("/v2/whitelist/review", data = "<data>")]
+pub async fn review(
+    state: &State<ServerState>,
+    db: &GlobedDb,
+    _user_agent: ClientUserAgentGuard<'_>,
+    data: EncryptedJsonGuard<ApplicationReviewData>,
+) -> WebResult<()> {
+    let data = data.0;
+    let user = _verify_admin(state, db, &data.credentials).await?;
+
+    let Some(application) = db.get_pending_application(data.target_account_id).await? else {
+        not_found!("this user has no pending whitelist application");
+    };
+
+    db.review_whitelist_application(data.target_account_id, data.credentials.account_id, data.approve, &data.reason)
+        .await?;
+
+    let mod_name = user.user_name.unwrap_or_else(|| data.credentials.account_id.to_string());
+
+    info!(
+        "{} {} the whitelist application of {} ({})",
+        mod_name,
+        if data.approve { "approved" } else { "denied" },
+        application.user_name,
+        application.account_id
+    );
+
+    let state = state.state_read().await;
+    let webhook_url = state.config.admin_webhook_url.clone();
+    let http_client = state.http_client.clone();
+    drop(state);
+
+    if !webhook_url.is_empty()
+        && let Err(e) = webhook::send_webhook_messages(
+            http_client,
+            &webhook_url,
+            &[webhook::WebhookMessage::WhitelistApplicationReviewed(
+                mod_name,
+                application.user_name,
+                data.approve,
+                data.reason,
+            )],
+            None,
+        )
+        .await
+    {
+        warn!("error sending webhook message: {e:?}");
+    }
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use std::{
     error::Error,
     fmt::{Display, Write},
     sync::atomic::{AtomicBool, Ordering},
-    time::Duration,
+    time::{Duration, UNIX_EPOCH},
 };
 
 use esp::{
@@ -78,23 +78,28 @@ pub struct CentralBridge {
     pub admin_webhook_present: AtomicBool,
     pub featured_webhook_present: AtomicBool,
     pub room_webhook_present: AtomicBool,
+    pub global_chat: AtomicBool,
 }
 
 #[derive(Encodable, DynamicSize)]
 struct UserLoginPayload<'a> {
     pub account_id: i32,
     pub username: Cow<'a, str>,
+    pub session_id: Option<Cow<'a, str>>,
 }
 
 pub enum AdminUserAction {
     UpdateUsername(AdminUpdateUsernameAction),
     SetNameColor(AdminSetNameColorAction),
     SetUserRoles(AdminSetUserRolesAction),
+    GrantRole(AdminGrantRoleAction),
     PunishUser(AdminPunishUserAction),
     RemovePunishment(AdminRemovePunishmentAction),
     Whitelist(AdminWhitelistAction),
     SetAdminPassword(AdminSetAdminPasswordAction),
     EditPunishment(AdminEditPunishmentAction),
+    RevokeSessions(AdminRevokeSessionsAction),
+    ReviewApplication(AdminReviewApplicationAction),
 }
 
 impl CentralBridge {
@@ -116,11 +121,15 @@ impl CentralBridge {
             admin_webhook_present: AtomicBool::new(false),
             featured_webhook_present: AtomicBool::new(false),
             room_webhook_present: AtomicBool::new(false),
+            global_chat: AtomicBool::new(false),
         }
     }
 
+    /// Returns whether the server is under maintenance, either manually or because of a scheduled maintenance window
     pub fn is_maintenance(&self) -> bool {
-        self.maintenance.load(Ordering::Relaxed)
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        self.maintenance.load(Ordering::Relaxed) || self.central_conf.lock().scheduled_maintenance.is_some_and(|x| x.is_active(now))
     }
 
     pub fn is_whitelist(&self) -> bool {
@@ -139,6 +148,10 @@ impl CentralBridge {
         self.room_webhook_present.load(Ordering::Relaxed)
     }
 
+    pub fn is_global_chat_enabled(&self) -> bool {
+        self.global_chat.load(Ordering::Relaxed)
+    }
+
     pub async fn request_boot_data(&self) -> Result<GameServerBootData> {
         let response = self
             .http_client
@@ -196,11 +209,13 @@ impl CentralBridge {
         self.featured_webhook_present
             .store(!data.featured_webhook_url.is_empty(), Ordering::Relaxed);
         self.room_webhook_present.store(!data.room_webhook_url.is_empty(), Ordering::Relaxed);
+        self.global_chat.store(data.global_chat, Ordering::Relaxed);
 
         let mut issuer = self.token_issuer.lock();
 
         issuer.set_expiration_period(Duration::from_secs(data.token_expiry));
         issuer.set_secret_key(&data.secret_key2);
+        issuer.set_retired_keys(&data.retired_secret_keys2);
 
         *self.central_conf.lock() = data;
     }
@@ -228,10 +243,11 @@ impl CentralBridge {
         Ok(reader.read_value()?)
     }
 
-    pub async fn user_login(&self, account_id: i32, username: &str) -> Result<UserLoginResponse> {
+    pub async fn user_login(&self, account_id: i32, username: &str, session_id: Option<&str>) -> Result<UserLoginResponse> {
         let payload = UserLoginPayload {
             account_id,
             username: Cow::Borrowed(username),
+            session_id: session_id.map(Cow::Borrowed),
         };
 
         self._send_encoded_body_req_resp("gs/userlogin", &payload).await
@@ -245,11 +261,14 @@ impl CentralBridge {
             AdminUserAction::UpdateUsername(x) => Ok((self._send_encoded_body_req_resp("user/update/username", x).await?, None, None)),
             AdminUserAction::SetNameColor(x) => Ok((self._send_encoded_body_req_resp("user/update/name_color", x).await?, None, None)),
             AdminUserAction::SetUserRoles(x) => Ok((self._send_encoded_body_req_resp("user/update/roles", x).await?, None, None)),
+            AdminUserAction::GrantRole(x) => Ok((self._send_encoded_body_req_resp("user/update/grantrole", x).await?, None, None)),
             AdminUserAction::PunishUser(x) => self._send_encoded_body_req_resp("user/update/punish", x).await,
             AdminUserAction::RemovePunishment(x) => self._send_encoded_body_req_resp("user/update/unpunish", x).await,
             AdminUserAction::Whitelist(x) => Ok((self._send_encoded_body_req_resp("user/update/whitelist", x).await?, None, None)),
             AdminUserAction::SetAdminPassword(x) => Ok((self._send_encoded_body_req_resp("user/update/adminpw", x).await?, None, None)),
             AdminUserAction::EditPunishment(x) => self._send_encoded_body_req_resp("user/update/editpunish", x).await,
+            AdminUserAction::RevokeSessions(x) => Ok((self._send_encoded_body_req_resp("user/update/revokesessions", x).await?, None, None)),
+            AdminUserAction::ReviewApplication(x) => Ok((self._send_encoded_body_req_resp("user/update/reviewapplication", x).await?, None, None)),
         }
     }
 
@@ -276,6 +295,183 @@ impl CentralBridge {
         Ok(reader.read_value()?)
     }
 
+    pub async fn get_sessions(&self, account_id: i32) -> Result<Vec<UserSession>> {
+        let response = self
+            .http_client
+            .get(format!("{}user/sessions", self.central_url))
+            .query(&[("account_id", account_id)])
+            .header("Authorization", self.central_pw.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value()?)
+    }
+
+    pub async fn get_whitelist_applications(&self, page: u32) -> Result<Vec<WhitelistApplication>> {
+        let response = self
+            .http_client
+            .get(format!("{}user/whitelist_applications", self.central_url))
+            .query(&[("page", page)])
+            .header("Authorization", self.central_pw.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value()?)
+    }
+
+    pub async fn get_friend_list(&self, account_id: i32) -> Result<FriendList> {
+        let response = self
+            .http_client
+            .get(format!("{}gs/friends/{}", self.central_url, account_id))
+            .header("Authorization", self.central_pw.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value()?)
+    }
+
+    /// Sends a friend request (or accepts a pending one from the target), returns the new friend status
+    pub async fn send_friend_request(&self, account_id: i32, target_id: i32) -> Result<FriendStatus> {
+        self._send_encoded_body_req_resp("gs/friends/request", &FriendActionData { account_id, target_id })
+            .await
+    }
+
+    pub async fn respond_friend_request(&self, account_id: i32, target_id: i32, accept: bool) -> Result<FriendStatus> {
+        self._send_encoded_body_req_resp(
+            "gs/friends/respond",
+            &FriendRequestResponseData {
+                account_id,
+                target_id,
+                accept,
+            },
+        )
+        .await
+    }
+
+    pub async fn remove_friend(&self, account_id: i32, target_id: i32) -> Result<FriendStatus> {
+        self._send_encoded_body_req_resp("gs/friends/remove", &FriendActionData { account_id, target_id })
+            .await
+    }
+
+    /// Blocks or unblocks a player, returns the updated list of blocked players
+    pub async fn update_block(&self, account_id: i32, target_id: i32, blocked: bool) -> Result<Vec<i32>> {
+        self._send_encoded_body_req_resp(
+            "gs/blocks/update",
+            &BlockActionData {
+                account_id,
+                target_id,
+                blocked,
+            },
+        )
+        .await
+    }
+
+    /// Stores a notice on the central server, to be delivered when the user logs in if `action.delivered` is false
+    pub async fn store_notice(&self, action: &AdminStoreNoticeAction) -> Result<StoredNotice> {
+        self._send_encoded_body_req_resp("gs/notices/store", action).await
+    }
+
+    pub async fn create_invite_code(&self, action: &AdminCreateInviteCodeAction) -> Result<InviteCode> {
+        self._send_encoded_body_req_resp("gs/invites/create", action).await
+    }
+
+    pub async fn delete_invite_code(&self, action: &AdminDeleteInviteCodeAction) -> Result<()> {
+        self._send_encoded_body_req("gs/invites/delete", action).await.map(|_| ())
+    }
+
+    pub async fn get_invite_codes(&self) -> Result<Vec<InviteCode>> {
+        let response = self
+            .http_client
+            .get(format!("{}gs/invites", self.central_url))
+            .header("Authorization", self.central_pw.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value()?)
+    }
+
+    pub async fn verify_totp(&self, account_id: i32, code: String) -> Result<bool> {
+        self._send_encoded_body_req_resp("gs/totp/verify", &TotpVerifyData { account_id, code }).await
+    }
+
+    pub async fn get_notices(&self, account_id: i32) -> Result<Vec<StoredNotice>> {
+        let response = self
+            .http_client
+            .get(format!("{}gs/notices/{}", self.central_url, account_id))
+            .header("Authorization", self.central_pw.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_else(|_| "<no response>".to_owned());
+
+            return Err(CentralBridgeError::CentralError((status, message)));
+        }
+
+        let data = response.bytes().await?;
+        let mut reader = ByteReader::from_bytes(&data);
+        reader.validate_self_checksum()?;
+
+        Ok(reader.read_value()?)
+    }
+
+    pub async fn mark_notices_delivered(&self, account_id: i32, ids: Vec<i64>) -> Result<()> {
+        self._send_encoded_body_req("gs/notices/delivered", &NoticeDeliveryData { account_id, ids })
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn acknowledge_notice(&self, account_id: i32, id: i64) -> Result<()> {
+        self._send_encoded_body_req("gs/notices/ack", &NoticeAckData { account_id, id })
+            .await
+            .map(|_| ())
+    }
+
     pub async fn get_many_names(&self, account_ids: &[i32]) -> Result<Vec<(i32, String)>> {
         if account_ids.is_empty() {
             return Ok(Vec::new());
@@ -400,7 +596,7 @@ impl CentralBridge {
                     new_color: user.name_color.clone(),
                 }));
             }
-            AdminUserAction::SetUserRoles(_act) => {
+            AdminUserAction::SetUserRoles(_) | AdminUserAction::GrantRole(_) => {
                 messages.push(WebhookMessage::UserRolesChanged(
                     mod_name,
                     user_name.into_owned(),
@@ -461,6 +657,21 @@ impl CentralBridge {
             }
             AdminUserAction::Whitelist(_action) => { /* no whitelist message */ }
             AdminUserAction::SetAdminPassword(_) => {}
+            AdminUserAction::RevokeSessions(action) => {
+                messages.push(WebhookMessage::UserSessionsRevoked(
+                    mod_name,
+                    user_name.into_owned(),
+                    (!action.session_id.is_empty()).then(|| action.session_id.clone()),
+                ));
+            }
+            AdminUserAction::ReviewApplication(action) => {
+                messages.push(WebhookMessage::WhitelistApplicationReviewed(
+                    mod_name,
+                    user_name.into_owned(),
+                    action.approve,
+                    action.reason.clone(),
+                ));
+            }
             AdminUserAction::EditPunishment(action) => {
                 messages.push(WebhookMessage::UserViolationMetaChanged(ViolationMetaChange {
                     account_id: user.account_id,
@@ -17,7 +17,7 @@ use crate::{
     },
 };
 use esp::ByteReader;
-use globed_shared::{ServerUserEntry, SyncMutex, logger::*, should_ignore_error};
+use globed_shared::{IntSet, ServerUserEntry, SyncMutex, logger::*, should_ignore_error};
 use handlers::game::MAX_VOICE_PACKET_SIZE;
 use tokio::time::Instant;
 
@@ -34,6 +34,7 @@ pub mod handlers;
 
 pub const INLINE_BUFFER_SIZE: usize = 164;
 pub const THREAD_MICRO_TIMEOUT: Duration = Duration::from_secs(30);
+const CHAT_RATE_LIMITER_COUNT: usize = 4;
 
 #[derive(Clone)]
 pub enum ServerThreadMessage {
@@ -42,11 +43,16 @@ pub enum ServerThreadMessage {
     BroadcastVoice(Arc<VoiceBroadcastPacket>),
     BroadcastText(ChatMessageBroadcastPacket),
     BroadcastNotice(ServerNoticePacket),
+    BroadcastStoredNotice(StoredNoticePacket),
     BroadcastInvite(RoomInvitePacket),
+    BroadcastInviteStatus(RoomInviteStatusPacket),
     BroadcastRoomInfo(RoomInfoPacket),
+    BroadcastTeamChange(RoomTeamChangedPacket),
     BroadcastBan(ServerBannedPacket),
     BroadcastMute(ServerMutedPacket),
     BroadcastRoleChange(RolesUpdatedPacket),
+    BroadcastFriendStatus(FriendStatusChangedPacket),
+    BroadcastPrivateMessage(PrivateMessageReceivedPacket),
     BroadcastRoomKicked,
     TerminationNotice(FastString),
 }
@@ -69,16 +75,20 @@ pub struct ClientThread {
     pub account_data: SyncMutex<PlayerAccountData>,
     pub user_entry: SyncMutex<ServerUserEntry>,
     pub user_role: SyncMutex<ComputedRole>,
+    pub session_id: SyncMutex<Option<String>>,
 
     pub is_authorized_user: AtomicBool,
+    pub pending_admin_totp: AtomicBool, // password was correct, but still waiting for the 2fa code
 
     pub privacy_settings: SyncMutex<UserPrivacyFlags>,
+    pub friends: SyncMutex<IntSet<i32>>,
+    pub blocked: SyncMutex<IntSet<i32>>,
 
     message_queue: Mutex<VecDeque<ServerThreadMessage>>,
     message_notify: Notify,
     rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
     voice_rate_limiter: LockfreeMutCell<SimpleRateLimiter>,
-    chat_rate_limiter: Option<LockfreeMutCell<SimpleRateLimiter>>,
+    chat_rate_limiters: Option<[LockfreeMutCell<SimpleRateLimiter>; CHAT_RATE_LIMITER_COUNT]>, // one per chat channel, the last one is for private messages
     translator: PacketTranslator,
 
     pub destruction_notify: Arc<Notify>,
@@ -93,17 +103,19 @@ impl ClientThread {
     pub fn from_unauthorized(thread: UnauthorizedThread) -> Self {
         let game_server = thread.game_server;
 
-        let (rate_limiter, voice_rate_limiter, chat_rate_limiter) = {
+        let (rate_limiter, voice_rate_limiter, chat_rate_limiters) = {
             let conf = game_server.bridge.central_conf.lock();
 
             (
                 SimpleRateLimiter::new(conf.tps as usize + 6, Duration::from_millis(900)),
                 SimpleRateLimiter::new(5, Duration::from_millis(1000)),
                 if conf.chat_burst_interval != 0 && conf.chat_burst_limit != 0 {
-                    Some(SimpleRateLimiter::new(
-                        conf.chat_burst_limit as usize,
-                        Duration::from_millis(u64::from(conf.chat_burst_interval)),
-                    ))
+                    Some(std::array::from_fn(|_| {
+                        LockfreeMutCell::new(SimpleRateLimiter::new(
+                            conf.chat_burst_limit as usize,
+                            Duration::from_millis(u64::from(conf.chat_burst_interval)),
+                        ))
+                    }))
                 } else {
                     None
                 },
@@ -133,16 +145,20 @@ impl ClientThread {
             account_data: SyncMutex::new(account_data),
             user_entry: SyncMutex::new(user_entry),
             user_role: SyncMutex::new(user_role),
+            session_id: thread.session_id,
 
             is_authorized_user: AtomicBool::new(false),
+            pending_admin_totp: AtomicBool::new(false),
 
             privacy_settings: thread.privacy_settings,
+            friends: thread.friends,
+            blocked: thread.blocked,
 
             message_queue: Mutex::new(VecDeque::new()),
             message_notify: Notify::new(),
             rate_limiter: LockfreeMutCell::new(rate_limiter),
             voice_rate_limiter: LockfreeMutCell::new(voice_rate_limiter),
-            chat_rate_limiter: chat_rate_limiter.map(LockfreeMutCell::new),
+            chat_rate_limiters,
             translator,
 
             destruction_notify: thread.destruction_notify,
@@ -252,6 +268,72 @@ impl ClientThread {
         self.room_id.load(Ordering::Relaxed) != 0
     }
 
+    pub fn is_friend(&self, account_id: i32) -> bool {
+        self.friends.lock().contains(&account_id)
+    }
+
+    pub fn is_blocked(&self, account_id: i32) -> bool {
+        self.blocked.lock().contains(&account_id)
+    }
+
+    /// Updates the cached list of friends after the friend status with the given player has changed
+    pub fn update_friend_status(&self, account_id: i32, status: FriendStatus) {
+        let mut friends = self.friends.lock();
+
+        if status == FriendStatus::Friends {
+            friends.insert(account_id);
+        } else {
+            friends.remove(&account_id);
+        }
+    }
+
+    /// Whether this player should be shown in player lists requested by `viewer`
+    pub fn is_visible_to(&self, viewer: i32) -> bool {
+        if self.account_id.load(Ordering::Relaxed) == viewer {
+            return true;
+        }
+
+        let settings = self.privacy_settings.lock();
+        !settings.get_hide_from_lists() && (!settings.get_visible_to_friends_only() || self.is_friend(viewer))
+    }
+
+    pub fn accepts_invites_from(&self, inviter: i32) -> bool {
+        if self.is_blocked(inviter) {
+            return false;
+        }
+
+        let settings = self.privacy_settings.lock();
+        !settings.get_no_invites() && (!settings.get_friend_invites_only() || self.is_friend(inviter))
+    }
+
+    pub fn has_expired_role_grants(&self, now: u64) -> bool {
+        self.user_entry.lock().role_grants.iter().any(|grant| grant.expires_at <= now)
+    }
+
+    /// Removes temporary roles that expired before `now`, and recomputes the roles of the user
+    pub fn expire_role_grants(&self, now: u64) {
+        let user_roles = {
+            let mut user_entry = self.user_entry.lock();
+            let (expired, active): (Vec<_>, Vec<_>) = std::mem::take(&mut user_entry.role_grants)
+                .into_iter()
+                .partition(|grant| grant.expires_at <= now);
+
+            user_entry.role_grants = active;
+            user_entry.user_roles.retain(|role| !expired.iter().any(|grant| grant.role_id == *role));
+            user_entry.user_roles.clone()
+        };
+
+        let role_manager = &self.game_server.state.role_manager;
+        self.account_data.lock().special_user_data = SpecialUserData::from_roles(&user_roles, role_manager);
+
+        let mut user_role = self.user_role.lock();
+
+        // dont replace if they have super admin
+        if user_role.priority != role_manager.get_superadmin().priority {
+            *user_role = role_manager.compute(&user_roles);
+        }
+    }
+
     /// schedule the thread to terminate as soon as possible.
     #[inline]
     pub fn terminate(&self) -> ClientThreadOutcome {
@@ -348,13 +430,15 @@ impl ClientThread {
             return false;
         }
 
-        if self.user_entry.lock().active_mute.is_some() {
-            // blocked from chat
-            return false;
-        }
-
-        // check for slowmode stuffs
+        // check for slowmode stuffs.
+        // text messages are rate limited per channel and can be commands, which is only known after decoding,
+        // so muted players and rate limits are handled there
         if voice {
+            if self.user_entry.lock().active_mute.is_some() {
+                // blocked from chat
+                return false;
+            }
+
             if len > MAX_VOICE_PACKET_SIZE {
                 // voice packet is too big
                 return false;
@@ -362,13 +446,6 @@ impl ClientThread {
 
             // safety: only we can access the rate limiters of our user.
             let block = !unsafe { self.voice_rate_limiter.get_mut().try_tick() };
-            if block {
-                return false;
-            }
-        } else {
-            // if rate limiting is disabled, do not block
-            let block = !self.chat_rate_limiter.as_ref().map_or(true, |x| unsafe { x.get_mut().try_tick() });
-
             if block {
                 return false;
             }
@@ -377,6 +454,24 @@ impl ClientThread {
         true
     }
 
+    /// Returns `false` if the user is sending text messages to the given channel too quickly, `None` means private messages.
+    /// Always returns `true` if chat rate limiting is disabled.
+    fn try_tick_chat_rate_limiter(&self, channel: Option<ChatChannel>) -> bool {
+        let idx = channel.map_or(CHAT_RATE_LIMITER_COUNT - 1, |channel| channel as usize);
+
+        // safety: only we can access the rate limiters of our user.
+        self.chat_rate_limiters.as_ref().map_or(true, |x| unsafe { x[idx].get_mut().try_tick() })
+    }
+
+    pub fn accepts_private_messages_from(&self, sender: i32) -> bool {
+        if self.is_blocked(sender) {
+            return false;
+        }
+
+        let settings = self.privacy_settings.lock();
+        !settings.get_no_private_messages() && (!settings.get_friend_messages_only() || self.is_friend(sender))
+    }
+
     #[inline]
     async fn recv_and_handle(&self, message_size: usize) -> Result<()> {
         // safety: only we can receive data from our client.
@@ -389,25 +484,46 @@ impl ClientThread {
         match message {
             ServerThreadMessage::Packet(mut packet) => self.handle_packet(&mut packet).await?,
             ServerThreadMessage::SmallPacket((mut packet, len)) => self.handle_packet(&mut packet[..len]).await?,
-            ServerThreadMessage::BroadcastText(text_packet) => self.send_packet_static(&text_packet).await?,
-            ServerThreadMessage::BroadcastVoice(voice_packet) => self.send_packet_dynamic(&*voice_packet).await?,
+            ServerThreadMessage::BroadcastText(text_packet) => {
+                if !self.is_blocked(text_packet.player_id) {
+                    self.send_packet_static(&text_packet).await?;
+                }
+            }
+            ServerThreadMessage::BroadcastVoice(voice_packet) => {
+                if !self.is_blocked(voice_packet.player_id) {
+                    self.send_packet_dynamic(&*voice_packet).await?;
+                }
+            }
             ServerThreadMessage::BroadcastNotice(packet) => {
                 self.send_packet_dynamic(&packet).await?;
                 info!("{} is receiving a notice: {}", self.account_data.lock().name, packet.message);
             }
+            ServerThreadMessage::BroadcastStoredNotice(packet) => {
+                self.send_packet_dynamic(&packet).await?;
+                info!("{} is receiving a stored notice: {}", self.account_data.lock().name, packet.message);
+            }
             ServerThreadMessage::BroadcastInvite(packet) => {
-                let invitable = !self.privacy_settings.lock().get_no_invites();
-
-                if invitable {
+                if self.accepts_invites_from(packet.player_data.account_id) {
                     self.send_packet_static(&packet).await?;
                 }
             }
+            ServerThreadMessage::BroadcastInviteStatus(packet) => self.send_packet_static(&packet).await?,
             ServerThreadMessage::BroadcastRoomInfo(packet) => {
                 self.send_packet_static(&packet).await?;
             }
+            ServerThreadMessage::BroadcastTeamChange(packet) => self.send_packet_static(&packet).await?,
             ServerThreadMessage::BroadcastBan(packet) => self.ban(packet.message, packet.expires_at).await?,
             ServerThreadMessage::BroadcastMute(packet) => self.send_packet_dynamic(&packet).await?,
             ServerThreadMessage::BroadcastRoleChange(packet) => self.send_packet_static(&packet).await?,
+            ServerThreadMessage::BroadcastFriendStatus(packet) => {
+                self.update_friend_status(packet.player, packet.status);
+                self.send_packet_static(&packet).await?;
+            }
+            ServerThreadMessage::BroadcastPrivateMessage(packet) => {
+                if !self.is_blocked(packet.sender.account_id) {
+                    self.send_packet_static(&packet).await?;
+                }
+            }
             ServerThreadMessage::BroadcastRoomKicked => self._kicked_from_room().await?,
             ServerThreadMessage::TerminationNotice(message) => self.kick(Cow::Borrowed(message.try_to_str())).await?,
         }
@@ -461,6 +577,15 @@ impl ClientThread {
             /* general */
             SyncIconsPacket::PACKET_ID => self.handle_sync_icons(&mut data).await,
             RequestGlobalPlayerListPacket::PACKET_ID => self.handle_request_global_list(&mut data).await,
+            RequestGlobalPlayerListPagePacket::PACKET_ID => self.handle_request_global_list_page(&mut data).await,
+            RequestFriendListPacket::PACKET_ID => self.handle_request_friend_list(&mut data).await,
+            SendFriendRequestPacket::PACKET_ID => self.handle_send_friend_request(&mut data).await,
+            FriendRequestResponsePacket::PACKET_ID => self.handle_friend_request_response(&mut data).await,
+            RemoveFriendPacket::PACKET_ID => self.handle_remove_friend(&mut data).await,
+            RequestBlockListPacket::PACKET_ID => self.handle_request_block_list(&mut data).await,
+            SetUserBlockedPacket::PACKET_ID => self.handle_set_user_blocked(&mut data).await,
+            PrivateMessagePacket::PACKET_ID => self.handle_private_message(&mut data).await,
+            AcknowledgeNoticePacket::PACKET_ID => self.handle_acknowledge_notice(&mut data).await,
             RequestLevelListPacket::PACKET_ID => self.handle_request_level_list(&mut data).await,
             RequestPlayerCountPacket::PACKET_ID => self.handle_request_player_count(&mut data).await,
             UpdatePlayerStatusPacket::PACKET_ID => self.handle_set_player_status(&mut data).await,
@@ -481,9 +606,16 @@ impl ClientThread {
             RequestRoomPlayerListPacket::PACKET_ID => self.handle_request_room_players(&mut data).await,
             UpdateRoomSettingsPacket::PACKET_ID => self.handle_update_room_settings(&mut data).await,
             RoomSendInvitePacket::PACKET_ID => self.handle_room_invitation(&mut data).await,
+            RoomInviteResponsePacket::PACKET_ID => self.handle_room_invite_response(&mut data).await,
             RequestRoomListPacket::PACKET_ID => self.handle_request_room_list(&mut data).await,
+            RequestRoomListPagePacket::PACKET_ID => self.handle_request_room_list_page(&mut data).await,
             CloseRoomPacket::PACKET_ID => self.handle_close_room(&mut data).await,
             KickRoomPlayerPacket::PACKET_ID => self.handle_kick_room_player(&mut data).await,
+            SetRoomPlayerMutedPacket::PACKET_ID => self.handle_set_room_player_muted(&mut data).await,
+            TransferRoomOwnershipPacket::PACKET_ID => self.handle_transfer_room_ownership(&mut data).await,
+            SetRoomCoHostPacket::PACKET_ID => self.handle_set_room_co_host(&mut data).await,
+            SetRoomTeamsPacket::PACKET_ID => self.handle_set_room_teams(&mut data).await,
+            AssignRoomTeamPacket::PACKET_ID => self.handle_assign_room_team(&mut data).await,
 
             /* admin related */
             AdminAuthPacket::PACKET_ID => self.handle_admin_auth(&mut data).await,
@@ -501,6 +633,16 @@ impl ClientThread {
             AdminSetAdminPasswordPacket::PACKET_ID => self.handle_admin_set_admin_password(&mut data).await,
             AdminEditPunishmentPacket::PACKET_ID => self.handle_admin_edit_punishment(&mut data).await,
             AdminGetPunishmentHistoryPacket::PACKET_ID => self.handle_admin_get_punishment_history(&mut data).await,
+            AdminGetNoticesPacket::PACKET_ID => self.handle_admin_get_notices(&mut data).await,
+            AdminGrantRolePacket::PACKET_ID => self.handle_admin_grant_role(&mut data).await,
+            AdminTotpCodePacket::PACKET_ID => self.handle_admin_totp_code(&mut data).await,
+            AdminGetSessionsPacket::PACKET_ID => self.handle_admin_get_sessions(&mut data).await,
+            AdminRevokeSessionsPacket::PACKET_ID => self.handle_admin_revoke_sessions(&mut data).await,
+            AdminGetWhitelistApplicationsPacket::PACKET_ID => self.handle_admin_get_applications(&mut data).await,
+            AdminReviewApplicationPacket::PACKET_ID => self.handle_admin_review_application(&mut data).await,
+            AdminCreateInviteCodePacket::PACKET_ID => self.handle_admin_create_invite_code(&mut data).await,
+            AdminGetInviteCodesPacket::PACKET_ID => self.handle_admin_get_invite_codes(&mut data).await,
+            AdminDeleteInviteCodePacket::PACKET_ID => self.handle_admin_delete_invite_code(&mut data).await,
 
             x => Err(PacketHandlingError::NoHandler(x)),
         }
@@ -1,4 +1,5 @@
 pub mod admin;
+pub mod commands;
 pub mod connection;
 pub mod game;
 pub mod general;
@@ -23,45 +23,10 @@ macro_rules! admin_error {
     };
 }
 
-#[derive(Clone, Copy)]
-enum AdminPerm {
-    Any,
-    Notice,
-    NoticeToEveryone,
-    Kick,
-    KickEveryone,
-    Mute,
-    Ban,
-    EditRoles,
-    // EditFeaturedLevels,
-    Admin,
-}
-
 impl ClientThread {
-    // check if the user is logged in as admin, and if they have the given permission
-    fn _has_perm(&self, perm: AdminPerm) -> bool {
-        if !self.is_authorized_user.load(Ordering::Relaxed) {
-            return false;
-        }
-
-        let role = self.user_role.lock();
-
-        if role.admin {
-            return true;
-        }
-
-        match perm {
-            AdminPerm::Any => role.can_moderate(),
-            AdminPerm::Notice => role.notices,
-            AdminPerm::NoticeToEveryone => role.notices_to_everyone,
-            AdminPerm::Ban => role.ban,
-            AdminPerm::Mute => role.mute,
-            AdminPerm::Kick => role.kick,
-            AdminPerm::KickEveryone => role.kick_everyone,
-            AdminPerm::EditRoles => role.edit_role,
-            // AdminPerm::EditFeaturedLevels => role.edit_featured_levels,
-            AdminPerm::Admin => false,
-        }
+    // check if the user is logged in as admin, and if they have the given permission (one of `permissions`, or a custom one)
+    pub(super) fn _has_perm(&self, perm: &str) -> bool {
+        self.is_authorized_user.load(Ordering::Relaxed) && self.user_role.lock().has_permission(perm)
     }
 
     fn _update_user_role(&self, from: &ComputedRole) {
@@ -74,15 +39,28 @@ impl ClientThread {
         // make sure we exist in the db!
         self._verify_user_exists(account_id).await?;
 
-        // test for the global password first
-        if packet.key.constant_time_compare(&self.game_server.bridge.central_conf.lock().admin_key) {
-            info!(
-                "[{} ({}) @ {}] just logged into the admin panel (with global password)",
-                self.account_data.lock().name,
-                account_id,
-                self.get_tcp_peer()
+        self.pending_admin_totp.store(false, Ordering::Relaxed);
+
+        // test for the global password first, central only gives it to us if it's allowed to be used (break-glass access)
+        let admin_key = self.game_server.bridge.central_conf.lock().admin_key.clone();
+        if !admin_key.is_empty() && packet.key.constant_time_compare(&admin_key) {
+            let name = self.account_data.lock().name.try_to_string();
+
+            warn!(
+                "[{name} ({account_id}) @ {}] just logged into the admin panel with the global admin key",
+                self.get_tcp_peer(),
             );
 
+            if self.game_server.bridge.has_admin_webhook()
+                && let Err(err) = self
+                    .game_server
+                    .bridge
+                    .send_admin_webhook_message(WebhookMessage::AdminKeyUsed(name, account_id))
+                    .await
+            {
+                warn!("webhook error during admin key login: {err}");
+            }
+
             self.is_authorized_user.store(true, Ordering::Relaxed);
             // give super admin perms
             let role = self.game_server.state.role_manager.get_superadmin();
@@ -98,21 +76,12 @@ impl ClientThread {
 
         match pass_result {
             Ok(true) => {
-                info!(
-                    "[{} ({}) @ {}] just logged into the admin panel",
-                    self.account_data.lock().name,
-                    account_id,
-                    self.get_tcp_peer()
-                );
-
-                self.is_authorized_user.store(true, Ordering::Relaxed);
-
-                let role = self.game_server.state.role_manager.compute(&self.user_entry.lock().user_roles);
-                self._update_user_role(&role);
-
-                self.send_packet_dynamic(&AdminAuthSuccessPacket { role }).await?;
+                if self.user_entry.lock().totp_enabled {
+                    self.pending_admin_totp.store(true, Ordering::Relaxed);
+                    return self.send_packet_static(&AdminTotpRequiredPacket).await;
+                }
 
-                return Ok(());
+                return self._complete_admin_login().await;
             }
             Ok(false) => {}
             Err(e) => {
@@ -140,10 +109,54 @@ impl ClientThread {
         self.send_packet_static(&AdminAuthFailedPacket).await
     });
 
+    gs_handler!(self, handle_admin_totp_code, AdminTotpCodePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        // the password has to be entered again after every wrong code
+        if !self.pending_admin_totp.swap(false, Ordering::Relaxed) {
+            return self.send_packet_static(&AdminAuthFailedPacket).await;
+        }
+
+        match self.game_server.bridge.verify_totp(account_id, packet.code.try_to_string()).await {
+            Ok(true) => return self._complete_admin_login().await,
+            Ok(false) => {}
+            Err(err) => warn!("error verifying 2fa code with the central server: {err}"),
+        }
+
+        info!(
+            "[{} ({}) @ {}] entered a wrong 2fa code when logging into the admin panel",
+            self.account_data.lock().name,
+            account_id,
+            self.get_tcp_peer(),
+        );
+
+        self.send_packet_static(&AdminAuthFailedPacket).await
+    });
+
+    async fn _complete_admin_login(&self) -> Result<()> {
+        info!(
+            "[{} ({}) @ {}] just logged into the admin panel",
+            self.account_data.lock().name,
+            self.account_id.load(Ordering::Relaxed),
+            self.get_tcp_peer()
+        );
+
+        self.is_authorized_user.store(true, Ordering::Relaxed);
+
+        let role = self.game_server.state.role_manager.compute(&self.user_entry.lock().user_roles);
+        self._update_user_role(&role);
+
+        self.send_packet_dynamic(&AdminAuthSuccessPacket { role }).await
+    }
+
     gs_handler!(self, handle_admin_send_notice, AdminSendNoticePacket, packet, {
+        self._handle_admin_send_notice(packet).await
+    });
+
+    pub(super) async fn _handle_admin_send_notice(&self, packet: AdminSendNoticePacket) -> Result<()> {
         let account_id = gs_needauth!(self);
 
-        if !self._has_perm(AdminPerm::Notice) {
+        if !self._has_perm(permissions::NOTICES) {
             return Ok(());
         }
 
@@ -160,7 +173,7 @@ impl ClientThread {
         // i am not proud of this code
         match packet.notice_type {
             AdminSendNoticeType::Everyone => {
-                if !self._has_perm(AdminPerm::NoticeToEveryone) {
+                if !self._has_perm(permissions::NOTICES_TO_EVERYONE) {
                     admin_error!(self, "no permission");
                 }
 
@@ -210,11 +223,46 @@ impl ClientThread {
             AdminSendNoticeType::Person => {
                 let thread = self.game_server.find_user(&packet.player);
 
-                let player_name = thread.as_ref().map_or_else(
+                let mut player_name = thread.as_ref().map_or_else(
                     || "<invalid player>".to_owned(),
                     |thr| thr.account_data.lock().name.try_to_str().to_owned(),
                 );
 
+                // notices to offline users and notices that must be acknowledged are stored on the central server
+                let stored_notice = if !self.game_server.standalone && (thread.is_none() || packet.require_ack) {
+                    let target_id = if let Some(thread) = &thread {
+                        thread.account_id.load(Ordering::Relaxed)
+                    } else {
+                        match self.game_server.bridge.get_user_data(&packet.player).await {
+                            Ok(user) if user.user_name.is_some() => {
+                                player_name = user.user_name.unwrap_or_default();
+                                user.account_id
+                            }
+                            _ => {
+                                admin_error!(self, "failed to find the user");
+                            }
+                        }
+                    };
+
+                    let action = AdminStoreNoticeAction {
+                        account_id: target_id,
+                        issued_by: account_id,
+                        message: notice_packet.message.try_to_string(),
+                        require_ack: packet.require_ack,
+                        delivered: thread.is_some(),
+                    };
+
+                    match self.game_server.bridge.store_notice(&action).await {
+                        Ok(x) => Some(x),
+                        Err(err) => {
+                            warn!("error storing a notice: {err}");
+                            admin_error!(self, err.to_string());
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 let self_name = self.account_data.lock().name.try_to_string();
                 let notice_msg = notice_packet.message.try_to_string();
 
@@ -227,7 +275,7 @@ impl ClientThread {
                     if let Err(err) = self
                         .game_server
                         .bridge
-                        .send_admin_webhook_message(WebhookMessage::NoticeToPerson(self_name, player_name, notice_msg))
+                        .send_admin_webhook_message(WebhookMessage::NoticeToPerson(self_name, player_name.clone(), notice_msg))
                         .await
                     {
                         warn!("webhook error during notice to person: {err}");
@@ -235,12 +283,27 @@ impl ClientThread {
                 }
 
                 if let Some(thread) = thread {
-                    thread.push_new_message(ServerThreadMessage::BroadcastNotice(notice_packet.clone())).await;
+                    let msg = match stored_notice {
+                        Some(notice) => ServerThreadMessage::BroadcastStoredNotice(StoredNoticePacket {
+                            id: notice.id,
+                            message: notice_packet.message.clone(),
+                            require_ack: notice.require_ack,
+                            sent_at: notice.sent_at,
+                        }),
+                        None => ServerThreadMessage::BroadcastNotice(notice_packet.clone()),
+                    };
+
+                    thread.push_new_message(msg).await;
 
                     self.send_packet_dynamic(&AdminSuccessMessagePacket {
                         message: Cow::Owned(format!("Sent notice to {}", thread.account_data.lock().name)),
                     })
                     .await?;
+                } else if stored_notice.is_some() {
+                    self.send_packet_dynamic(&AdminSuccessMessagePacket {
+                        message: Cow::Owned(format!("{player_name} is offline, the notice will be delivered when they log in")),
+                    })
+                    .await?;
                 } else {
                     admin_error!(self, "failed to find the user");
                 }
@@ -252,7 +315,7 @@ impl ClientThread {
                 }
 
                 // if this is a global room, also require the notice to everyone perm
-                if packet.room_id == 0 && !self._has_perm(AdminPerm::NoticeToEveryone) {
+                if packet.room_id == 0 && !self._has_perm(permissions::NOTICES_TO_EVERYONE) {
                     admin_error!(self, "no permission");
                 }
 
@@ -312,17 +375,21 @@ impl ClientThread {
         }
 
         Ok(())
-    });
+    }
 
     gs_handler!(self, handle_admin_disconnect, AdminDisconnectPacket, packet, {
+        self._handle_admin_disconnect(packet).await
+    });
+
+    pub(super) async fn _handle_admin_disconnect(&self, packet: AdminDisconnectPacket) -> Result<()> {
         let _ = gs_needauth!(self);
 
-        if !self._has_perm(AdminPerm::Kick) {
+        if !self._has_perm(permissions::KICK) {
             return Ok(());
         }
 
         // to kick everyone, require admin
-        if &*packet.player == "@everyone" && self._has_perm(AdminPerm::KickEveryone) {
+        if &*packet.player == "@everyone" && self._has_perm(permissions::KICK_EVERYONE) {
             let threads: Vec<_> = self.game_server.clients.lock().values().cloned().collect();
             for thread in threads {
                 thread
@@ -377,12 +444,12 @@ impl ClientThread {
         } else {
             admin_error!(self, "failed to find the user");
         }
-    });
+    }
 
     gs_handler!(self, handle_admin_get_user_state, AdminGetUserStatePacket, packet, {
         let _ = gs_needauth!(self);
 
-        if !self._has_perm(AdminPerm::Any) {
+        if !self.can_moderate() {
             return Ok(());
         }
 
@@ -417,7 +484,7 @@ impl ClientThread {
         let account_id = gs_needauth!(self);
         let self_name = self.account_data.lock().name.try_to_string();
 
-        if !self._has_perm(AdminPerm::Any) {
+        if !self.can_moderate() {
             return Ok(());
         }
 
@@ -447,9 +514,12 @@ impl ClientThread {
     gs_handler!(self, handle_admin_update_username, AdminUpdateUsernamePacket, packet, {
         let _ = gs_needauth!(self);
 
-        self._handle_admin_action(
+        if !self.can_moderate() {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        self._send_admin_action(
             packet.account_id,
-            AdminPerm::Any,
             &AdminUserAction::UpdateUsername(AdminUpdateUsernameAction {
                 account_id: packet.account_id,
                 username: packet.username,
@@ -467,7 +537,7 @@ impl ClientThread {
 
         self._handle_admin_action(
             packet.account_id,
-            AdminPerm::EditRoles,
+            permissions::EDIT_ROLE,
             &AdminUserAction::SetNameColor(AdminSetNameColorAction {
                 account_id: packet.account_id,
                 color: packet.color.to_fast_string(),
@@ -488,7 +558,6 @@ impl ClientThread {
 
         self._verify_user_exists(packet.account_id).await?;
 
-        let thread = self.game_server.get_user_by_id(packet.account_id);
         let editing_self = account_id == packet.account_id;
 
         let my_priority = self.game_server.state.role_manager.compute_priority(&self.user_entry.lock().user_roles);
@@ -496,20 +565,12 @@ impl ClientThread {
 
         let their_priority = if editing_self {
             my_priority
-        } else if let Some(user) = &thread {
-            user.user_role.lock().priority
         } else {
-            match self.game_server.bridge.get_user_data(&packet.account_id).await {
-                Ok(x) => self.game_server.state.role_manager.compute_priority(&x.user_roles),
-                Err(err) => {
-                    warn!("error fetching data from the bridge: {err}");
-                    admin_error!(self, err.to_string());
-                }
-            }
+            self._get_user_priority(packet.account_id).await?
         };
 
         // disallow editing overall if we <= them, and additionally disallow assigning roles >= us
-        if !self._has_perm(AdminPerm::Admin) {
+        if !self._has_perm(permissions::ADMIN) {
             if my_priority <= their_priority && !editing_self {
                 admin_error!(self, "cannot edit roles of a user with higher roles than you");
             } else if their_new_priority >= my_priority {
@@ -519,7 +580,7 @@ impl ClientThread {
 
         self._handle_admin_action(
             packet.account_id,
-            AdminPerm::EditRoles,
+            permissions::EDIT_ROLE,
             &AdminUserAction::SetUserRoles(AdminSetUserRolesAction {
                 account_id: packet.account_id,
                 roles: packet.roles,
@@ -528,19 +589,77 @@ impl ClientThread {
         )
         .await?;
 
-        // if the user is online on the server, update live
-        if let Some(user) = self.game_server.get_user_by_id(packet.account_id) {
+        self._notify_roles_updated(packet.account_id).await;
+        self._send_admin_success(packet.account_id).await
+    });
+
+    gs_handler!(self, handle_admin_grant_role, AdminGrantRolePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        let granted_roles = std::slice::from_ref(&packet.role_id);
+
+        if !self.game_server.state.role_manager.all_valid(granted_roles) {
+            admin_error!(self, "attempting to grant an invalid role");
+        }
+
+        if packet.expires_at <= UNIX_EPOCH.elapsed().unwrap().as_secs() {
+            admin_error!(self, "invalid expiration date");
+        }
+
+        self._verify_user_exists(packet.account_id).await?;
+
+        let editing_self = account_id == packet.account_id;
+
+        let my_priority = self.game_server.state.role_manager.compute_priority(&self.user_entry.lock().user_roles);
+        let role_priority = self.game_server.state.role_manager.compute_priority(granted_roles);
+
+        let their_priority = if editing_self {
+            my_priority
+        } else {
+            self._get_user_priority(packet.account_id).await?
+        };
+
+        // same rules as when setting roles
+        if !self._has_perm(permissions::ADMIN) {
+            if my_priority <= their_priority && !editing_self {
+                admin_error!(self, "cannot edit roles of a user with higher roles than you");
+            } else if role_priority >= my_priority {
+                admin_error!(self, "cannot grant a role higher than yours");
+            }
+        }
+
+        self._handle_admin_action(
+            packet.account_id,
+            permissions::EDIT_ROLE,
+            &AdminUserAction::GrantRole(AdminGrantRoleAction {
+                account_id: packet.account_id,
+                role_id: packet.role_id,
+                expires_at: packet.expires_at,
+                issued_by: account_id,
+            }),
+        )
+        .await?;
+
+        self._notify_roles_updated(packet.account_id).await;
+        self._send_admin_success(packet.account_id).await
+    });
+
+    // if the user is online on the server, send them their new roles
+    async fn _notify_roles_updated(&self, account_id: i32) {
+        if let Some(user) = self.game_server.get_user_by_id(account_id) {
             let pkt = ServerThreadMessage::BroadcastRoleChange(RolesUpdatedPacket {
                 special_user_data: user.account_data.lock().special_user_data.clone(),
             });
 
             user.push_new_message(pkt).await;
         }
+    }
 
-        self._send_admin_success(packet.account_id).await
+    gs_handler!(self, handle_admin_punish_user, AdminPunishUserPacket, packet, {
+        self._handle_admin_punish_user(packet).await
     });
 
-    gs_handler!(self, handle_admin_punish_user, AdminPunishUserPacket, packet, {
+    pub(super) async fn _handle_admin_punish_user(&self, packet: AdminPunishUserPacket) -> Result<()> {
         let account_id = gs_needauth!(self);
 
         // verify the punishment is correct
@@ -548,7 +667,7 @@ impl ClientThread {
             admin_error!(self, "invalid expiration date");
         }
 
-        self._verify_user_exists(packet.account_id).await?;
+        let user_entry = self._verify_user_exists(packet.account_id).await?;
 
         let thread = self.game_server.get_user_by_id(packet.account_id);
         let editing_self = account_id == packet.account_id;
@@ -557,28 +676,30 @@ impl ClientThread {
 
         let their_priority = if editing_self {
             my_priority
-        } else if let Some(user) = &thread {
-            user.user_role.lock().priority
         } else {
-            // oh well we gotta make a bridge req to get their prio
-            match self.game_server.bridge.get_user_data(&packet.account_id).await {
-                Ok(x) => self.game_server.state.role_manager.compute_priority(&x.user_roles),
-                Err(err) => {
-                    warn!("error fetching data from the bridge: {err}");
-                    admin_error!(self, err.to_string());
-                }
-            }
+            self._get_user_priority(packet.account_id).await?
         };
 
         // cannot ban role above you or at your level
-        if packet.is_ban && my_priority <= their_priority && !self._has_perm(AdminPerm::Admin) {
+        if packet.is_ban && my_priority <= their_priority && !self._has_perm(permissions::ADMIN) {
             admin_error!(self, "cannot ban user above or at your permission level");
         }
 
+        if let Some(limits) = self._punishment_limits(packet.is_ban) {
+            if let Err(msg) = limits.check_expiry(packet.expires_at, UNIX_EPOCH.elapsed().unwrap().as_secs()) {
+                admin_error!(self, msg);
+            }
+
+            // punishing replaces the currently active punishment
+            if !limits.edit_higher && !self._can_edit_punishment(&user_entry, packet.is_ban).await? {
+                admin_error!(self, "cannot replace a punishment issued by someone above your permission level");
+            }
+        }
+
         let _ = self
             ._handle_admin_action(
                 packet.account_id,
-                if packet.is_ban { AdminPerm::Ban } else { AdminPerm::Mute },
+                if packet.is_ban { permissions::BAN } else { permissions::MUTE },
                 &AdminUserAction::PunishUser(AdminPunishUserAction {
                     account_id: packet.account_id,
                     is_ban: packet.is_ban,
@@ -607,16 +728,23 @@ impl ClientThread {
         }
 
         self._send_admin_success(packet.account_id).await
-    });
+    }
 
     gs_handler!(self, handle_admin_remove_punishment, AdminRemovePunishmentPacket, packet, {
         let account_id = gs_needauth!(self);
 
-        self._verify_user_exists(packet.account_id).await?;
+        let user_entry = self._verify_user_exists(packet.account_id).await?;
+
+        if let Some(limits) = self._punishment_limits(packet.is_ban)
+            && !limits.edit_higher
+            && !self._can_edit_punishment(&user_entry, packet.is_ban).await?
+        {
+            admin_error!(self, "cannot remove a punishment issued by someone above your permission level");
+        }
 
         self._handle_admin_action(
             packet.account_id,
-            if packet.is_ban { AdminPerm::Ban } else { AdminPerm::Mute },
+            if packet.is_ban { permissions::BAN } else { permissions::MUTE },
             &AdminUserAction::RemovePunishment(AdminRemovePunishmentAction {
                 account_id: packet.account_id,
                 is_ban: packet.is_ban,
@@ -635,7 +763,7 @@ impl ClientThread {
 
         self._handle_admin_action(
             packet.account_id,
-            AdminPerm::Ban,
+            permissions::BAN,
             &AdminUserAction::Whitelist(AdminWhitelistAction {
                 account_id: packet.account_id,
                 state: packet.state,
@@ -654,7 +782,7 @@ impl ClientThread {
 
         self._handle_admin_action(
             packet.account_id,
-            AdminPerm::Admin,
+            permissions::ADMIN,
             &AdminUserAction::SetAdminPassword(AdminSetAdminPasswordAction {
                 account_id: packet.account_id,
                 new_password: packet.new_password,
@@ -673,11 +801,21 @@ impl ClientThread {
             admin_error!(self, "invalid expiration date");
         }
 
-        self._verify_user_exists(packet.account_id).await?;
+        let user_entry = self._verify_user_exists(packet.account_id).await?;
+
+        if let Some(limits) = self._punishment_limits(packet.is_ban) {
+            if let Err(msg) = limits.check_expiry(packet.expires_at, UNIX_EPOCH.elapsed().unwrap().as_secs()) {
+                admin_error!(self, msg);
+            }
+
+            if !limits.edit_higher && !self._can_edit_punishment(&user_entry, packet.is_ban).await? {
+                admin_error!(self, "cannot edit a punishment issued by someone above your permission level");
+            }
+        }
 
         self._handle_admin_action(
             packet.account_id,
-            if packet.is_ban { AdminPerm::Ban } else { AdminPerm::Mute },
+            if packet.is_ban { permissions::BAN } else { permissions::MUTE },
             &AdminUserAction::EditPunishment(AdminEditPunishmentAction {
                 account_id: packet.account_id,
                 is_ban: packet.is_ban,
@@ -695,13 +833,22 @@ impl ClientThread {
     async fn _handle_admin_action(
         &self,
         user_account_id: i32,
-        perm: AdminPerm,
+        perm: &str,
         action: &AdminUserAction,
     ) -> Result<(Option<UserPunishment>, Option<UserPunishment>)> {
         if !self._has_perm(perm) {
             return Err(PacketHandlingError::NoPermission);
         }
 
+        self._send_admin_action(user_account_id, action).await
+    }
+
+    // same as `_handle_admin_action` but without checking permissions
+    async fn _send_admin_action(
+        &self,
+        user_account_id: i32,
+        action: &AdminUserAction,
+    ) -> Result<(Option<UserPunishment>, Option<UserPunishment>)> {
         if self.game_server.standalone {
             self.send_packet_dynamic(&AdminErrorPacket {
                 message: Cow::Borrowed("This cannot be done on a standalone server"),
@@ -762,6 +909,56 @@ impl ClientThread {
         }
     }
 
+    // returns the punishment limits of this user, or `None` if they are a super admin or can't issue this punishment at all
+    fn _punishment_limits(&self, is_ban: bool) -> Option<PunishmentLimits> {
+        if self._has_perm(permissions::ADMIN) {
+            return None;
+        }
+
+        self.game_server
+            .state
+            .role_manager
+            .punishment_limits(&self.user_entry.lock().user_roles, is_ban)
+    }
+
+    // check if the currently active punishment of the user wasn't issued by someone with a higher priority than us
+    async fn _can_edit_punishment(&self, user: &UserEntry, is_ban: bool) -> Result<bool> {
+        let punishment = if is_ban { user.active_ban.as_ref() } else { user.active_mute.as_ref() };
+
+        let Some(issued_by) = punishment.and_then(|p| p.issued_by) else {
+            return Ok(true);
+        };
+
+        if issued_by == self.account_id.load(Ordering::Relaxed) {
+            return Ok(true);
+        }
+
+        let my_priority = self.game_server.state.role_manager.compute_priority(&self.user_entry.lock().user_roles);
+        let their_priority = self._get_user_priority(issued_by).await?;
+
+        Ok(their_priority <= my_priority)
+    }
+
+    async fn _get_user_priority(&self, account_id: i32) -> Result<i32> {
+        if let Some(user) = self.game_server.get_user_by_id(account_id) {
+            return Ok(user.user_role.lock().priority);
+        }
+
+        // oh well we gotta make a bridge req to get their prio
+        match self.game_server.bridge.get_user_data(&account_id).await {
+            Ok(x) => Ok(self.game_server.state.role_manager.compute_priority(&x.user_roles)),
+            Err(err) => {
+                warn!("error fetching data from the bridge: {err}");
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(err.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(err))
+            }
+        }
+    }
+
     async fn _send_admin_success(&self, account_id: i32) -> Result<()> {
         let user_entry = match self.game_server.bridge.get_user_data(&account_id).await {
             Ok(x) => x,
@@ -807,7 +1004,7 @@ impl ClientThread {
     gs_handler!(self, handle_admin_get_punishment_history, AdminGetPunishmentHistoryPacket, packet, {
         let _ = gs_needauth!(self);
 
-        if !self._has_perm(AdminPerm::Any) {
+        if !self.can_moderate() {
             return Err(PacketHandlingError::NoPermission);
         }
 
@@ -844,4 +1041,309 @@ impl ClientThread {
             }
         }
     });
+
+    gs_handler!(self, handle_admin_get_notices, AdminGetNoticesPacket, packet, {
+        let _ = gs_needauth!(self);
+
+        if !self._has_perm(permissions::NOTICES) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        match self.game_server.bridge.get_notices(packet.account_id).await {
+            Ok(notices) => {
+                // the client needs the names of the admins that sent the notices
+                let mut ids = notices.iter().map(|x| x.issued_by).collect::<Vec<_>>();
+                ids.sort_unstable();
+                ids.dedup();
+
+                let admin_name_data = match self.game_server.bridge.get_many_names(&ids).await {
+                    Ok(x) => x,
+                    Err(err) => {
+                        warn!("error fetching data from the bridge: {err}");
+                        Vec::new()
+                    }
+                };
+
+                self.send_packet_dynamic(&AdminNoticeListPacket { notices, admin_name_data }).await
+            }
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_get_sessions, AdminGetSessionsPacket, packet, {
+        let _ = gs_needauth!(self);
+
+        if !self._has_perm(permissions::MANAGE_SESSIONS) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        match self.game_server.bridge.get_sessions(packet.account_id).await {
+            Ok(sessions) => {
+                self.send_packet_dynamic(&AdminSessionListPacket {
+                    account_id: packet.account_id,
+                    sessions,
+                })
+                .await
+            }
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_revoke_sessions, AdminRevokeSessionsPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self._has_perm(permissions::MANAGE_SESSIONS) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        // revoking your own sessions is always allowed, other users must have a lower priority than you
+        if account_id != packet.account_id && !self._has_perm(permissions::ADMIN) {
+            let my_priority = self.user_role.lock().priority;
+
+            if my_priority <= self._get_user_priority(packet.account_id).await? {
+                admin_error!(self, "cannot revoke sessions of a user with higher roles than you");
+            }
+        }
+
+        self._send_admin_action(
+            packet.account_id,
+            &AdminUserAction::RevokeSessions(AdminRevokeSessionsAction {
+                issued_by: account_id,
+                account_id: packet.account_id,
+                session_id: packet.session_id.try_to_string(),
+            }),
+        )
+        .await?;
+
+        // if the user is online with a session that was just revoked, disconnect them
+        if let Some(thread) = self.game_server.get_user_by_id(packet.account_id) {
+            let revoked = packet.session_id.is_empty() || thread.session_id.lock().as_deref() == Some(packet.session_id.try_to_str());
+
+            if revoked {
+                thread
+                    .push_new_message(ServerThreadMessage::TerminationNotice(FastString::new(
+                        "Your session has been revoked, please log in again.",
+                    )))
+                    .await;
+            }
+        }
+
+        self._send_admin_success_msg("Successfully revoked the sessions").await
+    });
+
+    gs_handler!(self, handle_admin_get_applications, AdminGetWhitelistApplicationsPacket, packet, {
+        let _ = gs_needauth!(self);
+
+        if !self._has_perm(permissions::BAN) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        match self.game_server.bridge.get_whitelist_applications(packet.page).await {
+            Ok(applications) => {
+                self.send_packet_dynamic(&AdminWhitelistApplicationsPacket {
+                    page: packet.page,
+                    applications,
+                })
+                .await
+            }
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_review_application, AdminReviewApplicationPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        self._handle_admin_action(
+            packet.account_id,
+            permissions::BAN,
+            &AdminUserAction::ReviewApplication(AdminReviewApplicationAction {
+                issued_by: account_id,
+                account_id: packet.account_id,
+                approve: packet.approve,
+                reason: packet.reason.try_to_string(),
+            }),
+        )
+        .await?;
+
+        self._send_admin_success_msg(if packet.approve {
+            "Successfully approved the application"
+        } else {
+            "Successfully denied the application"
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_admin_create_invite_code, AdminCreateInviteCodePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self._has_perm(permissions::BAN) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        if packet.max_uses == 0 {
+            admin_error!(self, "invite code must have at least one use");
+        }
+
+        if packet.expires_at != 0 && packet.expires_at <= UNIX_EPOCH.elapsed().unwrap().as_secs() {
+            admin_error!(self, "invalid expiration date");
+        }
+
+        let role_id = (!packet.role_id.is_empty()).then(|| packet.role_id.try_to_string());
+
+        // giving out roles follows the same rules as granting them directly
+        if let Some(role_id) = &role_id {
+            if !self._has_perm(permissions::EDIT_ROLE) {
+                return Err(PacketHandlingError::NoPermission);
+            }
+
+            let granted_roles = std::slice::from_ref(role_id);
+
+            if !self.game_server.state.role_manager.all_valid(granted_roles) {
+                admin_error!(self, "attempting to grant an invalid role");
+            }
+
+            let my_priority = self.game_server.state.role_manager.compute_priority(&self.user_entry.lock().user_roles);
+            let role_priority = self.game_server.state.role_manager.compute_priority(granted_roles);
+
+            if !self._has_perm(permissions::ADMIN) && role_priority >= my_priority {
+                admin_error!(self, "cannot grant a role higher than yours");
+            }
+        }
+
+        let action = AdminCreateInviteCodeAction {
+            issued_by: account_id,
+            max_uses: packet.max_uses,
+            expires_at: packet.expires_at,
+            role_id,
+        };
+
+        match self.game_server.bridge.create_invite_code(&action).await {
+            Ok(code) => {
+                if self.game_server.bridge.has_admin_webhook() {
+                    let name = self.account_data.lock().name.try_to_string();
+
+                    if let Err(err) = self
+                        .game_server
+                        .bridge
+                        .send_admin_webhook_message(WebhookMessage::InviteCodeCreated(
+                            name,
+                            action.max_uses,
+                            action.expires_at,
+                            action.role_id,
+                        ))
+                        .await
+                    {
+                        warn!("webhook error during invite code creation: {err}");
+                    }
+                }
+
+                self.send_packet_dynamic(&AdminInviteCodeCreatedPacket { code }).await
+            }
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_get_invite_codes, AdminGetInviteCodesPacket, _packet, {
+        let _ = gs_needauth!(self);
+
+        if !self._has_perm(permissions::BAN) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        match self.game_server.bridge.get_invite_codes().await {
+            Ok(codes) => self.send_packet_dynamic(&AdminInviteCodeListPacket { codes }).await,
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
+
+    gs_handler!(self, handle_admin_delete_invite_code, AdminDeleteInviteCodePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self._has_perm(permissions::BAN) {
+            return Err(PacketHandlingError::NoPermission);
+        }
+
+        if self.game_server.standalone {
+            admin_error!(self, "This cannot be done on a standalone server");
+        }
+
+        let action = AdminDeleteInviteCodeAction {
+            issued_by: account_id,
+            // codes are always stored in uppercase
+            code: packet.code.try_to_str().trim().to_uppercase(),
+        };
+
+        match self.game_server.bridge.delete_invite_code(&action).await {
+            Ok(()) => self._send_admin_success_msg("Successfully deleted the invite code").await,
+
+            Err(e) => {
+                self.send_packet_dynamic(&AdminErrorPacket {
+                    message: Cow::Owned(e.to_string()),
+                })
+                .await?;
+
+                Err(PacketHandlingError::BridgeError(e))
+            }
+        }
+    });
 }
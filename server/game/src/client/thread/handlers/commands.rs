@@ -0,0 +1,257 @@
+use std::time::UNIX_EPOCH;
+
+use globed_shared::{
+    permissions,
+    rand::{self, Rng},
+};
+
+use super::*;
+
+/// max amount of names listed by the `/players` command
+const MAX_LISTED_PLAYERS: usize = 50;
+
+const HELP_MESSAGE: &str = "Available commands: /help, /msg <player> <message>, /roll [max], /players";
+const MOD_HELP_MESSAGE: &str = "Moderator commands: /kick <player> [reason], /mute <player> <duration> [reason], /notice <message>";
+
+/// Parses a duration like `30m`, `12h` or `7d` into seconds. `perm` and `0` mean a permanent punishment.
+fn parse_duration(input: &str) -> Option<u64> {
+    if input == "perm" || input == "0" {
+        return Some(0);
+    }
+
+    let (num, unit) = input.split_at(input.find(|c: char| !c.is_ascii_digit())?);
+    let num = num.parse::<u64>().ok().filter(|x| *x > 0)?;
+
+    let mult = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        _ => return None,
+    };
+
+    num.checked_mul(mult)
+}
+
+impl ClientThread {
+    /// Handles a chat message that starts with a `/`. The message is never broadcast to other players,
+    /// replies are sent only to the player who used the command.
+    pub(super) async fn _handle_chat_command(&self, input: &str) -> Result<()> {
+        let _ = gs_needauth!(self);
+
+        let input = input.trim_start_matches('/');
+        let (name, args) = input.split_once(' ').map_or((input, ""), |(name, args)| (name, args.trim()));
+        let name = name.to_ascii_lowercase();
+
+        // commands share the rate limit of private messages, `/msg` gets counted once the message is sent
+        if name != "msg" && !self.try_tick_chat_rate_limiter(None) {
+            return Ok(());
+        }
+
+        match name.as_str() {
+            "help" => self._command_help().await,
+            "msg" => self._command_msg(args).await,
+            "roll" => self._command_roll(args).await,
+            "players" => self._command_players().await,
+            "kick" => self._command_kick(args).await,
+            "mute" => self._command_mute(args).await,
+            "notice" => self._command_notice(args).await,
+            _ => {
+                let response = self
+                    .game_server
+                    .bridge
+                    .central_conf
+                    .lock()
+                    .chat_commands
+                    .iter()
+                    .find(|cmd| cmd.name.eq_ignore_ascii_case(&name))
+                    .map(|cmd| cmd.response.clone());
+
+                match response {
+                    Some(response) => self._send_command_response(response).await,
+                    None => {
+                        self._send_command_response(format!("Unknown command /{name}, use /help to see all commands"))
+                            .await
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like `find_user`, but players that are hidden from this player are treated as offline,
+    /// so that commands can't be used to find out whether they are online.
+    fn _find_visible_user(&self, name: &str) -> Option<Arc<ClientThread>> {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+
+        self.game_server.find_user(name).filter(|thread| thread.is_visible_to(account_id))
+    }
+
+    async fn _send_command_response(&self, message: impl Into<String>) -> Result<()> {
+        self.send_packet_dynamic(&ChatCommandResponsePacket { message: message.into() }).await
+    }
+
+    async fn _command_help(&self) -> Result<()> {
+        let mut message = HELP_MESSAGE.to_owned();
+
+        {
+            let conf = self.game_server.bridge.central_conf.lock();
+            for cmd in &conf.chat_commands {
+                message.push_str(", /");
+                message.push_str(&cmd.name);
+            }
+        }
+
+        if self.can_moderate() {
+            message.push('\n');
+            message.push_str(MOD_HELP_MESSAGE);
+        }
+
+        self._send_command_response(message).await
+    }
+
+    async fn _command_msg(&self, args: &str) -> Result<()> {
+        let Some((player, message)) = args.split_once(' ') else {
+            return self._send_command_response("Usage: /msg <player> <message>").await;
+        };
+
+        let Some(thread) = self._find_visible_user(player) else {
+            return self._send_command_response(format!("Player {player} is not online")).await;
+        };
+
+        // the full chat message fits, so the part after the name always fits too
+        let Ok(message) = InlineString::try_from(message.trim()) else {
+            return Ok(());
+        };
+
+        self._send_private_message(thread.account_id.load(Ordering::Relaxed), message).await
+    }
+
+    async fn _command_roll(&self, args: &str) -> Result<()> {
+        let max = if args.is_empty() {
+            100
+        } else {
+            match args.parse::<u32>() {
+                Ok(x) if x > 0 => x,
+                _ => return self._send_command_response("Usage: /roll [max]").await,
+            }
+        };
+
+        let value = rand::rng().random_range(1..=max);
+
+        self._send_command_response(format!("You rolled {value} (1-{max})")).await
+    }
+
+    async fn _command_players(&self) -> Result<()> {
+        let account_id = self.account_id.load(Ordering::Relaxed);
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        let players = self.game_server.get_player_previews_in_room(room_id, account_id, false);
+
+        let names = players
+            .iter()
+            .take(MAX_LISTED_PLAYERS)
+            .map(|player| player.name.try_to_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut message = if room_id == 0 {
+            format!("{} players online: {names}", players.len())
+        } else {
+            format!("{} players in this room: {names}", players.len())
+        };
+
+        if players.len() > MAX_LISTED_PLAYERS {
+            message.push_str(&format!(" and {} more", players.len() - MAX_LISTED_PLAYERS));
+        }
+
+        self._send_command_response(message).await
+    }
+
+    /* moderator commands, these reuse the admin handlers which respond with the usual admin packets */
+
+    async fn _command_kick(&self, args: &str) -> Result<()> {
+        if !self._has_perm(permissions::KICK) {
+            return self._send_command_response("You don't have permission to use this command").await;
+        }
+
+        let (player, reason) = args.split_once(' ').map_or((args, ""), |(player, reason)| (player, reason.trim()));
+
+        if player.is_empty() {
+            return self._send_command_response("Usage: /kick <player> [reason]").await;
+        }
+
+        let reason = if reason.is_empty() {
+            "You have been kicked from the server."
+        } else {
+            reason
+        };
+
+        self._handle_admin_disconnect(AdminDisconnectPacket {
+            player: FastString::new(player),
+            message: FastString::new(reason),
+        })
+        .await
+    }
+
+    async fn _command_mute(&self, args: &str) -> Result<()> {
+        if !self._has_perm(permissions::MUTE) {
+            return self._send_command_response("You don't have permission to use this command").await;
+        }
+
+        let mut parts = args.splitn(3, ' ');
+        let player = parts.next().unwrap_or_default();
+        let duration = parts.next().and_then(parse_duration);
+        let reason = parts.next().unwrap_or_default().trim();
+
+        let Some(duration) = duration.filter(|_| !player.is_empty()) else {
+            return self
+                ._send_command_response("Usage: /mute <player> <duration> [reason], duration is for example 30m, 12h, 7d or perm")
+                .await;
+        };
+
+        // offline players can only be muted by their account ID
+        let target = match self._find_visible_user(player) {
+            Some(thread) => thread.account_id.load(Ordering::Relaxed),
+            None => match player.parse::<i32>() {
+                Ok(x) => x,
+                Err(_) => return self._send_command_response(format!("Player {player} is not online")).await,
+            },
+        };
+
+        let expires_at = if duration == 0 {
+            0
+        } else {
+            UNIX_EPOCH.elapsed().unwrap().as_secs() + duration
+        };
+
+        self._handle_admin_punish_user(AdminPunishUserPacket {
+            account_id: target,
+            is_ban: false,
+            reason: FastString::new(reason),
+            expires_at,
+        })
+        .await
+    }
+
+    async fn _command_notice(&self, args: &str) -> Result<()> {
+        if !self._has_perm(permissions::NOTICES) {
+            return self._send_command_response("You don't have permission to use this command").await;
+        }
+
+        if args.is_empty() {
+            return self._send_command_response("Usage: /notice <message>").await;
+        }
+
+        // sends to everyone in the current room, in the global room this requires the permission to notice everyone
+        self._handle_admin_send_notice(AdminSendNoticePacket {
+            notice_type: AdminSendNoticeType::RoomOrLevel,
+            room_id: self.room_id.load(Ordering::Relaxed),
+            level_id: 0,
+            player: FastString::default(),
+            message: FastString::new(args),
+            require_ack: false,
+        })
+        .await
+    }
+}
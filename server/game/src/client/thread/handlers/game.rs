@@ -220,13 +220,28 @@ impl ClientThread {
     gs_handler!(self, handle_voice, VoicePacket, packet, {
         let account_id = gs_needauth!(self);
 
+        if self.room.lock().is_player_muted(account_id) {
+            return Ok(());
+        }
+
+        // team-only messages are dropped if the player is not in any team
+        let team_id = if packet.team_only {
+            let Some(team_id) = self.room.lock().get_player_team(account_id) else {
+                return Ok(());
+            };
+
+            Some(team_id)
+        } else {
+            None
+        };
+
         let vpkt = Arc::new(VoiceBroadcastPacket {
             player_id: account_id,
             data: packet.data,
         });
 
         self.game_server
-            .broadcast_voice_packet(&vpkt, self.level_id.load(Ordering::Relaxed), self.room_id.load(Ordering::Relaxed))
+            .broadcast_voice_packet(&vpkt, self.level_id.load(Ordering::Relaxed), self.room_id.load(Ordering::Relaxed), team_id)
             .await;
 
         Ok(())
@@ -239,14 +254,68 @@ impl ClientThread {
             return Ok(());
         }
 
+        // commands are handled before the mute check, so that muted players can still use them
+        if let Ok(message) = packet.message.to_str()
+            && message.starts_with('/')
+        {
+            return self._handle_chat_command(message).await;
+        }
+
+        if self.user_entry.lock().active_mute.is_some() {
+            return Ok(());
+        }
+
+        if !self.try_tick_chat_rate_limiter(Some(packet.channel)) {
+            return Ok(());
+        }
+
+        let room_id = self.room_id.load(Ordering::Relaxed);
+
+        match packet.channel {
+            ChatChannel::Level => {}
+            ChatChannel::Room => {
+                if room_id == 0 {
+                    return Ok(());
+                }
+            }
+            ChatChannel::Global => {
+                if !self.game_server.bridge.is_global_chat_enabled() {
+                    return Ok(());
+                }
+            }
+        }
+
+        // players muted by the room host can still use the global channel
+        if packet.channel != ChatChannel::Global && self.room.lock().is_player_muted(account_id) {
+            return Ok(());
+        }
+
+        // team-only messages are dropped if the player is not in any team
+        let team_id = if packet.team_only && packet.channel != ChatChannel::Global {
+            let Some(team_id) = self.room.lock().get_player_team(account_id) else {
+                return Ok(());
+            };
+
+            Some(team_id)
+        } else {
+            None
+        };
+
         let cpkt = ChatMessageBroadcastPacket {
             player_id: account_id,
+            channel: packet.channel,
             message: packet.message,
         };
 
-        self.game_server
-            .broadcast_chat_packet(&cpkt, self.level_id.load(Ordering::Relaxed), self.room_id.load(Ordering::Relaxed))
-            .await;
+        match packet.channel {
+            ChatChannel::Level => {
+                self.game_server
+                    .broadcast_chat_packet(&cpkt, self.level_id.load(Ordering::Relaxed), room_id, team_id)
+                    .await;
+            }
+            ChatChannel::Room => self.game_server.broadcast_room_chat_packet(&cpkt, room_id, team_id).await,
+            ChatChannel::Global => self.game_server.broadcast_global_chat_packet(&cpkt).await,
+        }
 
         Ok(())
     });
@@ -9,10 +9,30 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_request_global_list, RequestGlobalPlayerListPacket, _packet, {
-        let _ = gs_needauth!(self);
+        let account_id = gs_needauth!(self);
 
         self.send_packet_dynamic(&GlobalPlayerListPacket {
-            players: self.game_server.get_player_previews_in_room(0, self.can_moderate()),
+            players: self.game_server.get_player_previews_in_room(0, account_id, self.can_moderate()),
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_request_global_list_page, RequestGlobalPlayerListPagePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        let (players, total_players) = self.game_server.get_player_preview_page_in_room(
+            0,
+            account_id,
+            packet.name_prefix.try_to_str(),
+            packet.sort,
+            packet.page as usize,
+            self.can_moderate(),
+        );
+
+        self.send_packet_dynamic(&GlobalPlayerListPagePacket {
+            players,
+            page: packet.page,
+            total_players: total_players as u32,
         })
         .await
     });
@@ -86,4 +106,180 @@ impl ClientThread {
 
         self.send_packet_static(&LinkCodeResponsePacket { link_code }).await
     });
+
+    gs_handler!(self, handle_request_friend_list, RequestFriendListPacket, _packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        let list = self.game_server.bridge.get_friend_list(account_id).await?;
+
+        // friends may have been added through another game server, so refresh our cached list
+        *self.friends.lock() = list.friends.iter().copied().collect();
+
+        let friends = self.game_server.get_friend_list_entries(&list.friends).await?;
+        let incoming = self.game_server.get_friend_list_entries(&list.incoming).await?;
+        let outgoing = self.game_server.get_friend_list_entries(&list.outgoing).await?;
+
+        self.send_packet_dynamic(&FriendListPacket {
+            friends,
+            incoming,
+            outgoing,
+        })
+        .await
+    });
+
+    gs_handler!(self, handle_send_friend_request, SendFriendRequestPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        if packet.player == account_id || packet.player <= 0 {
+            return Ok(());
+        }
+
+        let status = self.game_server.bridge.send_friend_request(account_id, packet.player).await?;
+
+        self._friend_status_changed(account_id, packet.player, status).await
+    });
+
+    gs_handler!(self, handle_friend_request_response, FriendRequestResponsePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        let status = self
+            .game_server
+            .bridge
+            .respond_friend_request(account_id, packet.player, packet.accept)
+            .await?;
+
+        self._friend_status_changed(account_id, packet.player, status).await
+    });
+
+    gs_handler!(self, handle_remove_friend, RemoveFriendPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        let status = self.game_server.bridge.remove_friend(account_id, packet.player).await?;
+
+        self._friend_status_changed(account_id, packet.player, status).await
+    });
+
+    gs_handler!(self, handle_request_block_list, RequestBlockListPacket, _packet, {
+        let _ = gs_needauth!(self);
+
+        let blocked = self.blocked.lock().iter().copied().collect::<Vec<_>>();
+
+        self._send_block_list(&blocked).await
+    });
+
+    gs_handler!(self, handle_set_user_blocked, SetUserBlockedPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        if packet.player == account_id || packet.player <= 0 {
+            return Ok(());
+        }
+
+        let blocked = self
+            .game_server
+            .bridge
+            .update_block(account_id, packet.player, packet.blocked)
+            .await?;
+
+        *self.blocked.lock() = blocked.iter().copied().collect();
+
+        // blocking someone also removes them from friends
+        if packet.blocked && self.is_friend(packet.player) {
+            self._friend_status_changed(account_id, packet.player, FriendStatus::None).await?;
+        }
+
+        self._send_block_list(&blocked).await
+    });
+
+    gs_handler!(self, handle_private_message, PrivateMessagePacket, packet, {
+        self._send_private_message(packet.receiver, packet.message).await
+    });
+
+    pub(super) async fn _send_private_message(&self, receiver: i32, message: InlineString<MAX_MESSAGE_SIZE>) -> Result<()> {
+        let account_id = gs_needauth!(self);
+
+        if message.is_empty() || receiver == account_id {
+            return Ok(());
+        }
+
+        let status = if self.user_entry.lock().active_mute.is_some() {
+            PrivateMessageStatus::Muted
+        } else if !self.try_tick_chat_rate_limiter(None) {
+            PrivateMessageStatus::RateLimited
+        } else if message.to_str().map_or(true, |msg| self.game_server.state.filter.is_bad(msg)) {
+            PrivateMessageStatus::Filtered
+        } else if let Some(thread) = self.game_server.get_user_by_id(receiver).filter(|x| x.is_visible_to(account_id)) {
+            // players that are hidden from the sender are reported as offline, so this can't be used to find out if they are online
+            if thread.accepts_private_messages_from(account_id) {
+                let sender = self.account_data.lock().make_preview(!self.privacy_settings.lock().get_hide_roles());
+
+                thread
+                    .push_new_message(ServerThreadMessage::BroadcastPrivateMessage(PrivateMessageReceivedPacket {
+                        sender,
+                        message,
+                    }))
+                    .await;
+
+                PrivateMessageStatus::Delivered
+            } else {
+                PrivateMessageStatus::Unavailable
+            }
+        } else {
+            PrivateMessageStatus::Offline
+        };
+
+        self.send_packet_static(&PrivateMessageStatusPacket { receiver, status }).await
+    }
+
+    gs_handler!(self, handle_acknowledge_notice, AcknowledgeNoticePacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if self.game_server.standalone {
+            return Err(PacketHandlingError::Standalone);
+        }
+
+        self.game_server.bridge.acknowledge_notice(account_id, packet.id).await?;
+
+        Ok(())
+    });
+
+    async fn _send_block_list(&self, blocked: &[i32]) -> Result<()> {
+        let players = self
+            .game_server
+            .get_player_names(blocked)
+            .await?
+            .into_iter()
+            .map(|(account_id, name)| BlockedUserEntry { account_id, name })
+            .collect();
+
+        self.send_packet_dynamic(&BlockListPacket { players }).await
+    }
+
+    /// Updates the cached friends of both players and notifies them about the new status
+    async fn _friend_status_changed(&self, account_id: i32, player: i32, status: FriendStatus) -> Result<()> {
+        self.update_friend_status(player, status);
+
+        self.game_server.broadcast_friend_status(player, account_id, status.reversed()).await;
+
+        self.send_packet_static(&FriendStatusChangedPacket { player, status }).await
+    }
 }
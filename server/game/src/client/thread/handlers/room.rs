@@ -1,5 +1,8 @@
-use crate::webhook::{WebhookChannel, WebhookMessage};
-use std::borrow::Cow;
+use crate::{
+    managers::RoomInvite,
+    webhook::{WebhookChannel, WebhookMessage},
+};
+use std::{borrow::Cow, time::Instant};
 
 use super::*;
 
@@ -82,71 +85,7 @@ impl ClientThread {
     gs_handler!(self, handle_join_room, JoinRoomPacket, packet, {
         let account_id = gs_needauth!(self);
 
-        if !self.game_server.state.room_manager.is_valid_room(packet.room_id) {
-            return self
-                .send_packet_static(&RoomJoinFailedPacket {
-                    was_invalid: true,
-                    ..Default::default()
-                })
-                .await;
-        }
-
-        // check if we are even able to join the room
-        let (was_invalid, was_protected, was_full) = self.game_server.state.room_manager.try_with_any(
-            packet.room_id,
-            |room| {
-                if !room.verify_password(&packet.password) {
-                    (false, true, false)
-                } else if room.is_full() {
-                    (false, false, true)
-                } else {
-                    (false, false, false)
-                }
-            },
-            || (true, false, false),
-        );
-
-        if was_invalid || was_protected || was_full {
-            return self
-                .send_packet_static(&RoomJoinFailedPacket {
-                    was_invalid,
-                    was_protected,
-                    was_full,
-                })
-                .await;
-        }
-
-        let old_room_id = self.room_id.swap(packet.room_id, Ordering::Relaxed);
-
-        // if we somehow tried to join the same room, do nothing
-        if old_room_id == packet.room_id {
-            return Ok(());
-        }
-
-        let level_id = self.level_id.load(Ordering::Relaxed);
-
-        // remove the player from the previously connected room (or the global room)
-        self.game_server.state.room_manager.remove_player(&self.room.lock(), account_id, level_id);
-
-        // set the current room
-        let room = {
-            let mut room = self.room.lock();
-            *room = self.game_server.state.room_manager.get_room_or_global(packet.room_id);
-
-            let mut manager = room.manager.write();
-
-            let is_invisible = self.privacy_settings.lock().get_hide_in_game();
-            manager.create_player(account_id, is_invisible);
-
-            // if we are in any level, clean transition to there
-            if level_id != 0 {
-                manager.add_to_level(level_id, account_id, self.on_unlisted_level.load(Ordering::SeqCst));
-            }
-
-            room.clone()
-        };
-
-        self._respond_with_room_list(room, true).await
+        self._join_room(account_id, packet.room_id, Some(&packet.password)).await
     });
 
     gs_handler!(self, handle_leave_room, LeaveRoomPacket, _packet, {
@@ -176,7 +115,7 @@ impl ClientThread {
 
         let room = self.room.lock().clone();
 
-        if room.owner.load(Ordering::Relaxed) == account_id {
+        if room.is_host(account_id) {
             room.set_settings(packet.settings);
             // send an update packet to all clients
             self.game_server.broadcast_room_info(room).await;
@@ -196,44 +135,90 @@ impl ClientThread {
         }
 
         // if we don't have permission to invite, skip
-        let room_password = {
-            let room = self.room.lock();
-
-            if room.is_protected() && (room.is_public_invites() || room.get_owner() == account_id) {
-                Some(room.password.clone())
-            } else if room.is_protected() {
-                None
-            } else {
-                Some(InlineString::default())
-            }
-        };
-
-        let Some(room_password) = room_password else {
+        if !self._can_invite(account_id) {
             #[cfg(debug_assertions)]
             debug!("invite from {account_id} rejected, user is unable to invite");
             return Ok(());
+        }
+
+        let thread = self
+            .game_server
+            .get_user_by_id(packet.player)
+            .filter(|thread| thread.accepts_invites_from(account_id));
+
+        let Some(thread) = thread else {
+            return self._send_invite_status(packet.player, RoomInviteStatus::Unavailable).await;
         };
 
-        let thread = self.game_server.get_user_by_id(packet.player);
+        let Some(invite) = self
+            .game_server
+            .state
+            .invite_manager
+            .create_invite(room_id, account_id, packet.player)
+        else {
+            return self._send_invite_status(packet.player, RoomInviteStatus::RateLimited).await;
+        };
 
-        if let Some(thread) = thread {
-            let player_data = self.account_data.lock().make_preview(!self.privacy_settings.lock().get_hide_roles());
+        let player_data = self.account_data.lock().make_preview(!self.privacy_settings.lock().get_hide_roles());
 
-            debug!(
-                "{account_id} sent an invite to {} (room: {}, password: {})",
-                packet.player, room_id, room_password
-            );
+        debug!("{account_id} sent an invite to {} (room: {}, invite id: {})", packet.player, room_id, invite.id);
 
-            let invite_packet = RoomInvitePacket {
-                player_data,
-                room_id,
-                room_password,
-            };
+        let invite_packet = RoomInvitePacket {
+            player_data,
+            room_id,
+            invite_id: invite.id,
+        };
+
+        thread.push_new_message(ServerThreadMessage::BroadcastInvite(invite_packet)).await;
+
+        self._send_invite_status(packet.player, RoomInviteStatus::Sent).await
+    });
+
+    gs_handler!(self, handle_room_invite_response, RoomInviteResponsePacket, packet, {
+        let account_id = gs_needauth!(self);
 
-            thread.push_new_message(ServerThreadMessage::BroadcastInvite(invite_packet.clone())).await;
+        let invite = self
+            .game_server
+            .state
+            .invite_manager
+            .take_invite(packet.invite_id, account_id)
+            .filter(|invite| self._is_invite_valid(invite));
+
+        let Some(invite) = invite else {
+            // the invite has expired, never existed, or the inviter can no longer invite to the room
+            if packet.accept {
+                return self
+                    .send_packet_static(&RoomJoinFailedPacket {
+                        was_invalid: true,
+                        ..Default::default()
+                    })
+                    .await;
+            }
+
+            return Ok(());
+        };
+
+        let status = if packet.accept {
+            RoomInviteStatus::Accepted
+        } else {
+            RoomInviteStatus::Declined
+        };
+
+        if let Some(inviter) = self.game_server.get_user_by_id(invite.inviter) {
+            inviter
+                .push_new_message(ServerThreadMessage::BroadcastInviteStatus(RoomInviteStatusPacket {
+                    player_id: account_id,
+                    status,
+                }))
+                .await;
         }
 
-        Ok(())
+        if packet.accept {
+            // accepted invites let you into the room without the password
+            self._join_room(account_id, invite.room_id, None).await
+        } else {
+            Ok(())
+        }
     });
 
     gs_handler!(self, handle_request_room_list, RequestRoomListPacket, _packet, {
@@ -256,6 +241,49 @@ impl ClientThread {
         self.send_packet_dynamic(&pkt).await
     });
 
+    gs_handler!(self, handle_request_room_list_page, RequestRoomListPagePacket, packet, {
+        let _ = gs_needauth!(self);
+
+        let name = packet.filter.name.try_to_str().to_ascii_lowercase();
+        let owner_name = packet.filter.owner_name.try_to_str().to_ascii_lowercase();
+
+        let mut matching: Vec<(usize, Instant, Arc<Room>)> = Vec::new();
+
+        self.game_server.for_every_public_room(
+            |room, _, matching| {
+                if room.matches_filter(&packet.filter, &name, &owner_name) {
+                    matching.push((room.get_player_count(), room.created_at, room.clone()));
+                    true
+                } else {
+                    false
+                }
+            },
+            &mut matching,
+            self.can_moderate(),
+        );
+
+        match packet.sort {
+            RoomListSortOrder::PlayerCount => matching.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1))),
+            RoomListSortOrder::Newest => matching.sort_by_key(|room| std::cmp::Reverse(room.1)),
+        }
+
+        let total_rooms = matching.len();
+
+        let rooms = matching
+            .iter()
+            .skip((packet.page as usize).saturating_mul(ROOM_LIST_PAGE_SIZE))
+            .take(ROOM_LIST_PAGE_SIZE)
+            .map(|(_, _, room)| room.get_room_listing_info(room.id))
+            .collect();
+
+        self.send_packet_dynamic(&RoomListPagePacket {
+            rooms,
+            page: packet.page,
+            total_rooms: total_rooms as u32,
+        })
+        .await
+    });
+
     gs_handler!(self, handle_close_room, CloseRoomPacket, packet, {
         let account_id = gs_needauth!(self);
 
@@ -298,22 +326,150 @@ impl ClientThread {
     });
 
     gs_handler!(self, handle_kick_room_player, KickRoomPlayerPacket, packet, {
-        let _ = gs_needauth!(self);
+        let account_id = gs_needauth!(self);
 
-        if !self.is_in_room() {
+        if !self.is_in_room() || packet.player == account_id {
             return Ok(());
         }
 
-        // check if the player is in our room
-        let has_player = self.room.lock().has_player(packet.player);
+        // only the owner and co-hosts can kick, and only the owner can kick co-hosts
+        let can_kick = {
+            let room = self.room.lock();
 
-        if has_player {
+            if !room.has_player(packet.player) {
+                false
+            } else if self.can_moderate() || room.get_owner() == account_id {
+                true
+            } else {
+                room.is_co_host(account_id) && !room.is_host(packet.player)
+            }
+        };
+
+        if can_kick {
             self.game_server.broadcast_room_kicked(packet.player).await;
         }
 
         Ok(())
     });
 
+    gs_handler!(self, handle_set_room_player_muted, SetRoomPlayerMutedPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self.is_in_room() || packet.player == account_id {
+            return Ok(());
+        }
+
+        // same rules as kicking, co-hosts can only mute regular players
+        let room = self.room.lock();
+
+        let can_mute = if !room.has_player(packet.player) {
+            false
+        } else if self.can_moderate() || room.get_owner() == account_id {
+            true
+        } else {
+            room.is_co_host(account_id) && !room.is_host(packet.player)
+        };
+
+        if can_mute && room.set_player_muted(packet.player, packet.muted) {
+            debug!("room {}: {} set muted = {} for {}", room.id, account_id, packet.muted, packet.player);
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_transfer_room_ownership, TransferRoomOwnershipPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self.is_in_room() {
+            return Ok(());
+        }
+
+        let room = self.room.lock().clone();
+
+        if room.get_owner() != account_id && !self.can_moderate() {
+            return Ok(());
+        }
+
+        if self.game_server.state.room_manager.transfer_ownership(&room, packet.player) {
+            debug!("room {} ownership transferred from {} to {}", room.id, account_id, packet.player);
+            self.game_server.broadcast_room_info(room).await;
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_set_room_co_host, SetRoomCoHostPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self.is_in_room() {
+            return Ok(());
+        }
+
+        let room = self.room.lock().clone();
+
+        // only the owner can appoint co-hosts, but co-hosts are allowed to step down
+        let allowed = room.get_owner() == account_id || self.can_moderate() || (!packet.co_host && packet.player == account_id);
+
+        if allowed && room.set_co_host(packet.player, packet.co_host) {
+            self.game_server.broadcast_room_info(room).await;
+        }
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_set_room_teams, SetRoomTeamsPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self.is_in_room() {
+            return Ok(());
+        }
+
+        let room = self.room.lock().clone();
+
+        if !room.is_host(account_id) && !self.can_moderate() {
+            return Ok(());
+        }
+
+        // team names go through the same filter as room names
+        let names_valid = packet
+            .teams
+            .iter()
+            .all(|team| team.name.to_str().is_ok_and(|name| !self.game_server.state.filter.is_bad(name)));
+
+        if !names_valid {
+            #[cfg(debug_assertions)]
+            debug!("rejecting room teams from {account_id}, invalid team name");
+            return Ok(());
+        }
+
+        room.set_teams(packet.teams.iter().cloned().collect());
+
+        self.game_server.broadcast_room_info(room).await;
+
+        Ok(())
+    });
+
+    gs_handler!(self, handle_assign_room_team, AssignRoomTeamPacket, packet, {
+        let account_id = gs_needauth!(self);
+
+        if !self.is_in_room() {
+            return Ok(());
+        }
+
+        let room = self.room.lock().clone();
+
+        // players can pick a team themselves unless the teams are locked, otherwise only hosts can assign teams
+        let allowed = (packet.player == account_id && !room.is_locked_teams()) || room.is_host(account_id) || self.can_moderate();
+
+        if allowed && room.set_player_team(packet.player, packet.team_id) {
+            self.game_server
+                .broadcast_room_team_change(room.id, packet.player, packet.team_id)
+                .await;
+        }
+
+        Ok(())
+    });
+
     pub async fn _kicked_from_room(&self) -> crate::client::Result<()> {
         self._remove_from_room().await;
 
@@ -322,6 +478,108 @@ impl ClientThread {
         self._respond_with_room_list(room, false).await
     }
 
+    /// Joins the given room, if `password` is `None` the password check is skipped.
+    async fn _join_room(&self, account_id: i32, room_id: u32, password: Option<&InlineString<16>>) -> Result<()> {
+        if !self.game_server.state.room_manager.is_valid_room(room_id) {
+            return self
+                .send_packet_static(&RoomJoinFailedPacket {
+                    was_invalid: true,
+                    ..Default::default()
+                })
+                .await;
+        }
+
+        // check if we are even able to join the room
+        let (was_invalid, was_protected, was_full) = self.game_server.state.room_manager.try_with_any(
+            room_id,
+            |room| {
+                if password.is_some_and(|pwd| !room.verify_password(pwd)) {
+                    (false, true, false)
+                } else if room.is_full() {
+                    (false, false, true)
+                } else {
+                    (false, false, false)
+                }
+            },
+            || (true, false, false),
+        );
+
+        if was_invalid || was_protected || was_full {
+            return self
+                .send_packet_static(&RoomJoinFailedPacket {
+                    was_invalid,
+                    was_protected,
+                    was_full,
+                })
+                .await;
+        }
+
+        let old_room_id = self.room_id.swap(room_id, Ordering::Relaxed);
+
+        // if we somehow tried to join the same room, do nothing
+        if old_room_id == room_id {
+            return Ok(());
+        }
+
+        let level_id = self.level_id.load(Ordering::Relaxed);
+
+        // invites to the previous room are no longer valid
+        self.game_server.state.invite_manager.remove_invites_from(account_id);
+
+        // remove the player from the previously connected room (or the global room)
+        self.game_server.state.room_manager.remove_player(&self.room.lock(), account_id, level_id);
+
+        // set the current room
+        let room = {
+            let mut room = self.room.lock();
+            *room = self.game_server.state.room_manager.get_room_or_global(room_id);
+
+            let mut manager = room.manager.write();
+
+            let is_invisible = self.privacy_settings.lock().get_hide_in_game();
+            manager.create_player(account_id, is_invisible);
+
+            // if we are in any level, clean transition to there
+            if level_id != 0 {
+                manager.add_to_level(level_id, account_id, self.on_unlisted_level.load(Ordering::SeqCst));
+            }
+
+            room.clone()
+        };
+
+        // the configured owner of a persistent room always gets their ownership back when rejoining,
+        // while persistent rooms without a configured owner are hosted by whoever joins them first
+        let owner_changed = room.is_persistent()
+            && (room.orig_owner == account_id || (room.orig_owner == 0 && room.get_owner() == 0))
+            && self.game_server.state.room_manager.transfer_ownership(&room, account_id);
+
+        self._respond_with_room_list(room.clone(), true).await?;
+
+        if owner_changed {
+            self.game_server.broadcast_room_info(room).await;
+        }
+
+        Ok(())
+    }
+
+    /// Whether this player is allowed to invite other players to their current room
+    fn _can_invite(&self, account_id: i32) -> bool {
+        let room = self.room.lock();
+        !room.is_protected() || room.is_public_invites() || room.is_host(account_id)
+    }
+
+    /// Checks that the player who sent the invite is still in the room and is still allowed to invite to it
+    fn _is_invite_valid(&self, invite: &RoomInvite) -> bool {
+        self.game_server
+            .get_user_by_id(invite.inviter)
+            .is_some_and(|inviter| inviter.room_id.load(Ordering::Relaxed) == invite.room_id && inviter._can_invite(invite.inviter))
+    }
+
+    #[inline]
+    async fn _send_invite_status(&self, player_id: i32, status: RoomInviteStatus) -> Result<()> {
+        self.send_packet_static(&RoomInviteStatusPacket { player_id, status }).await
+    }
+
     async fn _remove_from_room(&self) {
         let account_id = self.account_id.load(Ordering::Relaxed);
 
@@ -332,6 +590,9 @@ impl ClientThread {
 
         let level_id = self.level_id.load(Ordering::Relaxed);
 
+        // invites to the room are no longer valid
+        self.game_server.state.invite_manager.remove_invites_from(account_id);
+
         let should_send_update = self.game_server.state.room_manager.remove_player(&self.room.lock(), account_id, level_id);
 
         // if we were the owner, send update packets to everyone
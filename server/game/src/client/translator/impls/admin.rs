@@ -14,3 +14,13 @@ impl Translatable for AdminWhitelistPacket {}
 impl Translatable for AdminSetAdminPasswordPacket {}
 impl Translatable for AdminEditPunishmentPacket {}
 impl Translatable for AdminGetPunishmentHistoryPacket {}
+impl Translatable for AdminGetNoticesPacket {}
+impl Translatable for AdminGrantRolePacket {}
+impl Translatable for AdminTotpCodePacket {}
+impl Translatable for AdminGetSessionsPacket {}
+impl Translatable for AdminRevokeSessionsPacket {}
+impl Translatable for AdminGetWhitelistApplicationsPacket {}
+impl Translatable for AdminReviewApplicationPacket {}
+impl Translatable for AdminCreateInviteCodePacket {}
+impl Translatable for AdminGetInviteCodesPacket {}
+impl Translatable for AdminDeleteInviteCodePacket {}
@@ -6,3 +6,12 @@ impl Translatable for RequestLevelListPacket {}
 impl Translatable for RequestPlayerCountPacket {}
 impl Translatable for UpdatePlayerStatusPacket {}
 impl Translatable for LinkCodeRequestPacket {}
+impl Translatable for RequestGlobalPlayerListPagePacket {}
+impl Translatable for RequestFriendListPacket {}
+impl Translatable for SendFriendRequestPacket {}
+impl Translatable for FriendRequestResponsePacket {}
+impl Translatable for RemoveFriendPacket {}
+impl Translatable for RequestBlockListPacket {}
+impl Translatable for SetUserBlockedPacket {}
+impl Translatable for PrivateMessagePacket {}
+impl Translatable for AcknowledgeNoticePacket {}
@@ -9,3 +9,10 @@ impl Translatable for RoomSendInvitePacket {}
 impl Translatable for RequestRoomListPacket {}
 impl Translatable for CloseRoomPacket {}
 impl Translatable for KickRoomPlayerPacket {}
+impl Translatable for TransferRoomOwnershipPacket {}
+impl Translatable for SetRoomCoHostPacket {}
+impl Translatable for SetRoomTeamsPacket {}
+impl Translatable for AssignRoomTeamPacket {}
+impl Translatable for RequestRoomListPagePacket {}
+impl Translatable for RoomInviteResponsePacket {}
+impl Translatable for SetRoomPlayerMutedPacket {}
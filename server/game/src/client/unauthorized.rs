@@ -5,7 +5,7 @@ use std::{
         atomic::{AtomicBool, AtomicI32, AtomicU16, AtomicU32, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, UNIX_EPOCH},
 };
 
 #[allow(unused_imports)]
@@ -14,7 +14,7 @@ use globed_shared::{
     rand::{self, Rng},
     warn, SyncMutex, MIN_CLIENT_VERSION, MIN_SUPPORTED_PROTOCOL, SUPPORTED_PROTOCOLS,
 };
-use globed_shared::{should_ignore_error, ServerUserEntry, MAX_SUPPORTED_PROTOCOL};
+use globed_shared::{should_ignore_error, token_issuer::TokenValidationFailure, IntSet, ServerUserEntry, StoredNotice, MAX_SUPPORTED_PROTOCOL};
 
 use super::*;
 use crate::{
@@ -52,6 +52,7 @@ pub struct UnauthorizedThread {
     pub account_data: SyncMutex<PlayerAccountData>,
     pub user_entry: SyncMutex<Option<ServerUserEntry>>,
     pub user_role: SyncMutex<Option<ComputedRole>>,
+    pub session_id: SyncMutex<Option<String>>, // id of the session the user logged in with, none if standalone or an older token
 
     pub claim_udp_peer: SyncMutex<Option<SocketAddrV4>>,
     pub claim_udp_notify: Notify,
@@ -65,6 +66,8 @@ pub struct UnauthorizedThread {
     pub translator: NoopPacketTranslator,
 
     pub privacy_settings: SyncMutex<UserPrivacyFlags>,
+    pub friends: SyncMutex<IntSet<i32>>,
+    pub blocked: SyncMutex<IntSet<i32>>,
 }
 
 pub enum UnauthorizedThreadOutcome {
@@ -94,6 +97,7 @@ impl UnauthorizedThread {
             account_data: SyncMutex::new(PlayerAccountData::default()),
             user_entry: SyncMutex::new(None),
             user_role: SyncMutex::new(None),
+            session_id: SyncMutex::new(None),
 
             claim_udp_peer: SyncMutex::new(None),
             claim_udp_notify: Notify::new(),
@@ -107,6 +111,8 @@ impl UnauthorizedThread {
             translator: NoopPacketTranslator::default(),
 
             privacy_settings: SyncMutex::new(UserPrivacyFlags::default()),
+            friends: SyncMutex::new(IntSet::default()),
+            blocked: SyncMutex::new(IntSet::default()),
         }
     }
 
@@ -129,6 +135,7 @@ impl UnauthorizedThread {
             account_data: SyncMutex::new(std::mem::take(&mut *thread.account_data.lock())),
             user_entry: SyncMutex::new(Some(std::mem::take(&mut *thread.user_entry.lock()))),
             user_role: SyncMutex::new(Some(std::mem::take(&mut *thread.user_role.lock()))),
+            session_id: thread.session_id,
 
             claim_udp_peer: SyncMutex::new(None),
             claim_udp_notify: Notify::new(),
@@ -142,6 +149,8 @@ impl UnauthorizedThread {
             translator: NoopPacketTranslator::default(),
 
             privacy_settings: thread.privacy_settings,
+            friends: thread.friends,
+            blocked: thread.blocked,
         }
     }
 
@@ -331,8 +340,18 @@ impl UnauthorizedThread {
         let socket = self.get_socket();
 
         // disconnect if server is under maintenance
-        if self.game_server.bridge.central_conf.lock().maintenance {
-            gs_disconnect!(self, "The server is currently under maintenance, please try connecting again later.");
+        if self.game_server.bridge.is_maintenance() {
+            let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+            let end = self.game_server.bridge.central_conf.lock().scheduled_maintenance.and_then(|x| x.describe_end(now));
+
+            match end {
+                Some(end) => {
+                    gs_disconnect!(self, format!("The server is currently under maintenance ({end}), please try connecting again later."));
+                }
+                None => {
+                    gs_disconnect!(self, "The server is currently under maintenance, please try connecting again later.");
+                }
+            }
         }
 
         if packet.fragmentation_limit < 1300 {
@@ -363,7 +382,7 @@ impl UnauthorizedThread {
             packet.name
         } else {
             // lets verify the given token
-            let result = {
+            let validate = || {
                 self.game_server
                     .bridge
                     .token_issuer
@@ -371,8 +390,18 @@ impl UnauthorizedThread {
                     .validate(packet.account_id, packet.user_id, packet.token.to_str().unwrap())
             };
 
+            let mut result = validate();
+
+            // the token might be signed with a key that was added after we last fetched the configuration
+            if matches!(result, Err(TokenValidationFailure::UnknownKey)) && self.game_server.refresh_signing_keys().await {
+                result = validate();
+            }
+
             match result {
-                Ok(x) => InlineString::new(&x),
+                Ok(claims) => {
+                    *self.session_id.lock() = claims.session_id;
+                    InlineString::new(&claims.account_name)
+                }
                 Err(err) => {
                     let mut message = FastString::new("authentication failed: ");
                     message.extend(err.error_message());
@@ -390,9 +419,28 @@ impl UnauthorizedThread {
         // check if the user is already logged in, kick the other instance
         self.game_server.check_already_logged_in(packet.account_id).await?;
 
+        // notices that were sent while the user was offline, delivered once they are logged in
+        let mut pending_notices = Vec::new();
+
         // fetch data from the central
         if !standalone {
-            let response = match self.game_server.bridge.user_login(packet.account_id, &player_name).await {
+            let session_id = self.session_id.lock().clone();
+
+            let response = match self
+                .game_server
+                .bridge
+                .user_login(packet.account_id, &player_name, session_id.as_deref())
+                .await
+            {
+                Ok(response) if response.session_revoked => {
+                    socket
+                        .send_packet_dynamic(&LoginFailedPacket {
+                            message: Cow::Borrowed("authentication failed: session has been revoked, please log in again"),
+                        })
+                        .await?;
+
+                    return Ok(());
+                }
                 Ok(response) if response.ban.is_some() => {
                     let ban = response.ban.unwrap();
 
@@ -430,6 +478,9 @@ impl UnauthorizedThread {
 
             *self.user_role.lock() = Some(self.game_server.state.role_manager.compute(&response.user_entry.user_roles));
             *self.user_entry.lock() = Some(response.user_entry);
+            *self.friends.lock() = response.friends.into_iter().collect();
+            *self.blocked.lock() = response.blocked.into_iter().collect();
+            pending_notices = response.notices;
             self.link_code.store(response.link_code, Ordering::Relaxed);
         }
 
@@ -471,6 +522,12 @@ impl UnauthorizedThread {
 
         self.send_login_success().await?;
 
+        if !pending_notices.is_empty() {
+            self.deliver_stored_notices(packet.account_id, pending_notices).await?;
+        }
+
+        self.send_login_announcements(packet.platform.try_to_str()).await?;
+
         self.connection_state.store(ClientThreadState::Unclaimed); // as we still need ClaimThreadPacket to arrive
         self.privacy_settings.lock().clone_from(&packet.privacy_settings);
 
@@ -495,6 +552,65 @@ impl UnauthorizedThread {
             .await
     }
 
+    async fn deliver_stored_notices(&self, account_id: i32, notices: Vec<StoredNotice>) -> Result<()> {
+        let socket = self.get_socket();
+        let ids = notices.iter().map(|notice| notice.id).collect();
+
+        for notice in notices {
+            socket
+                .send_packet_dynamic(&StoredNoticePacket {
+                    id: notice.id,
+                    message: FastString::new(&notice.message),
+                    require_ack: notice.require_ack,
+                    sent_at: notice.sent_at,
+                })
+                .await?;
+        }
+
+        if let Err(err) = self.game_server.bridge.mark_notices_delivered(account_id, ids).await {
+            warn!("failed to mark notices as delivered for {account_id}: {err}");
+        }
+
+        Ok(())
+    }
+
+    /// Sends the message of the day and all currently active announcements that apply to this user
+    async fn send_login_announcements(&self, platform: &str) -> Result<()> {
+        let messages = {
+            let conf = self.game_server.bridge.central_conf.lock();
+            let user_entry = self.user_entry.lock();
+            let user_roles = user_entry.as_ref().map_or(&[][..], |x| &x.user_roles);
+            let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+            let mut messages = Vec::new();
+
+            if !conf.motd.is_empty() {
+                messages.push(conf.motd.clone());
+            }
+
+            messages.extend(
+                conf.announcements
+                    .iter()
+                    .filter(|x| x.should_show(now, user_roles, platform))
+                    .map(|x| x.message.clone()),
+            );
+
+            messages
+        };
+
+        let socket = self.get_socket();
+
+        for message in messages {
+            socket
+                .send_packet_dynamic(&ServerNoticePacket {
+                    message: FastString::new(&message),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Blocks until we get notified that we got claimed by a UDP socket.
     async fn wait_for_claimed(&self) {
         {
@@ -4,6 +4,14 @@ pub const MAX_NOTICE_SIZE: usize = 224;
 pub const MAX_MESSAGE_SIZE: usize = 156;
 /// amount of chars in a room id string (6)
 pub const ROOM_ID_LENGTH: usize = 6;
+/// maximum amount of co-hosts a single room can have (8)
+pub const MAX_ROOM_CO_HOSTS: usize = 8;
+/// maximum amount of teams in a single room (8)
+pub const MAX_ROOM_TEAMS: usize = 8;
+/// amount of rooms in a single page of the room list (25)
+pub const ROOM_LIST_PAGE_SIZE: usize = 25;
+/// amount of players in a single page of the global player list (50)
+pub const PLAYER_LIST_PAGE_SIZE: usize = 50;
 
 // this should be the PlayerData size plus some headroom
 pub const SMALL_PACKET_LIMIT: usize = 96;
@@ -22,6 +22,7 @@ pub struct AdminSendNoticePacket {
     pub level_id: LevelId,
     pub player: FastString,
     pub message: FastString,
+    pub require_ack: bool,
 }
 
 #[derive(Packet, Decodable)]
@@ -115,3 +116,68 @@ pub struct AdminEditPunishmentPacket {
 pub struct AdminGetPunishmentHistoryPacket {
     pub account_id: i32,
 }
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19019, encrypted = true)]
+pub struct AdminGetNoticesPacket {
+    pub account_id: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19020, encrypted = true)]
+pub struct AdminGrantRolePacket {
+    pub account_id: i32,
+    pub role_id: String,
+    pub expires_at: u64,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19021, encrypted = true)]
+pub struct AdminTotpCodePacket {
+    pub code: FastString,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19022, encrypted = true)]
+pub struct AdminGetSessionsPacket {
+    pub account_id: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19023, encrypted = true)]
+pub struct AdminRevokeSessionsPacket {
+    pub account_id: i32,
+    pub session_id: FastString, // empty to revoke all sessions
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19024, encrypted = true)]
+pub struct AdminGetWhitelistApplicationsPacket {
+    pub page: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19025, encrypted = true)]
+pub struct AdminReviewApplicationPacket {
+    pub account_id: i32,
+    pub approve: bool,
+    pub reason: FastString,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19026, encrypted = true)]
+pub struct AdminCreateInviteCodePacket {
+    pub max_uses: u32,
+    pub expires_at: u64,     // 0 to never expire
+    pub role_id: FastString, // empty to not give any role
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19027, encrypted = true)]
+pub struct AdminGetInviteCodesPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 19028, encrypted = true)]
+pub struct AdminDeleteInviteCodePacket {
+    pub code: FastString,
+}
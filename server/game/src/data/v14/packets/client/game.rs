@@ -29,10 +29,13 @@ pub struct PlayerDataPacket {
 #[packet(id = 12010, encrypted = true)]
 pub struct VoicePacket {
     pub data: FastEncodedAudioFrame,
+    pub team_only: bool,
 }
 
 #[derive(Packet, Decodable)]
 #[packet(id = 12011, encrypted = true)]
 pub struct ChatMessagePacket {
     pub message: InlineString<MAX_MESSAGE_SIZE>,
+    pub channel: ChatChannel,
+    pub team_only: bool,
 }
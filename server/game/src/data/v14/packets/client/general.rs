@@ -29,3 +29,58 @@ pub struct UpdatePlayerStatusPacket {
 #[derive(Packet, Decodable)]
 #[packet(id = 11005)]
 pub struct LinkCodeRequestPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11006)]
+pub struct RequestGlobalPlayerListPagePacket {
+    pub name_prefix: InlineString<MAX_NAME_SIZE>,
+    pub sort: PlayerListSortOrder,
+    pub page: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11007)]
+pub struct RequestFriendListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11008)]
+pub struct SendFriendRequestPacket {
+    pub player: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11009)]
+pub struct FriendRequestResponsePacket {
+    pub player: i32,
+    pub accept: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11010)]
+pub struct RemoveFriendPacket {
+    pub player: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11011)]
+pub struct RequestBlockListPacket;
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11012)]
+pub struct SetUserBlockedPacket {
+    pub player: i32,
+    pub blocked: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11013, encrypted = true)]
+pub struct PrivateMessagePacket {
+    pub receiver: i32,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 11014)]
+pub struct AcknowledgeNoticePacket {
+    pub id: i64,
+}
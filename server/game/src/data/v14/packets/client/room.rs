@@ -50,3 +50,51 @@ pub struct CloseRoomPacket {
 pub struct KickRoomPlayerPacket {
     pub player: i32,
 }
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13009)]
+pub struct TransferRoomOwnershipPacket {
+    pub player: i32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13010)]
+pub struct SetRoomCoHostPacket {
+    pub player: i32,
+    pub co_host: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13011)]
+pub struct SetRoomTeamsPacket {
+    pub teams: FastVec<RoomTeam, MAX_ROOM_TEAMS>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13012)]
+pub struct AssignRoomTeamPacket {
+    pub player: i32,
+    pub team_id: Option<u8>,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13013)]
+pub struct RequestRoomListPagePacket {
+    pub filter: RoomListFilter,
+    pub sort: RoomListSortOrder,
+    pub page: u32,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13014)]
+pub struct RoomInviteResponsePacket {
+    pub invite_id: u32,
+    pub accept: bool,
+}
+
+#[derive(Packet, Decodable)]
+#[packet(id = 13015)]
+pub struct SetRoomPlayerMutedPacket {
+    pub player: i32,
+    pub muted: bool,
+}
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use globed_shared::{UserEntry, UserPunishment};
+use globed_shared::{InviteCode, StoredNotice, UserEntry, UserPunishment, UserSession, WhitelistApplication};
 
 use crate::{data::*, managers::ComputedRole};
 
@@ -45,3 +45,41 @@ pub struct AdminPunishmentHistoryPacket {
 pub struct AdminSuccessfulUpdatePacket {
     pub user_entry: UserEntry,
 }
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29007, tcp, encrypted)]
+pub struct AdminNoticeListPacket {
+    pub notices: Vec<StoredNotice>,
+    pub admin_name_data: Vec<(i32, String)>,
+}
+
+/// sent instead of `AdminAuthSuccessPacket` if the password is correct but the user has 2fa enabled
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 29008, tcp, encrypted)]
+pub struct AdminTotpRequiredPacket;
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29009, tcp, encrypted)]
+pub struct AdminSessionListPacket {
+    pub account_id: i32,
+    pub sessions: Vec<UserSession>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29010, tcp, encrypted)]
+pub struct AdminWhitelistApplicationsPacket {
+    pub page: u32,
+    pub applications: Vec<WhitelistApplication>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29011, tcp, encrypted)]
+pub struct AdminInviteCodeListPacket {
+    pub codes: Vec<InviteCode>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 29012, tcp, encrypted)]
+pub struct AdminInviteCodeCreatedPacket {
+    pub code: InviteCode,
+}
@@ -62,9 +62,10 @@ pub struct ClaimThreadFailedPacket;
 #[packet(id = 20009, tcp = true)]
 pub struct LoginRecoveryFailedPacket;
 
-// used to communicate a simple message to the user
+// used to communicate a simple message to the user.
+// sent over tcp so that it can be delivered during login, before the udp socket is claimed
 #[derive(Packet, Encodable, DynamicSize, Clone)]
-#[packet(id = 20100, tcp = false)]
+#[packet(id = 20100, tcp = true)]
 pub struct ServerNoticePacket {
     pub message: FastString,
 }
@@ -83,6 +84,16 @@ pub struct ServerMutedPacket {
     pub expires_at: u64,
 }
 
+// a notice that was stored on the central server, sent on login if the user was offline
+#[derive(Packet, Encodable, DynamicSize, Clone)]
+#[packet(id = 20103, tcp = true)]
+pub struct StoredNoticePacket {
+    pub id: i64,
+    pub message: FastString,
+    pub require_ack: bool,
+    pub sent_at: i64,
+}
+
 #[derive(Packet, Encodable, DynamicSize)]
 #[packet(id = 20200, tcp = false)]
 pub struct ConnectionTestResponsePacket {
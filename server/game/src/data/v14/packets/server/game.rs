@@ -32,5 +32,12 @@ pub struct VoiceBroadcastPacket {
 #[packet(id = 22011, encrypted = true, tcp = false)]
 pub struct ChatMessageBroadcastPacket {
     pub player_id: i32,
+    pub channel: ChatChannel,
     pub message: InlineString<MAX_MESSAGE_SIZE>,
 }
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 22012, tcp = true)]
+pub struct ChatCommandResponsePacket {
+    pub message: String,
+}
@@ -33,3 +33,46 @@ pub struct RolesUpdatedPacket {
 pub struct LinkCodeResponsePacket {
     pub link_code: u32,
 }
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21005, tcp = true)]
+pub struct GlobalPlayerListPagePacket {
+    pub players: Vec<PlayerPreviewAccountData>,
+    pub page: u32,
+    pub total_players: u32,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21006, tcp = true)]
+pub struct FriendListPacket {
+    pub friends: Vec<FriendListEntry>,
+    pub incoming: Vec<FriendListEntry>,
+    pub outgoing: Vec<FriendListEntry>,
+}
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 21007)]
+pub struct FriendStatusChangedPacket {
+    pub player: i32,
+    pub status: FriendStatus,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 21008, tcp = true)]
+pub struct BlockListPacket {
+    pub players: Vec<BlockedUserEntry>,
+}
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 21009, encrypted = true, tcp = true)]
+pub struct PrivateMessageReceivedPacket {
+    pub sender: PlayerPreviewAccountData,
+    pub message: InlineString<MAX_MESSAGE_SIZE>,
+}
+
+#[derive(Packet, Encodable, StaticSize)]
+#[packet(id = 21010)]
+pub struct PrivateMessageStatusPacket {
+    pub receiver: i32,
+    pub status: PrivateMessageStatus,
+}
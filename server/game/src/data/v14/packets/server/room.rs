@@ -40,7 +40,7 @@ pub struct RoomInfoPacket {
 pub struct RoomInvitePacket {
     pub player_data: PlayerPreviewAccountData,
     pub room_id: u32,
-    pub room_password: InlineString<16>,
+    pub invite_id: u32,
 }
 
 #[derive(Packet, Encodable, DynamicSize)]
@@ -54,3 +54,25 @@ pub struct RoomListPacket {
 pub struct RoomCreateFailedPacket<'a> {
     pub reason: Cow<'a, str>,
 }
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 23008, tcp = true)]
+pub struct RoomTeamChangedPacket {
+    pub player_id: i32,
+    pub team_id: Option<u8>,
+}
+
+#[derive(Packet, Encodable, DynamicSize)]
+#[packet(id = 23009, tcp = true)]
+pub struct RoomListPagePacket {
+    pub rooms: Vec<RoomListingInfo>,
+    pub page: u32,
+    pub total_rooms: u32,
+}
+
+#[derive(Packet, Encodable, StaticSize, Clone)]
+#[packet(id = 23010, tcp = true)]
+pub struct RoomInviteStatusPacket {
+    pub player_id: i32,
+    pub status: RoomInviteStatus,
+}
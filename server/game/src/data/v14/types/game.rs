@@ -19,6 +19,18 @@ pub enum PlayerIconType {
     Jetpack = 9,
 }
 
+/* ChatChannel */
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum ChatChannel {
+    #[default]
+    Level = 0,  // players on the same level in the same room
+    Room = 1,   // everyone in the same room, no matter the level
+    Global = 2, // everyone on the server, only if enabled by the admins
+}
+
 /* SpiderTeleportData (spider teleport data) */
 #[derive(Clone, Debug, Default, Encodable, Decodable, StaticSize, DynamicSize)]
 #[dynamic_size(as_static = true)]
@@ -122,6 +122,7 @@ impl PlayerAccountData {
             } else {
                 SpecialUserData { roles: None }
             },
+            team_id: None,
         }
     }
 
@@ -151,7 +152,7 @@ pub struct PlayerPreviewAccountData {
     pub special_user_data: SpecialUserData,
 }
 
-/* PlayerRoomPreviewAccountData - similar to previous one but for rooms, the only difference is that it includes a level ID */
+/* PlayerRoomPreviewAccountData - similar to previous one but for rooms, additionally includes a level ID and the team of the player */
 
 #[derive(Clone, Default, Encodable, Decodable, StaticSize, DynamicSize)]
 pub struct PlayerRoomPreviewAccountData {
@@ -161,6 +162,7 @@ pub struct PlayerRoomPreviewAccountData {
     pub icons: PlayerIconDataSimple,
     pub level_id: LevelId,
     pub special_user_data: SpecialUserData,
+    pub team_id: Option<u8>,
 }
 
 /* AssociatedPlayerData */
@@ -9,6 +9,7 @@ pub struct RoomSettingsFlags {
     pub collision: bool,
     pub two_player: bool,
     pub deathlink: bool,
+    pub locked_teams: bool,
 }
 
 #[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug)]
@@ -20,6 +21,31 @@ pub struct RoomSettings {
     pub faster_reset: bool,
 }
 
+impl From<&globed_shared::PersistentRoom> for RoomSettings {
+    fn from(room: &globed_shared::PersistentRoom) -> Self {
+        Self {
+            flags: RoomSettingsFlags {
+                is_hidden: room.is_hidden,
+                public_invites: room.public_invites,
+                collision: room.collision,
+                two_player: room.two_player,
+                deathlink: room.deathlink,
+                locked_teams: room.locked_teams,
+            },
+            player_limit: room.player_limit,
+            level_id: room.level_id,
+            faster_reset: room.faster_reset,
+        }
+    }
+}
+
+#[derive(Clone, Default, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomTeam {
+    pub name: InlineString<16>,
+    pub color: Color3B,
+}
+
 #[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
 #[dynamic_size(as_static = true)]
 pub struct RoomInfo {
@@ -28,6 +54,8 @@ pub struct RoomInfo {
     pub name: InlineString<32>,
     pub password: InlineString<16>,
     pub settings: RoomSettings,
+    pub co_hosts: FastVec<i32, MAX_ROOM_CO_HOSTS>,
+    pub teams: FastVec<RoomTeam, MAX_ROOM_TEAMS>,
 }
 
 #[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
@@ -40,3 +68,34 @@ pub struct RoomListingInfo {
     pub has_password: bool,
     pub settings: RoomSettings,
 }
+
+#[derive(Clone, Default, Encodable, Decodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct RoomListFilter {
+    pub name: InlineString<32>,                  // case-insensitive substring, empty to match any room
+    pub owner_name: InlineString<MAX_NAME_SIZE>, // case-insensitive substring, empty to match any owner
+    pub level_id: LevelId,                       // 0 to match any level
+    pub hide_full: bool,
+    pub hide_protected: bool,
+}
+
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum RoomListSortOrder {
+    #[default]
+    PlayerCount = 0,
+    Newest = 1,
+}
+
+#[derive(Clone, Copy, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum RoomInviteStatus {
+    Sent = 0,
+    Accepted = 1,
+    Declined = 2,
+    Expired = 3,
+    Unavailable = 4, // player is offline or does not accept invites
+    RateLimited = 5,
+}
@@ -2,6 +2,8 @@ use std::str::FromStr;
 
 use crate::data::*;
 
+pub use globed_shared::FriendStatus;
+
 /// Structure that can represent either a single RGB color, or a combination of colors
 #[derive(Clone, Encodable, Decodable, StaticSize, DynamicSize)]
 pub struct RichColor {
@@ -80,6 +82,38 @@ impl UserPrivacyFlags {
     pub fn set_hide_roles(&mut self, s: bool) {
         self.flags.assign_bit(3, s);
     }
+
+    pub fn get_friend_invites_only(&self) -> bool {
+        self.flags.get_bit(4)
+    }
+
+    pub fn set_friend_invites_only(&mut self, s: bool) {
+        self.flags.assign_bit(4, s);
+    }
+
+    pub fn get_visible_to_friends_only(&self) -> bool {
+        self.flags.get_bit(5)
+    }
+
+    pub fn set_visible_to_friends_only(&mut self, s: bool) {
+        self.flags.assign_bit(5, s);
+    }
+
+    pub fn get_no_private_messages(&self) -> bool {
+        self.flags.get_bit(6)
+    }
+
+    pub fn set_no_private_messages(&mut self, s: bool) {
+        self.flags.assign_bit(6, s);
+    }
+
+    pub fn get_friend_messages_only(&self) -> bool {
+        self.flags.get_bit(7)
+    }
+
+    pub fn set_friend_messages_only(&mut self, s: bool) {
+        self.flags.assign_bit(7, s);
+    }
 }
 
 impl Default for UserPrivacyFlags {
@@ -90,7 +124,50 @@ impl Default for UserPrivacyFlags {
         returned.set_no_invites(false);
         returned.set_hide_in_game(false);
         returned.set_hide_roles(false);
+        returned.set_friend_invites_only(false);
+        returned.set_visible_to_friends_only(false);
+        returned.set_no_private_messages(false);
+        returned.set_friend_messages_only(false);
 
         returned
     }
 }
+
+#[derive(Clone, Copy, Default, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum PlayerListSortOrder {
+    #[default]
+    Name = 0,
+    RolePriority = 1,
+}
+
+#[derive(Clone, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct FriendListEntry {
+    pub account_id: i32,
+    pub name: InlineString<MAX_NAME_SIZE>,
+    // the central server does not know which game server a player is connected to,
+    // so this is `false` for friends that are offline or connected to another game server
+    pub on_this_server: bool,
+    pub level_id: LevelId, // 0 if not on this server or not on a visible level
+}
+
+#[derive(Clone, Encodable, StaticSize, DynamicSize)]
+#[dynamic_size(as_static = true)]
+pub struct BlockedUserEntry {
+    pub account_id: i32,
+    pub name: InlineString<MAX_NAME_SIZE>,
+}
+
+#[derive(Clone, Copy, Encodable, Decodable, StaticSize, DynamicSize, Debug, PartialEq, Eq)]
+#[dynamic_size(as_static = true)]
+#[repr(u8)]
+pub enum PrivateMessageStatus {
+    Delivered = 0,
+    Offline = 1,     // the player is not connected to this game server
+    Unavailable = 2, // the player does not accept messages from the sender
+    Muted = 3,
+    Filtered = 4,
+    RateLimited = 5,
+}
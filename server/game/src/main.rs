@@ -301,6 +301,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         debug!("* Maintenance: {}", if gsbd.maintenance { "yes" } else { "no" });
 
         debug!("* Token secret key: '{}'", censor_key(&gsbd.secret_key2, 4));
+        debug!("* Retired token secret keys: {}", gsbd.retired_secret_keys2.len());
 
         if standalone {
             debug!("* Admin key: '{}'", gsbd.admin_key);
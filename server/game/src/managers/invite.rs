@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use globed_shared::{
+    rand::{self, Rng},
+    IntMap, SyncMutex,
+};
+
+use crate::util::SimpleRateLimiter;
+
+/// how long an invite stays valid after being sent
+pub const INVITE_EXPIRY: Duration = Duration::from_secs(300);
+/// how many invites a single player can send in `INVITE_RATE_LIMIT_PERIOD`
+const INVITE_RATE_LIMIT: usize = 5;
+const INVITE_RATE_LIMIT_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct RoomInvite {
+    pub id: u32,
+    pub room_id: u32,
+    pub inviter: i32,
+    pub target: i32,
+    pub expires_at: Instant,
+}
+
+impl RoomInvite {
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+}
+
+// Keeps track of all pending room invites, so that they can be accepted, declined or expire.
+#[derive(Default)]
+pub struct InviteManager {
+    invites: SyncMutex<IntMap<u32, RoomInvite>>,
+    rate_limiters: SyncMutex<IntMap<i32, SimpleRateLimiter>>,
+}
+
+impl InviteManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new invite and returns it, or returns `None` if the inviter is sending invites too quickly.
+    /// If the inviter already has a pending invite to the same player for the same room, it gets replaced.
+    pub fn create_invite(&self, room_id: u32, inviter: i32, target: i32) -> Option<RoomInvite> {
+        let allowed = self
+            .rate_limiters
+            .lock()
+            .entry(inviter)
+            .or_insert_with(|| SimpleRateLimiter::new(INVITE_RATE_LIMIT, INVITE_RATE_LIMIT_PERIOD))
+            .try_tick();
+
+        if !allowed {
+            return None;
+        }
+
+        let mut invites = self.invites.lock();
+
+        invites.retain(|_, inv| !(inv.inviter == inviter && inv.target == target && inv.room_id == room_id));
+
+        // 0 is reserved as an invalid invite id
+        let id = loop {
+            let id = rand::rng().random_range(1..u32::MAX);
+            if !invites.contains_key(&id) {
+                break id;
+            }
+        };
+
+        let invite = RoomInvite {
+            id,
+            room_id,
+            inviter,
+            target,
+            expires_at: Instant::now() + INVITE_EXPIRY,
+        };
+
+        invites.insert(id, invite.clone());
+
+        Some(invite)
+    }
+
+    /// Removes the invite and returns it, if it exists, is addressed to `target` and has not expired yet.
+    pub fn take_invite(&self, invite_id: u32, target: i32) -> Option<RoomInvite> {
+        let mut invites = self.invites.lock();
+
+        if !invites.get(&invite_id).is_some_and(|inv| inv.target == target) {
+            return None;
+        }
+
+        invites.remove(&invite_id).filter(|inv| !inv.is_expired())
+    }
+
+    /// Removes all expired invites and returns them
+    pub fn remove_expired(&self) -> Vec<RoomInvite> {
+        let mut expired = Vec::new();
+
+        self.invites.lock().retain(|_, inv| {
+            if inv.is_expired() {
+                expired.push(inv.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        // rate limiters that have been refilled long ago are of no use anymore
+        self.rate_limiters
+            .lock()
+            .retain(|_, limiter| limiter.since_last_refill() < INVITE_RATE_LIMIT_PERIOD * 2);
+
+        expired
+    }
+
+    /// Removes all invites sent by this player, used when they leave the room they invited to
+    pub fn remove_invites_from(&self, inviter: i32) {
+        self.invites.lock().retain(|_, inv| inv.inviter != inviter);
+    }
+
+    /// Removes all invites sent by or addressed to this player
+    pub fn remove_player(&self, account_id: i32) {
+        self.invites.lock().retain(|_, inv| inv.inviter != account_id && inv.target != account_id);
+    }
+}
@@ -1,7 +1,9 @@
+mod invite;
 mod level;
 mod role;
 mod room;
 
+pub use invite::{InviteManager, RoomInvite};
 pub use level::LevelManager;
 pub use role::{ComputedRole, GameServerRole, RoleManager};
 pub use room::{Room, RoomManager};
@@ -1,5 +1,5 @@
 use crate::data::*;
-use globed_shared::{error, warn, GameServerBootData, IntMap, ServerRole, SyncMutex};
+use globed_shared::{error, permissions, resolve_roles, warn, GameServerBootData, IntMap, PunishmentLimits, ServerRole, SyncMutex};
 
 #[derive(Default)]
 pub struct RoleManager {
@@ -20,6 +20,7 @@ pub struct ComputedRole {
     pub name_color: Option<RichColor>,
     pub chat_color: Option<Color3B>,
 
+    // these are only sent to the client, the server checks `permissions` instead
     pub notices: bool,
     pub notices_to_everyone: bool,
     pub kick: bool,
@@ -29,20 +30,18 @@ pub struct ComputedRole {
     pub edit_role: bool,
     pub edit_featured_levels: bool,
     pub admin: bool,
+
+    pub permissions: Vec<String>,
 }
 
 impl ComputedRole {
-    // check if the user has any mod perms at all
+    pub fn has_permission(&self, perm: &str) -> bool {
+        self.permissions.iter().any(|x| x == permissions::ADMIN || x == perm)
+    }
+
+    // check if the user has any mod perms at all, named permissions count as well
     pub fn can_moderate(&self) -> bool {
-        self.notices
-            || self.notices_to_everyone
-            || self.kick
-            || self.kick_everyone
-            || self.mute
-            || self.ban
-            || self.edit_role
-            || self.edit_featured_levels
-            || self.admin
+        !self.permissions.is_empty()
     }
 }
 
@@ -61,7 +60,13 @@ impl RoleManager {
             panic!("aborting due to too many roles");
         }
 
-        gsbd.roles.iter().enumerate().for_each(|(idx, role)| {
+        // central should already send them resolved, but do it again in case it's an older version
+        let mut server_roles = gsbd.roles.clone();
+        if let Err(e) = resolve_roles(&mut server_roles) {
+            error!("failed to resolve role inheritance: {e}");
+        }
+
+        server_roles.into_iter().enumerate().for_each(|(idx, role)| {
             // print warnings
             if role.badge_icon.len() > 32 {
                 warn!("role id '{}' has a badge sprite of over 32 characters, this is not supported", role.id);
@@ -78,7 +83,7 @@ impl RoleManager {
                 }
             }
 
-            roles.insert(idx as u8, role.clone());
+            roles.insert(idx as u8, role);
         });
     }
 
@@ -156,25 +161,10 @@ impl RoleManager {
                 }
             }
 
-            if role.admin {
-                computed.notices = true;
-                computed.notices_to_everyone = true;
-                computed.kick = true;
-                computed.kick_everyone = true;
-                computed.mute = true;
-                computed.ban = true;
-                computed.edit_role = true;
-                computed.edit_featured_levels = true;
-                computed.admin = true;
-            } else {
-                computed.notices |= role.notices;
-                computed.notices_to_everyone |= role.notices_to_everyone;
-                computed.kick |= role.kick;
-                computed.kick_everyone |= role.kick_everyone;
-                computed.mute |= role.mute;
-                computed.ban |= role.ban;
-                computed.edit_role |= role.edit_role;
-                computed.edit_featured_levels |= role.edit_featured_levels;
+            for perm in &role.permissions {
+                if !computed.permissions.contains(perm) {
+                    computed.permissions.push(perm.clone());
+                }
             }
 
             if is_higher {
@@ -182,6 +172,16 @@ impl RoleManager {
             }
         }
 
+        computed.admin = computed.has_permission(permissions::ADMIN);
+        computed.notices = computed.has_permission(permissions::NOTICES);
+        computed.notices_to_everyone = computed.has_permission(permissions::NOTICES_TO_EVERYONE);
+        computed.kick = computed.has_permission(permissions::KICK);
+        computed.kick_everyone = computed.has_permission(permissions::KICK_EVERYONE);
+        computed.mute = computed.has_permission(permissions::MUTE);
+        computed.ban = computed.has_permission(permissions::BAN);
+        computed.edit_role = computed.has_permission(permissions::EDIT_ROLE);
+        computed.edit_featured_levels = computed.has_permission(permissions::EDIT_FEATURED_LEVELS);
+
         computed
     }
 
@@ -195,6 +195,24 @@ impl RoleManager {
             .unwrap_or(i32::MIN)
     }
 
+    /// Same as `compute_priority` but takes the integer role IDs that are sent to clients
+    pub fn compute_priority_from_int_ids(&self, int_ids: &[u8]) -> i32 {
+        let roles = self.roles.lock();
+
+        int_ids
+            .iter()
+            .map(|id| roles.get(id).map_or(i32::MIN, |x| x.priority))
+            .max()
+            .unwrap_or(i32::MIN)
+    }
+
+    /// Returns the punishment limits for a user with the given roles, or `None` if none of them allow this punishment
+    pub fn punishment_limits(&self, user_roles: &[String], is_ban: bool) -> Option<PunishmentLimits> {
+        let roles = self.roles.lock();
+
+        PunishmentLimits::from_roles(roles.values().filter(|role| user_roles.contains(&role.id)), is_ban)
+    }
+
     // check if all provided role ids are valid and do exist
     pub fn all_valid(&self, user_roles: &[String]) -> bool {
         let roles = self.roles.lock();
@@ -1,16 +1,21 @@
-use std::sync::{
-    atomic::{AtomicI32, Ordering},
-    Arc, OnceLock,
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        Arc, OnceLock,
+    },
+    time::Instant,
 };
 
 use esp::InlineString;
 use globed_shared::{
     rand::{self, Rng},
-    IntMap, SyncMutex, SyncMutexGuard, SyncRwLock,
+    warn, IntMap, IntSet, PersistentRoom, SyncMutex, SyncMutexGuard, SyncRwLock,
 };
 
 use crate::{
-    data::{LevelId, PlayerPreviewAccountData, RoomInfo, RoomListingInfo, RoomSettings, ROOM_ID_LENGTH},
+    data::{
+        LevelId, PlayerPreviewAccountData, RoomInfo, RoomListFilter, RoomListingInfo, RoomSettings, RoomTeam, MAX_ROOM_CO_HOSTS, ROOM_ID_LENGTH,
+    },
     server::GameServer,
 };
 
@@ -20,9 +25,12 @@ use super::LevelManager;
 struct RoomMutableData {
     pub owner: Option<PlayerPreviewAccountData>,
     pub settings: RoomSettings,
+    pub co_hosts: Vec<i32>,
+    pub teams: Vec<RoomTeam>,
+    pub player_teams: IntMap<i32, u8>, // player id : team id
+    pub muted_players: IntSet<i32>,    // kept even after the player leaves, so that rejoining does not unmute them
 }
 
-#[derive(Default)]
 pub struct Room {
     pub orig_owner: i32,
     pub orig_owner_data: Option<PlayerPreviewAccountData>,
@@ -31,12 +39,15 @@ pub struct Room {
     pub password: InlineString<16>,
     pub manager: SyncRwLock<LevelManager>,
     pub id: u32,
+    pub created_at: Instant,
+    persistent: AtomicBool,
     data: SyncMutex<RoomMutableData>,
 }
 
 #[derive(Default)]
 pub struct RoomManager {
     rooms: SyncMutex<IntMap<u32, Arc<Room>>>,
+    persistent_rooms: SyncMutex<IntMap<u32, PersistentRoom>>, // room id : last applied configuration
     global: Arc<Room>,
     game_server: OnceLock<&'static GameServer>,
 }
@@ -63,24 +74,46 @@ impl Room {
             password,
             manager: SyncRwLock::new(manager),
             id,
-            data: SyncMutex::new(RoomMutableData { owner: owner_data, settings }),
+            created_at: Instant::now(),
+            persistent: AtomicBool::new(false),
+            data: SyncMutex::new(RoomMutableData {
+                owner: owner_data,
+                settings,
+                co_hosts: Vec::new(),
+                teams: Vec::new(),
+                player_teams: IntMap::default(),
+                muted_players: IntSet::default(),
+            }),
         }
     }
 
     // Removes a player, if the player was the owner, rotates the owner and returns `true`.
+    // Co-hosts are preferred when picking the new owner, in the order they were appointed.
+    // Persistent rooms with a configured owner only rotate to co-hosts, otherwise they are left without an owner until the configured owner rejoins.
     pub fn remove_player(&self, player: i32) -> bool {
         let was_owner = self.get_owner() == player;
 
+        // lock order: `data` before `manager`, same as in `get_room_listing_info`
+        let mut data = self.data.lock();
         let mut manager = self.manager.write();
 
+        data.co_hosts.retain(|&id| id != player);
+        data.player_teams.remove(&player);
+
         if was_owner {
-            // rotate the owner
-            let mut rotate_to: i32 = 0;
-            manager.for_each_player(|rp| {
-                if rotate_to == 0 && rp.account_id != player {
-                    rotate_to = rp.account_id;
-                }
-            });
+            // rotate the owner, try co-hosts first and fall back to any other player
+            let mut rotate_to = data.co_hosts.iter().copied().find(|&id| manager.has_player(id)).unwrap_or(0);
+
+            if rotate_to == 0 && (!self.is_persistent() || self.orig_owner == 0) {
+                manager.for_each_player(|rp| {
+                    if rotate_to == 0 && rp.account_id != player {
+                        rotate_to = rp.account_id;
+                    }
+                });
+            }
+
+            // the new owner is no longer a co-host
+            data.co_hosts.retain(|&id| id != rotate_to);
 
             self.owner.store(rotate_to, Ordering::Relaxed);
         }
@@ -100,6 +133,105 @@ impl Room {
         self.manager.read().has_player(player)
     }
 
+    /// Makes the given player the owner of the room. Returns `false` if they are not in the room or already the owner.
+    /// Note that this does not update the owner data, use `RoomManager::transfer_ownership` for that.
+    pub fn set_owner(&self, player: i32) -> bool {
+        if player == 0 || self.get_owner() == player || !self.has_player(player) {
+            return false;
+        }
+
+        self.data.lock().co_hosts.retain(|&id| id != player);
+        self.owner.store(player, Ordering::Relaxed);
+
+        true
+    }
+
+    /// Appoints or removes a co-host. Returns `true` if the list of co-hosts has changed.
+    pub fn set_co_host(&self, player: i32, co_host: bool) -> bool {
+        if co_host && (player == 0 || self.get_owner() == player || !self.has_player(player)) {
+            return false;
+        }
+
+        let mut data = self.data.lock();
+        let position = data.co_hosts.iter().position(|&id| id == player);
+
+        match (co_host, position) {
+            (true, None) if data.co_hosts.len() < MAX_ROOM_CO_HOSTS => {
+                data.co_hosts.push(player);
+                true
+            }
+            (false, Some(idx)) => {
+                data.co_hosts.remove(idx);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_co_host(&self, player: i32) -> bool {
+        self.data.lock().co_hosts.contains(&player)
+    }
+
+    /// Whether the player is allowed to manage the room (kick players, change settings, invite), i.e. is the owner or a co-host
+    pub fn is_host(&self, player: i32) -> bool {
+        self.get_owner() == player || self.is_co_host(player)
+    }
+
+    /// Replaces the teams in the room, players that were in a team that no longer exists are left without a team.
+    pub fn set_teams(&self, teams: Vec<RoomTeam>) {
+        let mut data = self.data.lock();
+
+        let team_count = teams.len();
+        data.player_teams.retain(|_, team_id| usize::from(*team_id) < team_count);
+        data.teams = teams;
+    }
+
+    /// Assigns the player to a team, or removes them from their team if `team_id` is `None`.
+    /// Returns `true` if the team of the player has changed.
+    pub fn set_player_team(&self, player: i32, team_id: Option<u8>) -> bool {
+        if !self.has_player(player) {
+            return false;
+        }
+
+        let mut data = self.data.lock();
+
+        match team_id {
+            Some(team_id) if usize::from(team_id) < data.teams.len() => data.player_teams.insert(player, team_id) != Some(team_id),
+            Some(_) => false,
+            None => data.player_teams.remove(&player).is_some(),
+        }
+    }
+
+    pub fn get_player_team(&self, player: i32) -> Option<u8> {
+        self.data.lock().player_teams.get(&player).copied()
+    }
+
+    /// Returns the account IDs of all players in the given team
+    pub fn get_team_members(&self, team_id: u8) -> Vec<i32> {
+        self.data
+            .lock()
+            .player_teams
+            .iter()
+            .filter(|(_, team)| **team == team_id)
+            .map(|(player, _)| *player)
+            .collect()
+    }
+
+    /// Mutes or unmutes a player in the room chat and voice chat. Returns `true` if the state has changed.
+    pub fn set_player_muted(&self, player: i32, muted: bool) -> bool {
+        let mut data = self.data.lock();
+
+        if muted {
+            data.muted_players.insert(player)
+        } else {
+            data.muted_players.remove(&player)
+        }
+    }
+
+    pub fn is_player_muted(&self, player: i32) -> bool {
+        self.data.lock().muted_players.contains(&player)
+    }
+
     #[inline]
     pub fn set_settings(&self, settings: RoomSettings) {
         self.data.lock().settings = settings;
@@ -115,6 +247,8 @@ impl Room {
             password: self.password.clone(),
             owner: data.owner.clone().unwrap_or_default(),
             settings: data.settings,
+            co_hosts: data.co_hosts.iter().copied().collect(),
+            teams: data.teams.iter().cloned().collect(),
         }
     }
 
@@ -133,6 +267,33 @@ impl Room {
         }
     }
 
+    /// Checks whether the room matches the given filter. `name` and `owner_name` must already be lowercase.
+    pub fn matches_filter(&self, filter: &RoomListFilter, name: &str, owner_name: &str) -> bool {
+        if filter.hide_protected && self.is_protected() {
+            return false;
+        }
+
+        if filter.hide_full && self.is_full() {
+            return false;
+        }
+
+        if !name.is_empty() && !self.name.try_to_str().to_ascii_lowercase().contains(name) {
+            return false;
+        }
+
+        let data = self.data.lock();
+
+        if filter.level_id != 0 && data.settings.level_id != filter.level_id {
+            return false;
+        }
+
+        owner_name.is_empty()
+            || data
+                .owner
+                .as_ref()
+                .is_some_and(|owner| owner.name.try_to_str().to_ascii_lowercase().contains(owner_name))
+    }
+
     pub fn is_hidden(&self) -> bool {
         self.data.lock().settings.flags.is_hidden
     }
@@ -141,10 +302,22 @@ impl Room {
         self.data.lock().settings.flags.public_invites
     }
 
+    pub fn is_locked_teams(&self) -> bool {
+        self.data.lock().settings.flags.locked_teams
+    }
+
     pub fn is_two_player_mode(&self) -> bool {
         self.data.lock().settings.flags.two_player
     }
 
+    pub fn is_persistent(&self) -> bool {
+        self.persistent.load(Ordering::Relaxed)
+    }
+
+    pub fn set_persistent(&self, state: bool) {
+        self.persistent.store(state, Ordering::Relaxed);
+    }
+
     pub fn is_protected(&self) -> bool {
         !self.password.is_empty()
     }
@@ -181,6 +354,20 @@ impl Room {
     }
 }
 
+impl Default for Room {
+    fn default() -> Self {
+        Self::new(
+            0,
+            None,
+            InlineString::default(),
+            InlineString::default(),
+            RoomSettings::default(),
+            LevelManager::default(),
+            0,
+        )
+    }
+}
+
 impl RoomManager {
     pub fn new() -> Self {
         Self::default()
@@ -265,11 +452,13 @@ impl RoomManager {
         self.rooms.lock().contains_key(&room_id)
     }
 
-    /// Deletes a room if there are no players in it
+    /// Deletes a room if there are no players in it and it is not persistent
     pub fn maybe_remove_room(&self, room_id: u32) {
         let mut rooms = self.rooms.lock();
 
-        let to_remove = rooms.get(&room_id).is_some_and(|room| room.get_player_count() == 0);
+        let to_remove = rooms
+            .get(&room_id)
+            .is_some_and(|room| !room.is_persistent() && room.get_player_count() == 0);
 
         if to_remove {
             rooms.remove(&room_id);
@@ -298,6 +487,76 @@ impl RoomManager {
         was_owner
     }
 
+    /// Transfers the ownership of the room to the given player, returns `true` on success.
+    pub fn transfer_ownership(&self, room: &Room, account_id: i32) -> bool {
+        if room.id == 0 || !room.set_owner(account_id) {
+            return false;
+        }
+
+        room.data.lock().owner = self.get_game_server().get_player_preview_data(account_id);
+
+        true
+    }
+
+    /// Creates, updates or releases persistent rooms so that they match the configuration from the central server.
+    /// Settings are only overwritten if their configuration has changed since the last sync,
+    /// while a changed name or password only gets applied once the room is empty.
+    pub fn sync_persistent_rooms(&self, configured: &[PersistentRoom]) {
+        let mut applied = self.persistent_rooms.lock();
+
+        // rooms removed from the configuration become regular rooms, and get deleted once empty
+        let released = applied
+            .keys()
+            .copied()
+            .filter(|id| !configured.iter().any(|conf| conf.id == *id))
+            .collect::<Vec<_>>();
+
+        for room_id in released {
+            applied.remove(&room_id);
+
+            if let Some(room) = self.get_room(room_id) {
+                room.set_persistent(false);
+                self.maybe_remove_room(room_id);
+            }
+        }
+
+        for conf in configured {
+            if applied.get(&conf.id) == Some(conf) {
+                continue;
+            }
+
+            let name = InlineString::new(&conf.name);
+            let password = InlineString::new(&conf.password);
+
+            match self.get_room(conf.id) {
+                Some(room) if !room.is_persistent() => {
+                    warn!("cannot create persistent room {}, a regular room with the same id already exists", conf.id);
+                    continue;
+                }
+
+                Some(room) if room.name == name && room.password == password && room.orig_owner == conf.owner => {
+                    room.set_settings(RoomSettings::from(conf));
+                }
+
+                Some(room) if room.get_player_count() != 0 => {
+                    // the room can only be recreated once it is empty, until then just update the settings and try again on the next sync
+                    room.set_settings(RoomSettings::from(conf));
+                    continue;
+                }
+
+                _ => {
+                    let mut room = Room::new(0, None, name, password, RoomSettings::from(conf), LevelManager::new(), conf.id);
+                    room.orig_owner = conf.owner;
+                    room.set_persistent(true);
+
+                    self.rooms.lock().insert(conf.id, Arc::new(room));
+                }
+            }
+
+            applied.insert(conf.id, conf.clone());
+        }
+    }
+
     pub fn get_room_info(&self, room_id: u32) -> Option<RoomInfo> {
         self.try_with_any(room_id, |room| Some(room.get_room_info()), || None)
     }
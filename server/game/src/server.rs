@@ -2,7 +2,7 @@ use std::{
     collections::VecDeque,
     net::{SocketAddr, SocketAddrV4},
     sync::{Arc, atomic::Ordering},
-    time::Duration,
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 
 use globed_shared::{
@@ -43,9 +43,22 @@ const INLINE_BUFFER_SIZE: usize = 164;
 const MAX_UDP_PACKET_SIZE: usize = 65536;
 const LARGE_BUFFER_SIZE: usize = 2usize.pow(19); // 2^19, 0.5mb
 
+/// how many minutes before a scheduled maintenance players get warned about it
+const MAINTENANCE_WARNINGS: [u64; 4] = [30, 10, 5, 1];
+
+/// minimum time between configuration refreshes caused by tokens signed with an unknown key
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
 const MARKER_CONN_INITIAL: u8 = 0xe0;
 const MARKER_CONN_RECOVERY: u8 = 0xe1;
 
+#[derive(Default)]
+struct MaintenanceCountdown {
+    starts_at: u64,
+    last_warning: Option<u64>, // in minutes
+    started: bool,
+}
+
 enum EitherClientThread {
     Authorized(Arc<ClientThread>),
     Unauthorized(Arc<UnauthorizedThread>),
@@ -65,6 +78,7 @@ pub struct GameServer {
     pub bridge: CentralBridge,
     pub standalone: bool,
     pub large_packet_buffer: SyncMutex<Box<[u8]>>,
+    last_key_refresh: SyncMutex<Option<Instant>>,
 }
 
 impl GameServer {
@@ -84,6 +98,7 @@ impl GameServer {
             bridge,
             standalone,
             large_packet_buffer: SyncMutex::new(vec![0; LARGE_BUFFER_SIZE].into_boxed_slice()),
+            last_key_refresh: SyncMutex::new(None),
         }
     }
 
@@ -95,6 +110,7 @@ impl GameServer {
         );
 
         self.state.room_manager.set_game_server(self);
+        self.sync_persistent_rooms();
 
         // spawn central conf refresher (runs every 5 minutes)
         if !self.standalone {
@@ -111,6 +127,28 @@ impl GameServer {
                 }
             });
 
+            // warn players about scheduled maintenance and disconnect them once it starts
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(10));
+                let mut countdown = MaintenanceCountdown::default();
+
+                loop {
+                    interval.tick().await;
+                    self.check_scheduled_maintenance(&mut countdown).await;
+                }
+            });
+
+            // take away temporary roles once they expire
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(30));
+                interval.tick().await;
+
+                loop {
+                    interval.tick().await;
+                    self.expire_role_grants().await;
+                }
+            });
+
             // spawn the role info refresher as well (slightly less common)
             tokio::spawn(async move {
                 let mut interval = tokio::time::interval(Duration::from_mins(30));
@@ -124,6 +162,17 @@ impl GameServer {
             });
         }
 
+        // clean up expired room invites and let the inviters know about them
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(30));
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                self.expire_room_invites().await;
+            }
+        });
+
         // print some useful stats every once in a bit
         let interval = self.bridge.central_conf.lock().status_print_interval;
 
@@ -439,16 +488,71 @@ impl GameServer {
         }
     }
 
-    pub async fn broadcast_voice_packet(&self, vpkt: &Arc<VoiceBroadcastPacket>, level_id: LevelId, room_id: u32) {
-        self.broadcast_user_message(&ServerThreadMessage::BroadcastVoice(vpkt.clone()), vpkt.player_id, level_id, room_id)
-            .await;
+    /// broadcast a voice packet to everyone on the level, or only to the members of the given team if `team_id` is not `None`
+    pub async fn broadcast_voice_packet(&self, vpkt: &Arc<VoiceBroadcastPacket>, level_id: LevelId, room_id: u32, team_id: Option<u8>) {
+        self.broadcast_user_message(
+            &ServerThreadMessage::BroadcastVoice(vpkt.clone()),
+            vpkt.player_id,
+            level_id,
+            room_id,
+            team_id,
+        )
+        .await;
     }
 
-    pub async fn broadcast_chat_packet(&self, tpkt: &ChatMessageBroadcastPacket, level_id: LevelId, room_id: u32) {
-        self.broadcast_user_message(&ServerThreadMessage::BroadcastText(tpkt.clone()), tpkt.player_id, level_id, room_id)
+    /// broadcast a chat message to everyone on the level, or only to the members of the given team if `team_id` is not `None`
+    pub async fn broadcast_chat_packet(&self, tpkt: &ChatMessageBroadcastPacket, level_id: LevelId, room_id: u32, team_id: Option<u8>) {
+        self.broadcast_user_message(&ServerThreadMessage::BroadcastText(tpkt.clone()), tpkt.player_id, level_id, room_id, team_id)
             .await;
     }
 
+    /// broadcast a chat message to everyone in the room regardless of their level, or only to the members of the given team if `team_id` is not `None`
+    pub async fn broadcast_room_chat_packet(&self, tpkt: &ChatMessageBroadcastPacket, room_id: u32, team_id: Option<u8>) {
+        let msg = ServerThreadMessage::BroadcastText(tpkt.clone());
+
+        match team_id {
+            Some(team_id) => {
+                let members = self.state.room_manager.with_any(room_id, |room| room.get_team_members(team_id));
+
+                let threads: Vec<_> = self
+                    .clients
+                    .lock()
+                    .values()
+                    .filter(|thread| {
+                        let account_id = thread.account_id.load(Ordering::Relaxed);
+                        thread.room_id.load(Ordering::Relaxed) == room_id && account_id != tpkt.player_id && members.contains(&account_id)
+                    })
+                    .cloned()
+                    .collect();
+
+                for thread in threads {
+                    thread.push_new_message(msg.clone()).await;
+                }
+            }
+            None => self.broadcast_room_message(&msg, tpkt.player_id, room_id).await,
+        }
+    }
+
+    /// broadcast a chat message to everyone on the server
+    pub async fn broadcast_global_chat_packet(&self, tpkt: &ChatMessageBroadcastPacket) {
+        let threads: Vec<_> = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thread| {
+                let account_id = thread.account_id.load(Ordering::Relaxed);
+                account_id != 0 && account_id != tpkt.player_id
+            })
+            .cloned()
+            .collect();
+
+        let msg = ServerThreadMessage::BroadcastText(tpkt.clone());
+
+        for thread in threads {
+            thread.push_new_message(msg.clone()).await;
+        }
+    }
+
     /// iterate over every player in this list and run F
     #[inline]
     pub fn for_each_player<F, A>(&self, ids: &[i32], f: F, additional: &mut A) -> usize
@@ -478,7 +582,7 @@ impl GameServer {
     }
 
     #[inline]
-    pub fn for_every_player_preview_in_room<F, A>(&self, room_id: u32, f: F, additional: &mut A, force_visibility: bool) -> usize
+    pub fn for_every_player_preview_in_room<F, A>(&self, room_id: u32, viewer: i32, f: F, additional: &mut A, force_visibility: bool) -> usize
     where
         F: Fn(&PlayerPreviewAccountData, usize, &mut A) -> bool,
     {
@@ -490,7 +594,7 @@ impl GameServer {
                     return false;
                 }
 
-                force_visibility || !thr.privacy_settings.lock().get_hide_from_lists()
+                force_visibility || thr.is_visible_to(viewer)
             })
             .map(|thread| thread.account_data.lock().make_preview(!thread.privacy_settings.lock().get_hide_roles()))
             .fold(0, |count, preview| count + usize::from(f(&preview, count, additional)))
@@ -509,7 +613,7 @@ impl GameServer {
                     return false;
                 }
 
-                force_visibility || !thr.privacy_settings.lock().get_hide_from_lists() || thr.account_id.load(Ordering::Relaxed) == requested
+                force_visibility || thr.is_visible_to(requested)
             })
             .map(|thread| {
                 let mut level_id = thread.level_id.load(Ordering::Relaxed);
@@ -562,17 +666,22 @@ impl GameServer {
             force_visibility,
         );
 
+        for player in &mut vec {
+            player.team_id = room.get_player_team(player.account_id);
+        }
+
         vec
     }
 
     #[inline]
-    pub fn get_player_previews_in_room(&self, room_id: u32, force_visibility: bool) -> Vec<PlayerPreviewAccountData> {
+    pub fn get_player_previews_in_room(&self, room_id: u32, viewer: i32, force_visibility: bool) -> Vec<PlayerPreviewAccountData> {
         let player_count = self.state.room_manager.with_any(room_id, |room| room.get_player_count());
 
         let mut vec = Vec::with_capacity(player_count);
 
         self.for_every_player_preview_in_room(
             room_id,
+            viewer,
             |p, _, vec| {
                 vec.push(p.clone());
                 true
@@ -584,14 +693,77 @@ impl GameServer {
         vec
     }
 
+    /// get a single page of players in a room, filtered by a case-insensitive name prefix, along with the total amount of matching players
+    pub fn get_player_preview_page_in_room(
+        &self,
+        room_id: u32,
+        viewer: i32,
+        name_prefix: &str,
+        sort: PlayerListSortOrder,
+        page: usize,
+        force_visibility: bool,
+    ) -> (Vec<PlayerPreviewAccountData>, usize) {
+        let mut matching: Vec<(i32, PlayerPreviewAccountData)> = Vec::new();
+
+        self.for_every_player_preview_in_room(
+            room_id,
+            viewer,
+            |preview, _, matching| {
+                let name = preview.name.try_to_str();
+                if !name
+                    .get(..name_prefix.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name_prefix))
+                {
+                    return false;
+                }
+
+                // hidden roles are not included in the preview, so they can't be figured out by sorting
+                let priority = preview
+                    .special_user_data
+                    .roles
+                    .as_ref()
+                    .map_or(i32::MIN, |roles| self.state.role_manager.compute_priority_from_int_ids(roles));
+
+                matching.push((priority, preview.clone()));
+                true
+            },
+            &mut matching,
+            force_visibility,
+        );
+
+        let by_name = |a: &PlayerPreviewAccountData, b: &PlayerPreviewAccountData| {
+            a.name.try_to_str().to_ascii_lowercase().cmp(&b.name.try_to_str().to_ascii_lowercase())
+        };
+
+        match sort {
+            PlayerListSortOrder::Name => matching.sort_by(|a, b| by_name(&a.1, &b.1)),
+            PlayerListSortOrder::RolePriority => matching.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| by_name(&a.1, &b.1))),
+        }
+
+        let total = matching.len();
+
+        let players = matching
+            .into_iter()
+            .skip(page.saturating_mul(PLAYER_LIST_PAGE_SIZE))
+            .take(PLAYER_LIST_PAGE_SIZE)
+            .map(|(_, preview)| preview)
+            .collect();
+
+        (players, total)
+    }
+
+    /// iterate over every public room (or every room if `force_visibility` is `true`) and run F
     #[inline]
-    pub fn for_every_public_room<F, A>(&self, f: F, additional: &mut A) -> usize
+    pub fn for_every_public_room<F, A>(&self, f: F, additional: &mut A, force_visibility: bool) -> usize
     where
-        F: Fn(RoomListingInfo, usize, &mut A) -> bool,
+        F: Fn(&Arc<Room>, usize, &mut A) -> bool,
     {
-        self.state.room_manager.get_rooms().iter().fold(0, |count, (id, room)| {
-            count + usize::from(f(room.get_room_listing_info(*id), count, additional))
-        })
+        self.state
+            .room_manager
+            .get_rooms()
+            .values()
+            .filter(|room| force_visibility || !room.is_hidden())
+            .fold(0, |count, room| count + usize::from(f(room, count, additional)))
     }
 
     #[inline]
@@ -612,6 +784,80 @@ impl GameServer {
             })
     }
 
+    /// Builds friend list entries for the given players. Presence is only known for players connected to this game server,
+    /// the others are reported as not being on this server (they may be offline or on another game server),
+    /// and their names are fetched from the central server.
+    pub async fn get_friend_list_entries(&self, account_ids: &[i32]) -> bridge::Result<Vec<FriendListEntry>> {
+        let mut entries = Vec::with_capacity(account_ids.len());
+        let mut elsewhere = Vec::new();
+
+        for &account_id in account_ids {
+            let Some(thread) = self.get_user_by_id(account_id) else {
+                elsewhere.push(account_id);
+                continue;
+            };
+
+            let mut level_id = thread.level_id.load(Ordering::Relaxed);
+
+            // same rules as in room player lists, and nothing at all if they are hidden in game
+            if thread.on_unlisted_level.load(Ordering::SeqCst)
+                || is_editorcollab_level(level_id)
+                || thread.privacy_settings.lock().get_hide_in_game()
+            {
+                level_id = 0;
+            }
+
+            entries.push(FriendListEntry {
+                account_id,
+                name: thread.account_data.lock().name.clone(),
+                on_this_server: true,
+                level_id,
+            });
+        }
+
+        for (account_id, name) in self.get_player_names(&elsewhere).await? {
+            entries.push(FriendListEntry {
+                account_id,
+                name,
+                on_this_server: false,
+                level_id: 0,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the names of the given players, asking the central server about the ones that are not connected to this game server.
+    /// Names of unknown players are left empty.
+    pub async fn get_player_names(&self, account_ids: &[i32]) -> bridge::Result<Vec<(i32, InlineString<MAX_NAME_SIZE>)>> {
+        let mut names = Vec::with_capacity(account_ids.len());
+        let mut remote = Vec::new();
+
+        for &account_id in account_ids {
+            match self.get_user_by_id(account_id) {
+                Some(thread) => names.push((account_id, thread.account_data.lock().name.clone())),
+                None => remote.push(account_id),
+            }
+        }
+
+        let remote_names = if remote.is_empty() || self.standalone {
+            Vec::new()
+        } else {
+            self.bridge.get_many_names(&remote).await?
+        };
+
+        for account_id in remote {
+            let mut name = InlineString::default();
+            if let Some((_, n)) = remote_names.iter().find(|(id, _)| *id == account_id) {
+                name.extend_safe(n);
+            }
+
+            names.push((account_id, name));
+        }
+
+        Ok(names)
+    }
+
     #[inline]
     pub fn get_player_preview_data(&self, account_id: i32) -> Option<PlayerPreviewAccountData> {
         self.clients
@@ -708,9 +954,11 @@ impl GameServer {
 
     /* private handling stuff */
 
-    /// broadcast a message to all people on the level
-    async fn broadcast_user_message(&self, msg: &ServerThreadMessage, origin_id: i32, level_id: LevelId, room_id: u32) {
+    /// broadcast a message to all people on the level, optionally limited to the members of a team
+    async fn broadcast_user_message(&self, msg: &ServerThreadMessage, origin_id: i32, level_id: LevelId, room_id: u32, team_id: Option<u8>) {
         let threads = self.state.room_manager.with_any(room_id, |pm| {
+            let team_members = team_id.map(|team_id| pm.get_team_members(team_id));
+
             let manager = pm.manager.read();
             let players = manager.get_level(level_id);
 
@@ -720,7 +968,9 @@ impl GameServer {
                     .values()
                     .filter(|thread| {
                         let account_id = thread.account_id.load(Ordering::Relaxed);
-                        account_id != origin_id && level.players.contains(&account_id)
+                        account_id != origin_id
+                            && level.players.contains(&account_id)
+                            && team_members.as_ref().is_none_or(|members| members.contains(&account_id))
                     })
                     .cloned()
                     .collect()
@@ -764,6 +1014,27 @@ impl GameServer {
             .await;
     }
 
+    /// send `RoomTeamChangedPacket` to all players in a room
+    pub async fn broadcast_room_team_change(&self, room_id: u32, player_id: i32, team_id: Option<u8>) {
+        if room_id == 0 {
+            return;
+        }
+
+        let pkt = RoomTeamChangedPacket { player_id, team_id };
+
+        self.broadcast_room_message(&ServerThreadMessage::BroadcastTeamChange(pkt), 0, room_id)
+            .await;
+    }
+
+    /// notify the player about a changed friend status, if they are connected to this game server.
+    /// players on other game servers see the new status the next time they request their friend list
+    pub async fn broadcast_friend_status(&self, account_id: i32, player: i32, status: FriendStatus) {
+        if let Some(user) = self.get_user_by_id(account_id) {
+            user.push_new_message(ServerThreadMessage::BroadcastFriendStatus(FriendStatusChangedPacket { player, status }))
+                .await;
+        }
+    }
+
     /// kick users from the room and send a `RoomPlayerListPacket`
     pub async fn broadcast_room_kicked(&self, account_id: i32) {
         if let Some(user) = self.get_user_by_id(account_id) {
@@ -776,6 +1047,43 @@ impl GameServer {
         }
     }
 
+    /// remove all expired room invites and send a `RoomInviteStatusPacket` to the inviters
+    async fn expire_room_invites(&self) {
+        for invite in self.state.invite_manager.remove_expired() {
+            if let Some(inviter) = self.get_user_by_id(invite.inviter) {
+                inviter
+                    .push_new_message(ServerThreadMessage::BroadcastInviteStatus(RoomInviteStatusPacket {
+                        player_id: invite.target,
+                        status: RoomInviteStatus::Expired,
+                    }))
+                    .await;
+            }
+        }
+    }
+
+    /// Strips temporary roles that have expired from online players and sends them their new roles.
+    async fn expire_role_grants(&self) {
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        let threads = self
+            .clients
+            .lock()
+            .values()
+            .filter(|thread| thread.has_expired_role_grants(now))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.expire_role_grants(now);
+
+            let pkt = RolesUpdatedPacket {
+                special_user_data: thread.account_data.lock().special_user_data.clone(),
+            };
+
+            thread.push_new_message(ServerThreadMessage::BroadcastRoleChange(pkt)).await;
+        }
+    }
+
     /// Try to handle a packet that is not addressed to a specific thread, but to the game server.
     async fn try_udp_handle(&self, data: &[u8], peer: SocketAddrV4) -> anyhow::Result<bool> {
         let mut byte_reader = ByteReader::from_bytes(data);
@@ -853,6 +1161,9 @@ impl GameServer {
         // decrement player count
         self.state.dec_player_count();
 
+        // any pending invites from or to this player are no longer valid
+        self.state.invite_manager.remove_player(account_id);
+
         // remove from the player manager and the level if they are on one
         let was_owner = self.state.room_manager.remove_player(&room, account_id, level_id);
 
@@ -875,21 +1186,115 @@ impl GameServer {
         info!("-------------------------------------------");
     }
 
+    fn sync_persistent_rooms(&self) {
+        let rooms = self.bridge.central_conf.lock().persistent_rooms.clone();
+        self.state.room_manager.sync_persistent_rooms(&rooms);
+    }
+
     async fn refresh_bootdata(&self) -> bridge::Result<()> {
         self.bridge.refresh_boot_data().await?;
+        self.sync_persistent_rooms();
 
         // if we are now under maintenance, disconnect everyone who's still connected
         if self.bridge.is_maintenance() {
-            let threads: Vec<_> = self.clients.lock().values().cloned().collect();
-            for thread in threads {
-                thread
-                    .push_new_message(ServerThreadMessage::TerminationNotice(FastString::new(
-                        "The server is now under maintenance, please try connecting again later",
-                    )))
-                    .await;
-            }
+            self.disconnect_for_maintenance().await;
         }
 
         Ok(())
     }
+
+    /// Refreshes the configuration after a token signed with an unknown key was used, as the key might have been rotated recently.
+    /// Returns `false` if a refresh was already done recently or if it failed.
+    pub async fn refresh_signing_keys(&self) -> bool {
+        {
+            let mut last = self.last_key_refresh.lock();
+            if last.is_some_and(|x| x.elapsed() < KEY_REFRESH_INTERVAL) {
+                return false;
+            }
+
+            *last = Some(Instant::now());
+        }
+
+        match self.refresh_bootdata().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("failed to refresh configuration from the central server: {e}");
+                false
+            }
+        }
+    }
+
+    async fn disconnect_for_maintenance(&self) {
+        let threads: Vec<_> = self.clients.lock().values().cloned().collect();
+        for thread in threads {
+            thread
+                .push_new_message(ServerThreadMessage::TerminationNotice(FastString::new(
+                    "The server is now under maintenance, please try connecting again later",
+                )))
+                .await;
+        }
+    }
+
+    /// Sends countdown notices before a scheduled maintenance, and disconnects everyone once it starts
+    async fn check_scheduled_maintenance(&self, countdown: &mut MaintenanceCountdown) {
+        let Some(maintenance) = self.bridge.central_conf.lock().scheduled_maintenance else {
+            return;
+        };
+
+        // the maintenance was rescheduled, start over
+        if countdown.starts_at != maintenance.starts_at {
+            *countdown = MaintenanceCountdown {
+                starts_at: maintenance.starts_at,
+                ..Default::default()
+            };
+        }
+
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        if now >= maintenance.starts_at {
+            if maintenance.is_active(now) && !countdown.started {
+                countdown.started = true;
+                info!("Scheduled maintenance has started, disconnecting everyone");
+                self.disconnect_for_maintenance().await;
+            }
+
+            return;
+        }
+
+        let remaining = maintenance.starts_at - now;
+
+        // only send the closest warning, in case the maintenance was scheduled shortly before it starts
+        let Some(warning) = MAINTENANCE_WARNINGS.iter().copied().filter(|m| remaining <= m * 60).min() else {
+            return;
+        };
+
+        if countdown.last_warning.is_some_and(|x| x <= warning) {
+            return;
+        }
+
+        countdown.last_warning = Some(warning);
+
+        let minutes = remaining.div_ceil(60);
+        let mut message = format!(
+            "The server will go down for maintenance in {minutes} minute{}",
+            if minutes == 1 { "" } else { "s" }
+        );
+
+        if maintenance.duration != 0 {
+            let duration = maintenance.duration.div_ceil(60);
+            message.push_str(&format!(", it is expected to last about {duration} minute{}", if duration == 1 { "" } else { "s" }));
+        }
+
+        let threads: Vec<_> = self.clients.lock().values().filter(|thr| thr.authenticated()).cloned().collect();
+
+        info!("Warning {} players about the upcoming maintenance: {message}", threads.len());
+
+        let notice = ServerNoticePacket {
+            message: FastString::new(&message),
+        };
+
+        for thread in threads {
+            thread.push_new_message(ServerThreadMessage::BroadcastNotice(notice.clone())).await;
+        }
+    }
 }
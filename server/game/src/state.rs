@@ -1,5 +1,5 @@
 use crate::{
-    managers::{RoleManager, RoomManager},
+    managers::{InviteManager, RoleManager, RoomManager},
     util::WordFilter,
 };
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -9,6 +9,7 @@ pub struct ServerState {
     pub player_count: AtomicU32,
     pub room_manager: RoomManager,
     pub role_manager: RoleManager,
+    pub invite_manager: InviteManager,
     pub filter: WordFilter,
 }
 
@@ -13,7 +13,9 @@ pub struct GameServerBootData {
     pub protocol: u16,
     pub tps: u32,
     pub maintenance: bool,
+    pub scheduled_maintenance: Option<ScheduledMaintenance>,
     pub secret_key2: String,
+    pub retired_secret_keys2: Vec<RetiredKey>,
     pub token_expiry: u64,
     pub status_print_interval: u64,
     pub admin_key: FastString,
@@ -24,7 +26,12 @@ pub struct GameServerBootData {
     pub room_webhook_url: String,
     pub chat_burst_limit: u32,
     pub chat_burst_interval: u32,
+    pub global_chat: bool,
     pub roles: Vec<ServerRole>,
+    pub persistent_rooms: Vec<PersistentRoom>,
+    pub chat_commands: Vec<ChatCommand>,
+    pub motd: String,
+    pub announcements: Vec<Announcement>,
 }
 
 impl Default for GameServerBootData {
@@ -38,7 +45,9 @@ impl Default for GameServerBootData {
             protocol: MAX_SUPPORTED_PROTOCOL,
             tps: 30,
             maintenance: false,
+            scheduled_maintenance: None,
             secret_key2: String::new(),
+            retired_secret_keys2: Vec::new(),
             token_expiry: 0,
             status_print_interval,
             admin_key: generate_alphanum_string(ADMIN_KEY_LENGTH).into(),
@@ -49,11 +58,23 @@ impl Default for GameServerBootData {
             room_webhook_url: String::new(),
             chat_burst_limit: 0,
             chat_burst_interval: 0,
+            global_chat: false,
             roles: Vec::new(),
+            persistent_rooms: Vec::new(),
+            chat_commands: Vec::new(),
+            motd: String::new(),
+            announcements: Vec::new(),
         }
     }
 }
 
+/// A signing key that was replaced by a newer one, but is still accepted for verification until `expires_at`
+#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone)]
+pub struct RetiredKey {
+    pub key: String,
+    pub expires_at: u64,
+}
+
 pub fn generate_argon2_hash(password: &str) -> String {
     let salt = SaltString::generate(&mut OsRng);
 
@@ -75,6 +96,16 @@ pub struct ServerUserEntry {
     pub active_mute: Option<i64>,
     pub active_ban: Option<i64>,
     pub punishment_count: u16,
+    pub role_grants: Vec<RoleGrant>, // temporary roles, these are also included in `user_roles`
+    pub totp_enabled: bool,          // whether logging into the admin panel requires a code from an authenticator app
+}
+
+/// A role given to a user only until the given time
+#[derive(Encodable, Decodable, Serialize, Deserialize, DynamicSize, Clone, Default)]
+pub struct RoleGrant {
+    pub role_id: String,
+    pub expires_at: u64,
+    pub granted_by: i32,
 }
 
 // this is pure laziness i was just too lazy to get derive macros n shit into the central server
@@ -82,6 +113,7 @@ pub struct ServerUserEntry {
 pub struct UserLoginData {
     pub account_id: i32,
     pub name: String,
+    pub session_id: Option<String>,
 }
 
 // same here
@@ -90,6 +122,47 @@ pub struct UserLoginResponse {
     pub user_entry: ServerUserEntry,
     pub ban: Option<UserPunishment>,
     pub link_code: u32,
+    pub friends: Vec<i32>,
+    pub blocked: Vec<i32>,
+    pub notices: Vec<StoredNotice>, // notices that were sent while the user was offline
+    pub session_revoked: bool,      // if true, the token used to log in was revoked and the login must be rejected
+}
+
+/// A session token issued to a user by the central server
+#[derive(Clone, Encodable, Decodable, DynamicSize)]
+pub struct UserSession {
+    pub session_id: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub last_used_at: u64, // last login to a game server, 0 if the token was never used
+}
+
+/// A code that whitelists the users redeeming it, and optionally gives them a role
+#[derive(Clone, Encodable, Decodable, DynamicSize)]
+pub struct InviteCode {
+    pub code: String,
+    pub created_by: i32,
+    pub created_at: u64,
+    pub expires_at: u64, // 0 if the code never expires
+    pub max_uses: u32,
+    pub uses: u32,
+    pub role_id: Option<String>,
+}
+
+impl InviteCode {
+    pub fn is_usable(&self, now: u64) -> bool {
+        self.uses < self.max_uses && (self.expires_at == 0 || self.expires_at > now)
+    }
+}
+
+/// A request to be whitelisted, submitted by a user that was rejected by a whitelisted server
+#[derive(Clone, Encodable, Decodable, DynamicSize, Serialize)]
+pub struct WhitelistApplication {
+    pub application_id: i64,
+    pub account_id: i32,
+    pub user_name: String,
+    pub message: String,
+    pub submitted_at: u64,
 }
 
 impl ServerUserEntry {
@@ -202,10 +275,413 @@ pub struct ServerRole {
     pub edit_featured_levels: bool,
     #[serde(default)]
     pub admin: bool,
+
+    // punishment limits
+    #[serde(default)]
+    pub max_ban_duration: u64, // in seconds, 0 means no limit
+    #[serde(default)]
+    pub max_mute_duration: u64, // in seconds, 0 means no limit
+    #[serde(default = "default_true")]
+    pub permanent_punishments: bool,
+    #[serde(default)]
+    pub edit_higher_punishments: bool,
+
+    /// ids of the roles whose permissions this role inherits
+    #[serde(default)]
+    pub inherits: Vec<String>,
+    /// names of the permissions this role has, after `resolve_roles` this also includes the ones set with the fields above and inherited ones
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// Names of the built-in permissions. Roles can be given any other permission name as well,
+/// it simply does nothing until something checks for it.
+pub mod permissions {
+    pub const NOTICES: &str = "notices";
+    pub const NOTICES_TO_EVERYONE: &str = "notices_to_everyone";
+    pub const KICK: &str = "kick";
+    pub const KICK_EVERYONE: &str = "kick_everyone";
+    pub const MUTE: &str = "mute";
+    pub const BAN: &str = "ban";
+    pub const EDIT_ROLE: &str = "edit_role";
+    pub const EDIT_FEATURED_LEVELS: &str = "edit_featured_levels";
+    pub const MANAGE_SESSIONS: &str = "manage_sessions";
+    /// implicitly grants every other permission
+    pub const ADMIN: &str = "admin";
+}
+
+impl ServerRole {
+    pub fn has_permission(&self, perm: &str) -> bool {
+        self.permissions.iter().any(|x| x == permissions::ADMIN || x == perm)
+    }
+
+    // permissions that have their own field, kept for compatibility with older configs and clients
+    fn legacy_permissions(&mut self) -> [(&'static str, &mut bool); 9] {
+        [
+            (permissions::NOTICES, &mut self.notices),
+            (permissions::NOTICES_TO_EVERYONE, &mut self.notices_to_everyone),
+            (permissions::KICK, &mut self.kick),
+            (permissions::KICK_EVERYONE, &mut self.kick_everyone),
+            (permissions::MUTE, &mut self.mute),
+            (permissions::BAN, &mut self.ban),
+            (permissions::EDIT_ROLE, &mut self.edit_role),
+            (permissions::EDIT_FEATURED_LEVELS, &mut self.edit_featured_levels),
+            (permissions::ADMIN, &mut self.admin),
+        ]
+    }
+
+    fn collect_permissions(roles: &[Self], idx: usize, path: &mut Vec<usize>, out: &mut Vec<String>) -> Result<(), String> {
+        if path.contains(&idx) {
+            return Err(format!("role '{}' inherits from itself", roles[idx].id));
+        }
+
+        path.push(idx);
+
+        for perm in &roles[idx].permissions {
+            if !out.contains(perm) {
+                out.push(perm.clone());
+            }
+        }
+
+        for parent in &roles[idx].inherits {
+            let Some(parent_idx) = roles.iter().position(|x| x.id == *parent) else {
+                return Err(format!("role '{}' inherits from a role that does not exist ('{parent}')", roles[idx].id));
+            };
+
+            Self::collect_permissions(roles, parent_idx, path, out)?;
+        }
+
+        path.pop();
+
+        Ok(())
+    }
+}
+
+/// Resolves role inheritance, so that the `permissions` of every role contain all the permissions it has,
+/// and the legacy permission fields are set accordingly. Fails if a role inherits from an unknown role or from itself.
+pub fn resolve_roles(roles: &mut [ServerRole]) -> Result<(), String> {
+    // move the legacy fields into the permission list first, so they get inherited as well
+    for role in roles.iter_mut() {
+        let mut perms = std::mem::take(&mut role.permissions);
+
+        for (name, value) in role.legacy_permissions() {
+            if *value && !perms.iter().any(|x| x == name) {
+                perms.push(name.to_owned());
+            }
+        }
+
+        role.permissions = perms;
+    }
+
+    let mut resolved = Vec::with_capacity(roles.len());
+
+    for idx in 0..roles.len() {
+        let mut perms = Vec::new();
+        ServerRole::collect_permissions(roles, idx, &mut Vec::new(), &mut perms)?;
+        resolved.push(perms);
+    }
+
+    for (role, perms) in roles.iter_mut().zip(resolved) {
+        role.permissions = perms;
+
+        let perms = role.permissions.clone();
+        for (name, value) in role.legacy_permissions() {
+            *value = perms.iter().any(|x| x == name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Limits on the punishments a user can issue, merged from all of their roles that grant the ban or mute permission.
+#[derive(Clone, Copy)]
+pub struct PunishmentLimits {
+    pub max_duration: u64,
+    pub permanent: bool,
+    pub edit_higher: bool,
+}
+
+impl PunishmentLimits {
+    pub const UNLIMITED: Self = Self {
+        max_duration: 0,
+        permanent: true,
+        edit_higher: true,
+    };
+
+    /// Computes the most permissive limits out of the given roles.
+    /// Returns `None` if none of the roles allow issuing this kind of punishment.
+    pub fn from_roles<'a>(roles: impl IntoIterator<Item = &'a ServerRole>, is_ban: bool) -> Option<Self> {
+        let mut limits: Option<Self> = None;
+
+        for role in roles {
+            if role.has_permission(permissions::ADMIN) {
+                return Some(Self::UNLIMITED);
+            }
+
+            let (allowed, max_duration) = if is_ban {
+                (role.has_permission(permissions::BAN), role.max_ban_duration)
+            } else {
+                (role.has_permission(permissions::MUTE), role.max_mute_duration)
+            };
+
+            if !allowed {
+                continue;
+            }
+
+            match &mut limits {
+                None => {
+                    limits = Some(Self {
+                        max_duration,
+                        permanent: role.permanent_punishments,
+                        edit_higher: role.edit_higher_punishments,
+                    });
+                }
+                Some(limits) => {
+                    // 0 means unlimited, so it always wins
+                    if limits.max_duration != 0 && (max_duration == 0 || max_duration > limits.max_duration) {
+                        limits.max_duration = max_duration;
+                    }
+
+                    limits.permanent |= role.permanent_punishments;
+                    limits.edit_higher |= role.edit_higher_punishments;
+                }
+            }
+        }
+
+        limits
+    }
+
+    /// Checks whether a punishment expiring at `expires_at` (0 for permanent) may be issued, returns an error message if not
+    pub fn check_expiry(&self, expires_at: u64, now: u64) -> Result<(), String> {
+        if expires_at == 0 {
+            if !self.permanent {
+                return Err("your role does not allow issuing permanent punishments".to_owned());
+            }
+        } else if self.max_duration != 0 && expires_at.saturating_sub(now) > self.max_duration {
+            let hours = self.max_duration / 3600;
+            let minutes = (self.max_duration % 3600) / 60;
+
+            return Err(format!("your role does not allow punishments longer than {hours}h {minutes}m"));
+        }
+
+        Ok(())
+    }
+}
+
+/* Friends */
+
+#[derive(Encodable, Decodable, DynamicSize, Clone, Default)]
+pub struct FriendList {
+    pub friends: Vec<i32>,
+    pub incoming: Vec<i32>, // pending requests sent to the user
+    pub outgoing: Vec<i32>, // pending requests sent by the user
+}
+
+/// The relationship between two players, from the perspective of the first one
+#[derive(Clone, Copy, Encodable, Decodable, DynamicSize, StaticSize, Debug, PartialEq, Eq)]
+#[repr(u8)]
+#[dynamic_size(as_static)]
+pub enum FriendStatus {
+    None = 0,
+    Friends = 1,
+    RequestSent = 2,
+    RequestReceived = 3,
+}
+
+impl FriendStatus {
+    /// Returns the same relationship, but from the perspective of the other player
+    pub const fn reversed(self) -> Self {
+        match self {
+            Self::RequestSent => Self::RequestReceived,
+            Self::RequestReceived => Self::RequestSent,
+            x => x,
+        }
+    }
+}
+
+#[derive(Encodable, Decodable, DynamicSize, Clone)]
+pub struct TotpVerifyData {
+    pub account_id: i32,
+    pub code: String,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, StaticSize, Clone)]
+#[dynamic_size(as_static)]
+pub struct FriendActionData {
+    pub account_id: i32,
+    pub target_id: i32,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, StaticSize, Clone)]
+#[dynamic_size(as_static)]
+pub struct FriendRequestResponseData {
+    pub account_id: i32,
+    pub target_id: i32,
+    pub accept: bool,
+}
+
+/* Block lists */
+
+#[derive(Encodable, Decodable, DynamicSize, StaticSize, Clone)]
+#[dynamic_size(as_static)]
+pub struct BlockActionData {
+    pub account_id: i32,
+    pub target_id: i32,
+    pub blocked: bool,
+}
+
+/* Notices */
+
+/// A notice stored on the central server. Notices to offline players are delivered on their next login,
+/// and notices that require an acknowledgement are stored regardless so that admins can see their status.
+#[derive(Clone, Encodable, Decodable, DynamicSize, Serialize, Deserialize)]
+pub struct StoredNotice {
+    pub id: i64,
+    pub account_id: i32,
+    pub issued_by: i32,
+    pub message: String,
+    pub require_ack: bool,
+    pub sent_at: i64,
+    pub delivered_at: Option<i64>,
+    pub acked_at: Option<i64>,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, Clone)]
+pub struct NoticeDeliveryData {
+    pub account_id: i32,
+    pub ids: Vec<i64>,
+}
+
+#[derive(Encodable, Decodable, DynamicSize, StaticSize, Clone)]
+#[dynamic_size(as_static)]
+pub struct NoticeAckData {
+    pub account_id: i32,
+    pub id: i64,
+}
+
+/// A room that is configured on the central server. It is created when the game server boots,
+/// keeps a fixed id and is not deleted when the last player leaves.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct PersistentRoom {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub password: String,
+    /// account id of the player that becomes the room owner whenever they join,
+    /// 0 to let the first player that joins host the room, like in a regular room
+    #[serde(default)]
+    pub owner: i32,
+
+    // settings
+    #[serde(default)]
+    pub is_hidden: bool,
+    #[serde(default)]
+    pub public_invites: bool,
+    #[serde(default)]
+    pub collision: bool,
+    #[serde(default)]
+    pub two_player: bool,
+    #[serde(default)]
+    pub deathlink: bool,
+    #[serde(default)]
+    pub locked_teams: bool,
+    #[serde(default)]
+    pub player_limit: u16,
+    #[serde(default)]
+    pub level_id: i64,
+    #[serde(default)]
+    pub faster_reset: bool,
+}
+
+/// A maintenance window configured on the central server. Game servers warn players before it starts,
+/// and nobody can connect while it is active.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, StaticSize, Clone, Copy, Default)]
+#[dynamic_size(as_static)]
+pub struct ScheduledMaintenance {
+    /// unix timestamp (seconds) of when the maintenance starts
+    pub starts_at: u64,
+    /// expected duration in seconds, the maintenance ends automatically after this time. 0 to last until removed from the config
+    #[serde(default)]
+    pub duration: u64,
+}
+
+impl ScheduledMaintenance {
+    pub fn ends_at(&self) -> Option<u64> {
+        (self.duration != 0).then(|| self.starts_at.saturating_add(self.duration))
+    }
+
+    pub fn is_active(&self, now: u64) -> bool {
+        now >= self.starts_at && self.ends_at().is_none_or(|end| now < end)
+    }
+
+    /// Returns a human readable description of when the maintenance is expected to be over, if known
+    pub fn describe_end(&self, now: u64) -> Option<String> {
+        let remaining = self.ends_at()?.saturating_sub(now);
+        let minutes = remaining.div_ceil(60).max(1);
+
+        Some(format!(
+            "expected to be over in about {minutes} minute{}",
+            if minutes == 1 { "" } else { "s" }
+        ))
+    }
+}
+
+/// A chat command configured on the central server, which replies with a fixed text message to whoever used it.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Default)]
+pub struct ChatCommand {
+    /// name of the command without the leading slash
+    pub name: String,
+    pub response: String,
+}
+
+/// An announcement configured on the central server, sent as a notice to players when they log in.
+#[derive(Serialize, Deserialize, Encodable, Decodable, DynamicSize, Clone, Default)]
+pub struct Announcement {
+    pub message: String,
+    /// unix timestamp (seconds) of when the announcement starts being shown, 0 to show it right away
+    #[serde(default)]
+    pub starts_at: u64,
+    /// unix timestamp (seconds) of when the announcement stops being shown, 0 to never stop
+    #[serde(default)]
+    pub ends_at: u64,
+    /// only show to players that have any of these roles, empty for everyone
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// only show to players whose platform starts with any of these strings (case-insensitive), empty for everyone
+    #[serde(default)]
+    pub platforms: Vec<String>,
+}
+
+impl Announcement {
+    pub fn should_show(&self, now: u64, user_roles: &[String], platform: &str) -> bool {
+        let started = self.starts_at == 0 || self.starts_at <= now;
+        let ended = self.ends_at != 0 && self.ends_at <= now;
+
+        let role_matches = self.roles.is_empty() || self.roles.iter().any(|role| user_roles.contains(role));
+
+        let platform = platform.to_ascii_lowercase();
+        let platform_matches = self.platforms.is_empty() || self.platforms.iter().any(|p| platform.starts_with(&p.to_ascii_lowercase()));
+
+        started && !ended && role_matches && platform_matches
+    }
 }
 
 /* Admin actions */
 
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminStoreNoticeAction {
+    pub account_id: i32,
+    pub issued_by: i32,
+    pub message: String,
+    pub require_ack: bool,
+    pub delivered: bool, // whether the user is online and the notice is being delivered right away
+}
+
 #[derive(Decodable, Encodable, DynamicSize)]
 pub struct AdminUpdateUsernameAction {
     pub account_id: i32,
@@ -226,6 +702,14 @@ pub struct AdminSetUserRolesAction {
     pub roles: Vec<String>,
 }
 
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminGrantRoleAction {
+    pub issued_by: i32,
+    pub account_id: i32,
+    pub role_id: String,
+    pub expires_at: u64,
+}
+
 #[derive(Decodable, Encodable, DynamicSize)]
 pub struct AdminPunishUserAction {
     pub issued_by: i32,
@@ -255,6 +739,35 @@ pub struct AdminSetAdminPasswordAction {
     pub new_password: FastString,
 }
 
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminCreateInviteCodeAction {
+    pub issued_by: i32,
+    pub max_uses: u32,
+    pub expires_at: u64, // 0 to never expire
+    pub role_id: Option<String>,
+}
+
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminDeleteInviteCodeAction {
+    pub issued_by: i32,
+    pub code: String,
+}
+
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminReviewApplicationAction {
+    pub issued_by: i32,
+    pub account_id: i32,
+    pub approve: bool,
+    pub reason: String,
+}
+
+#[derive(Decodable, Encodable, DynamicSize)]
+pub struct AdminRevokeSessionsAction {
+    pub issued_by: i32,
+    pub account_id: i32,
+    pub session_id: String, // empty to revoke all sessions of the user
+}
+
 #[derive(Decodable, Encodable, DynamicSize)]
 pub struct AdminEditPunishmentAction {
     pub issued_by: i32,
@@ -263,3 +776,104 @@ pub struct AdminEditPunishmentAction {
     pub reason: FastString,
     pub expires_at: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduled_maintenance_window() {
+        let maintenance = ScheduledMaintenance {
+            starts_at: 1000,
+            duration: 600,
+        };
+
+        assert_eq!(maintenance.ends_at(), Some(1600));
+        assert!(!maintenance.is_active(999));
+        assert!(maintenance.is_active(1000));
+        assert!(maintenance.is_active(1599));
+        assert!(!maintenance.is_active(1600));
+    }
+
+    #[test]
+    fn scheduled_maintenance_without_duration() {
+        let maintenance = ScheduledMaintenance {
+            starts_at: 1000,
+            duration: 0,
+        };
+
+        assert_eq!(maintenance.ends_at(), None);
+        assert!(maintenance.is_active(u64::MAX));
+        assert_eq!(maintenance.describe_end(1000), None);
+    }
+
+    #[test]
+    fn scheduled_maintenance_describe_end() {
+        let maintenance = ScheduledMaintenance {
+            starts_at: 1000,
+            duration: 600,
+        };
+
+        assert_eq!(maintenance.describe_end(1000).unwrap(), "expected to be over in about 10 minutes");
+        // partial minutes are rounded up, and it never says 0 minutes
+        assert_eq!(maintenance.describe_end(1539).unwrap(), "expected to be over in about 2 minutes");
+        assert_eq!(maintenance.describe_end(1590).unwrap(), "expected to be over in about 1 minute");
+        assert_eq!(maintenance.describe_end(2000).unwrap(), "expected to be over in about 1 minute");
+    }
+
+    fn mod_role(ban: bool, max_ban_duration: u64, permanent_punishments: bool) -> ServerRole {
+        ServerRole {
+            ban,
+            max_ban_duration,
+            permanent_punishments,
+            ..Default::default()
+        }
+    }
+
+    fn limits_for(mut roles: Vec<ServerRole>, is_ban: bool) -> Option<PunishmentLimits> {
+        // the limits are only computed for resolved roles
+        resolve_roles(&mut roles).unwrap();
+        PunishmentLimits::from_roles(&roles, is_ban)
+    }
+
+    #[test]
+    fn punishment_limits_merge_roles() {
+        assert!(limits_for(vec![mod_role(false, 0, true)], true).is_none());
+
+        // only roles that allow the punishment count
+        let limits = limits_for(vec![mod_role(true, 3600, false), mod_role(false, 0, true)], true).unwrap();
+        assert_eq!(limits.max_duration, 3600);
+        assert!(!limits.permanent);
+
+        // the most permissive limits win, and 0 means unlimited
+        let limits = limits_for(vec![mod_role(true, 3600, false), mod_role(true, 7200, false)], true).unwrap();
+        assert_eq!(limits.max_duration, 7200);
+
+        let limits = limits_for(vec![mod_role(true, 3600, false), mod_role(true, 0, true)], true).unwrap();
+        assert_eq!(limits.max_duration, 0);
+        assert!(limits.permanent);
+
+        // mutes use their own permission and duration
+        assert!(limits_for(vec![mod_role(true, 3600, false)], false).is_none());
+
+        let admin = ServerRole {
+            admin: true,
+            ..Default::default()
+        };
+        let limits = limits_for(vec![mod_role(true, 3600, false), admin], false).unwrap();
+        assert_eq!(limits.max_duration, 0);
+        assert!(limits.permanent && limits.edit_higher);
+    }
+
+    #[test]
+    fn punishment_limits_check_expiry() {
+        let limits = limits_for(vec![mod_role(true, 3600, false)], true).unwrap();
+
+        assert!(limits.check_expiry(1000 + 3600, 1000).is_ok());
+        assert!(limits.check_expiry(1000 + 3601, 1000).is_err());
+        assert!(limits.check_expiry(0, 1000).is_err());
+
+        assert!(PunishmentLimits::UNLIMITED.check_expiry(u64::MAX, 1000).is_ok());
+        assert!(PunishmentLimits::UNLIMITED.check_expiry(0, 1000).is_ok());
+    }
+}
@@ -5,10 +5,14 @@ use std::{
 
 use base64::{Engine, engine::general_purpose as b64e};
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
+
+use crate::RetiredKey;
 
 pub struct TokenIssuer {
     hmac: Hmac<Sha256>,
+    key_id: String,
+    retired: Vec<(String, Hmac<Sha256>, u64)>, // key id, hmac, expires at
     expiration_period: Duration,
 }
 
@@ -18,10 +22,18 @@ pub enum TokenValidationFailure {
     Impersonation,      // account IDs don't match
     Expired,            // token expired
     InvalidSignature,   // signature does not match
+    UnknownKey,         // signed with a key we don't know about (or one that is no longer accepted)
 }
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Data stored inside of a valid token
+pub struct TokenClaims {
+    pub account_name: String,
+    /// `None` if the token was issued before sessions were tracked
+    pub session_id: Option<String>,
+}
+
 impl TokenValidationFailure {
     pub const fn error_message(&self) -> &'static str {
         match self {
@@ -30,6 +42,7 @@ impl TokenValidationFailure {
             Self::Impersonation => "account ID does not match this token",
             Self::Expired => "token expired",
             Self::InvalidSignature => "signature mismatch",
+            Self::UnknownKey => "token was signed with an unknown key",
         }
     }
 }
@@ -40,12 +53,24 @@ impl Display for TokenValidationFailure {
     }
 }
 
+/// Returns a short identifier of the key, which is included in tokens so that the right key can be picked when validating them
+pub fn signing_key_id(secret_key: &str) -> String {
+    let digest = Sha256::digest(secret_key.as_bytes());
+
+    digest[..4].iter().map(|b| format!("{b:02x}")).collect()
+}
+
 impl TokenIssuer {
     pub fn new(secret_key: &str, expiration_period: Duration) -> Self {
         let skey_bytes = secret_key.as_bytes();
         let hmac = HmacSha256::new_from_slice(skey_bytes).unwrap();
 
-        Self { hmac, expiration_period }
+        Self {
+            hmac,
+            key_id: signing_key_id(secret_key),
+            retired: Vec::new(),
+            expiration_period,
+        }
     }
 
     /// Change the secret key of this token issuer.
@@ -54,6 +79,21 @@ impl TokenIssuer {
         let hmac = HmacSha256::new_from_slice(skey_bytes).unwrap();
 
         self.hmac = hmac;
+        self.key_id = signing_key_id(secret_key);
+    }
+
+    /// Set the keys that were used before the current one. Tokens signed with them stay valid until the key expires.
+    pub fn set_retired_keys(&mut self, keys: &[RetiredKey]) {
+        self.retired = keys
+            .iter()
+            .map(|x| {
+                (
+                    signing_key_id(&x.key),
+                    HmacSha256::new_from_slice(x.key.as_bytes()).unwrap(),
+                    x.expires_at,
+                )
+            })
+            .collect();
     }
 
     pub fn set_expiration_period(&mut self, period: Duration) {
@@ -61,33 +101,38 @@ impl TokenIssuer {
     }
 
     /// Generates a new session token for the given account ID and username
-    pub fn generate(&self, account_id: i32, user_id: i32, account_name: &str) -> String {
+    pub fn generate(&self, account_id: i32, user_id: i32, account_name: &str, session_id: &str) -> String {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("whoops our clock went backwards")
             .as_secs();
 
-        let data = format!("{account_id}.{user_id}.{account_name}.{timestamp}");
+        let data = format!("{account_id}.{user_id}.{account_name}.{timestamp}.{session_id}");
         let mut hmac = self.hmac.clone();
         hmac.update(data.as_bytes());
         let res = hmac.finalize();
 
         format!(
-            "{}.{}",
+            "{}.{}.{}",
             b64e::URL_SAFE_NO_PAD.encode(data),
-            b64e::URL_SAFE_NO_PAD.encode(res.into_bytes())
+            b64e::URL_SAFE_NO_PAD.encode(res.into_bytes()),
+            self.key_id
         )
     }
 
-    /// Validates a token, returns the name of the user if successful
-    pub fn validate(&self, account_id: i32, user_id: i32, token: &str) -> Result<String, TokenValidationFailure> {
+    /// Validates a token, returns the name of the user and the session ID if successful.
+    /// Note that this does not check whether the session was revoked, that can only be done by the central server.
+    pub fn validate(&self, account_id: i32, user_id: i32, token: &str) -> Result<TokenClaims, TokenValidationFailure> {
         if token.is_empty() {
             return Err(TokenValidationFailure::Missing);
         }
 
         let timestamp = SystemTime::now();
 
-        let (claims, signature) = token.split_once('.').ok_or(TokenValidationFailure::MalformedStructure)?;
+        let mut parts = token.split('.');
+        let claims = parts.next().ok_or(TokenValidationFailure::MalformedStructure)?;
+        let signature = parts.next().ok_or(TokenValidationFailure::MalformedStructure)?;
+        let key_id = parts.next(); // not present in tokens issued before key rotation was added
 
         let data_str = String::from_utf8(
             b64e::URL_SAFE_NO_PAD
@@ -118,6 +163,8 @@ impl TokenIssuer {
             .parse::<u64>()
             .map_err(|_| TokenValidationFailure::MalformedStructure)?;
 
+        let session_id = claims.next().map(ToOwned::to_owned);
+
         if orig_id != account_id {
             return Err(TokenValidationFailure::Impersonation);
         }
@@ -135,15 +182,112 @@ impl TokenIssuer {
         }
 
         // verify the signature
-        let mut hmac = self.hmac.clone();
-        hmac.update(data_str.as_bytes());
-
         let signature = b64e::URL_SAFE_NO_PAD
             .decode(signature)
             .map_err(|_| TokenValidationFailure::MalformedStructure)?;
 
-        hmac.verify_slice(&signature).map_err(|_| TokenValidationFailure::InvalidSignature)?;
+        let now = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let retired = self.retired.iter().filter(|(_, _, expires_at)| *expires_at > now);
+
+        let candidates: Vec<&Hmac<Sha256>> = match key_id {
+            Some(id) if id == self.key_id => vec![&self.hmac],
+            Some(id) => retired.filter(|(kid, _, _)| kid == id).map(|(_, hmac, _)| hmac).collect(),
+            None => std::iter::once(&self.hmac).chain(retired.map(|(_, hmac, _)| hmac)).collect(),
+        };
+
+        if candidates.is_empty() {
+            return Err(TokenValidationFailure::UnknownKey);
+        }
+
+        let valid = candidates.into_iter().any(|hmac| {
+            let mut hmac = hmac.clone();
+            hmac.update(data_str.as_bytes());
+            hmac.verify_slice(&signature).is_ok()
+        });
+
+        if !valid {
+            return Err(TokenValidationFailure::InvalidSignature);
+        }
+
+        Ok(TokenClaims {
+            account_name: orig_name.to_owned(),
+            session_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_KEY: &str = "old secret key";
+    const NEW_KEY: &str = "new secret key";
+
+    fn issuer(secret_key: &str) -> TokenIssuer {
+        TokenIssuer::new(secret_key, Duration::from_secs(3600))
+    }
+
+    fn retired(key: &str, expires_at: u64) -> RetiredKey {
+        RetiredKey {
+            key: key.to_owned(),
+            expires_at,
+        }
+    }
+
+    fn now() -> u64 {
+        UNIX_EPOCH.elapsed().unwrap().as_secs()
+    }
+
+    #[test]
+    fn round_trip() {
+        let issuer = issuer(NEW_KEY);
+        let token = issuer.generate(1, 2, "Tester", "session");
+
+        let claims = issuer.validate(1, 2, &token).ok().unwrap();
+        assert_eq!(claims.account_name, "Tester");
+        assert_eq!(claims.session_id.as_deref(), Some("session"));
+
+        assert!(matches!(issuer.validate(3, 2, &token), Err(TokenValidationFailure::Impersonation)));
+        assert!(matches!(issuer.validate(1, 2, ""), Err(TokenValidationFailure::Missing)));
+    }
+
+    #[test]
+    fn rotated_key() {
+        let token = issuer(OLD_KEY).generate(1, 2, "Tester", "session");
+
+        let mut issuer = issuer(NEW_KEY);
+        assert!(matches!(issuer.validate(1, 2, &token), Err(TokenValidationFailure::UnknownKey)));
+
+        // tokens signed with a retired key are accepted until it expires
+        issuer.set_retired_keys(&[retired(OLD_KEY, now() + 60)]);
+        assert!(issuer.validate(1, 2, &token).is_ok());
+
+        issuer.set_retired_keys(&[retired(OLD_KEY, now() - 1)]);
+        assert!(matches!(issuer.validate(1, 2, &token), Err(TokenValidationFailure::UnknownKey)));
+    }
+
+    #[test]
+    fn token_without_key_id() {
+        // tokens issued before key rotation was added have no key id, so every accepted key is tried
+        let token = issuer(OLD_KEY).generate(1, 2, "Tester", "session");
+        let token = token.rsplit_once('.').unwrap().0;
+
+        let mut issuer = issuer(NEW_KEY);
+        assert!(matches!(issuer.validate(1, 2, token), Err(TokenValidationFailure::InvalidSignature)));
+
+        issuer.set_retired_keys(&[retired(OLD_KEY, now() + 60)]);
+        assert!(issuer.validate(1, 2, token).is_ok());
+    }
+
+    #[test]
+    fn forged_key_id() {
+        // claiming to be signed with the current key does not help if the signature is from another key
+        let token = issuer(OLD_KEY).generate(1, 2, "Tester", "session");
+        let forged = format!("{}.{}", token.rsplit_once('.').unwrap().0, signing_key_id(NEW_KEY));
 
-        Ok(orig_name.to_string())
+        assert!(matches!(
+            issuer(NEW_KEY).validate(1, 2, &forged),
+            Err(TokenValidationFailure::InvalidSignature)
+        ));
     }
 }
@@ -0,0 +1,172 @@
+//! Time-based one-time passwords (RFC 6238), compatible with the usual authenticator apps.
+
+use hmac::{Hmac, Mac};
+use rand::prelude::*;
+use sha1::Sha1;
+
+type HmacSha1 = Hmac<Sha1>;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// length of a single time step in seconds
+const STEP: u64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_SIZE: usize = 20;
+
+/// Generates a new random secret, encoded as base32
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_SIZE];
+    rand::rng().fill_bytes(&mut secret);
+
+    base32_encode(&secret)
+}
+
+/// Returns an `otpauth://` uri for the secret, which can be turned into a QR code and scanned by an authenticator app
+pub fn provisioning_uri(secret: &str, account_name: &str, issuer: &str) -> String {
+    let account_name = account_name.replace(|c: char| !c.is_ascii_alphanumeric(), "");
+    let issuer_clean = issuer.replace(|c: char| !c.is_ascii_alphanumeric(), "");
+
+    format!("otpauth://totp/{issuer_clean}:{account_name}?secret={secret}&issuer={issuer_clean}&digits={DIGITS}&period={STEP}")
+}
+
+/// Returns the time step that `code` is valid for, if it is valid at the time `now` for the given base32 secret.
+/// Codes from one step before or after are accepted as well, to allow for some clock drift.
+pub fn verify(secret: &str, code: &str, now: u64) -> Option<u64> {
+    let secret = base32_decode(secret)?;
+
+    if code.len() != DIGITS as usize || !code.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let code = code.parse::<u32>().ok()?;
+    let current = now / STEP;
+
+    (current.saturating_sub(1)..=current + 1).find(|step| generate_code(&secret, *step) == code)
+}
+
+fn generate_code(secret: &[u8], step: u64) -> u32 {
+    let mut mac = HmacSha1::new_from_slice(secret).expect("hmac accepts keys of any size");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // dynamic truncation, see RFC 4226 section 5.3
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let value = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+
+    value % 10u32.pow(DIGITS)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in data.bytes().filter(|c| *c != b'=' && *c != b' ') {
+        let value = BASE32_ALPHABET.iter().position(|x| *x == c.to_ascii_uppercase())?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the secret used by the test vectors in RFC 4226 and RFC 6238
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+    const RFC_SECRET_BASE32: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn base32() {
+        assert_eq!(base32_encode(RFC_SECRET), RFC_SECRET_BASE32);
+        assert_eq!(base32_decode(RFC_SECRET_BASE32).unwrap(), RFC_SECRET);
+        assert_eq!(base32_decode("gezdgnbv gy3tqojq====").unwrap(), b"1234567890");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_decode("MY======").unwrap(), b"f");
+        assert!(base32_decode("GEZ1").is_none());
+
+        let secret = generate_secret();
+        assert_eq!(base32_decode(&secret).unwrap().len(), SECRET_SIZE);
+    }
+
+    #[test]
+    fn rfc4226_hotp() {
+        // RFC 4226 appendix D
+        let expected = [755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489];
+
+        for (counter, code) in expected.into_iter().enumerate() {
+            assert_eq!(generate_code(RFC_SECRET, counter as u64), code, "counter {counter}");
+        }
+    }
+
+    #[test]
+    fn rfc6238_totp() {
+        // RFC 6238 appendix B (SHA1), truncated to 6 digits
+        let vectors = [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ];
+
+        for (time, code) in vectors {
+            assert_eq!(verify(RFC_SECRET_BASE32, code, time), Some(time / STEP), "time {time}");
+        }
+    }
+
+    #[test]
+    fn verify_window() {
+        let time = 1111111109;
+        let code = "081804";
+
+        // one step of clock drift in either direction is fine
+        assert_eq!(verify(RFC_SECRET_BASE32, code, time - STEP), Some(time / STEP));
+        assert_eq!(verify(RFC_SECRET_BASE32, code, time + STEP), Some(time / STEP));
+
+        // more than that is not
+        assert_eq!(verify(RFC_SECRET_BASE32, code, time - 2 * STEP), None);
+        assert_eq!(verify(RFC_SECRET_BASE32, code, time + 2 * STEP), None);
+    }
+
+    #[test]
+    fn verify_rejects_malformed() {
+        let time = 1111111109;
+
+        assert_eq!(verify(RFC_SECRET_BASE32, "81804", time), None);
+        assert_eq!(verify(RFC_SECRET_BASE32, "0818040", time), None);
+        assert_eq!(verify(RFC_SECRET_BASE32, "+81804", time), None);
+        assert_eq!(verify(RFC_SECRET_BASE32, "08180a", time), None);
+        assert_eq!(verify("not base32!", "081804", time), None);
+    }
+}
@@ -36,6 +36,8 @@ pub struct ViolationMetaChange {
 
 pub enum WebhookMessage {
     AuthFail(String),                         // username
+    AdminKeyUsed(String, i32),                // username, account id
+    AuthRateLimited(String, String, u64),     // rate limited ip or account, reason, retry after (seconds)
     NoticeToEveryone(String, usize, String),  // username, player count, message
     NoticeToSelection(String, usize, String), // username, player count, message
     NoticeToPerson(String, String, String),   // author, target username, message
@@ -48,6 +50,10 @@ pub enum WebhookMessage {
     UserViolationMetaChanged(ViolationMetaChange), // mod username, username, is_banned, is_muted, expiry, reason
     UserRolesChanged(String, String, Vec<String>), // mod username, username, new roles
     UserNameColorChanged(UserNameColorChange),
+    UserSessionsRevoked(String, String, Option<String>), // mod username, username, session id (none if all sessions were revoked)
+    WhitelistApplicationSubmitted(String, i32, String),  // username, account id, message
+    WhitelistApplicationReviewed(String, String, bool, String), // mod username, username, approved, reason
+    InviteCodeCreated(String, u32, u64, Option<String>), // mod username, max uses, expires at, granted role
     FeaturedLevelSend(i32, String, String, i32, String, i32, i32, Option<String>), // user id, user name, level name, level id, level author, difficulty, rate tier, notes
     LevelFeatured(String, i32, String, i32, i32),                                  // level name, level id, level author, difficulty, rate tier
     RoomCreated(u32, String, String, i32, bool, bool),                             // room id, room name, username, account id, hidden, protected
@@ -121,6 +127,25 @@ pub struct WebhookOpts<'a> {
 pub fn embed_for_message(message: &WebhookMessage) -> Option<WebhookEmbed> {
     match message {
         WebhookMessage::AuthFail(_user_name) => None,
+        WebhookMessage::AdminKeyUsed(username, account_id) => Some(WebhookEmbed {
+            title: Cow::Borrowed("Global admin key used"),
+            color: hex_color_to_decimal("#d60f0f"),
+            description: Some(Cow::Owned(format!(
+                "{username} ({account_id}) logged into the admin panel with the global admin key"
+            ))),
+            ..Default::default()
+        }),
+        WebhookMessage::AuthRateLimited(target, reason, retry_after) => Some(WebhookEmbed {
+            title: Cow::Borrowed("Auth rate limit hit"),
+            color: hex_color_to_decimal("#e8c74d"),
+            description: Some(Cow::Owned(format!("{target}: {reason}"))),
+            fields: vec![WebhookField {
+                name: Cow::Borrowed("Retry after"),
+                value: Cow::Owned(format!("{retry_after} seconds")),
+                inline: Some(true),
+            }],
+            ..Default::default()
+        }),
         WebhookMessage::NoticeToEveryone(username, player_count, message) => Some(WebhookEmbed {
             title: Cow::Owned(format!("Global notice (for {player_count} people)")),
             color: hex_color_to_decimal("#4dace8"),
@@ -322,6 +347,97 @@ pub fn embed_for_message(message: &WebhookMessage) -> Option<WebhookEmbed> {
             ],
             ..Default::default()
         }),
+        WebhookMessage::UserSessionsRevoked(mod_name, user_name, session_id) => Some(WebhookEmbed {
+            title: Cow::Borrowed("Sessions revoked"),
+            color: hex_color_to_decimal("#e88d4d"),
+            author: Some(WebhookAuthor {
+                name: Cow::Owned(user_name.clone()),
+                icon_url: None,
+            }),
+            fields: vec![
+                WebhookField {
+                    name: Cow::Borrowed("Performed by"),
+                    value: Cow::Owned(mod_name.clone()),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: Cow::Borrowed("Session"),
+                    value: Cow::Owned(session_id.clone().unwrap_or_else(|| "All sessions".to_owned())),
+                    inline: Some(true),
+                },
+            ],
+            ..Default::default()
+        }),
+        WebhookMessage::WhitelistApplicationSubmitted(user_name, account_id, message) => Some(WebhookEmbed {
+            title: Cow::Borrowed("New whitelist application"),
+            color: hex_color_to_decimal("#4dace8"),
+            author: Some(WebhookAuthor {
+                name: Cow::Owned(format!("{user_name} ({account_id})")),
+                icon_url: None,
+            }),
+            description: (!message.is_empty()).then(|| Cow::Owned(message.clone())),
+            ..Default::default()
+        }),
+        WebhookMessage::WhitelistApplicationReviewed(mod_name, user_name, approved, reason) => Some(WebhookEmbed {
+            title: Cow::Borrowed(if *approved {
+                "Whitelist application approved"
+            } else {
+                "Whitelist application denied"
+            }),
+            color: hex_color_to_decimal(if *approved { "#4de877" } else { "#e84d4d" }),
+            author: Some(WebhookAuthor {
+                name: Cow::Owned(user_name.clone()),
+                icon_url: None,
+            }),
+            fields: vec![
+                WebhookField {
+                    name: Cow::Borrowed("Performed by"),
+                    value: Cow::Owned(mod_name.clone()),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: Cow::Borrowed("Reason"),
+                    value: Cow::Owned(if reason.is_empty() {
+                        "No reason given".to_owned()
+                    } else {
+                        reason.clone()
+                    }),
+                    inline: Some(true),
+                },
+            ],
+            ..Default::default()
+        }),
+        WebhookMessage::InviteCodeCreated(mod_name, max_uses, expires_at, role_id) => Some(WebhookEmbed {
+            title: Cow::Borrowed("Invite code created"),
+            color: hex_color_to_decimal("#4dace8"),
+            fields: vec![
+                WebhookField {
+                    name: Cow::Borrowed("Performed by"),
+                    value: Cow::Owned(mod_name.clone()),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: Cow::Borrowed("Uses"),
+                    value: Cow::Owned(max_uses.to_string()),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: Cow::Borrowed("Expires"),
+                    value: Cow::Owned(if *expires_at == 0 {
+                        "Never".to_owned()
+                    } else {
+                        format!("<t:{expires_at}:f>")
+                    }),
+                    inline: Some(true),
+                },
+                WebhookField {
+                    name: Cow::Borrowed("Role"),
+                    value: Cow::Owned(role_id.clone().unwrap_or_else(|| "None".to_owned())),
+                    inline: Some(true),
+                },
+            ],
+            ..Default::default()
+        }),
         WebhookMessage::UserNameColorChanged(change) => Some(WebhookEmbed {
             title: Cow::Borrowed("Name color change"),
             color: hex_color_to_decimal(change.new_color.as_ref().map_or_else(|| "", |x| x.as_str())),
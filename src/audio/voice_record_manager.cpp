@@ -63,6 +63,7 @@ void VoiceRecordingManager::threadFunc(decltype(thread)::StopToken&) {
 
                 ByteBuffer buf;
                 buf.writeValue(frame);
+                buf.writeBool(false); // teamOnly
 
                 nm.send(RawPacket::create<VoicePacket>(std::move(buf)));
             });
@@ -28,18 +28,19 @@ class AdminSendNoticePacket : public Packet {
     GLOBED_PACKET(19001, AdminSendNoticePacket, true, true)
 
     AdminSendNoticePacket() {}
-    AdminSendNoticePacket(AdminSendNoticeType ptype, uint32_t roomId, LevelId levelId, std::string_view player, std::string_view message)
-        : ptype(ptype), roomId(roomId), levelId(levelId), player(player), message(message) {}
+    AdminSendNoticePacket(AdminSendNoticeType ptype, uint32_t roomId, LevelId levelId, std::string_view player, std::string_view message, bool requireAck = false)
+        : ptype(ptype), roomId(roomId), levelId(levelId), player(player), message(message), requireAck(requireAck) {}
 
     AdminSendNoticeType ptype;
     uint32_t roomId;
     LevelId levelId;
     std::string player;
     std::string message;
+    bool requireAck;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(AdminSendNoticePacket, (
-    ptype, roomId, levelId, player, message
+    ptype, roomId, levelId, player, message, requireAck
 ));
 
 // 19002 - AdminDisconnectPacket
@@ -227,4 +228,16 @@ class AdminGetPunishmentHistoryPacket : public Packet {
 
 GLOBED_SERIALIZABLE_STRUCT(AdminGetPunishmentHistoryPacket, (
     accountId
-));
\ No newline at end of file
+));
+
+// 19021 - AdminTotpCodePacket
+class AdminTotpCodePacket : public Packet {
+    GLOBED_PACKET(19021, AdminTotpCodePacket, true, true)
+
+    AdminTotpCodePacket() {}
+    AdminTotpCodePacket(std::string_view code) : code(code) {}
+
+    std::string code;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminTotpCodePacket, (code));
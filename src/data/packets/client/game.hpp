@@ -74,8 +74,9 @@ class VoicePacket : public Packet {
     VoicePacket(std::shared_ptr<EncodedAudioFrame> _frame) : frame(_frame) {}
 
     std::shared_ptr<EncodedAudioFrame> frame;
+    bool teamOnly = false;
 };
-GLOBED_SERIALIZABLE_STRUCT(VoicePacket, (frame));
+GLOBED_SERIALIZABLE_STRUCT(VoicePacket, (frame, teamOnly));
 
 #endif // GLOBED_VOICE_SUPPORT
 
@@ -84,8 +85,11 @@ class ChatMessagePacket : public Packet {
     GLOBED_PACKET(12011, ChatMessagePacket, true, false)
 
     ChatMessagePacket() {}
-    ChatMessagePacket(std::string_view message) : message(message) {}
+    ChatMessagePacket(std::string_view message, ChatChannel channel = ChatChannel::Level, bool teamOnly = false)
+        : message(message), channel(channel), teamOnly(teamOnly) {}
 
     std::string message;
+    ChatChannel channel = ChatChannel::Level;
+    bool teamOnly = false;
 };
-GLOBED_SERIALIZABLE_STRUCT(ChatMessagePacket, (message));
+GLOBED_SERIALIZABLE_STRUCT(ChatMessagePacket, (message, channel, teamOnly));
@@ -66,3 +66,15 @@ class LinkCodeRequestPacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(LinkCodeRequestPacket, ());
+
+// 11014 - AcknowledgeNoticePacket
+class AcknowledgeNoticePacket : public Packet {
+    GLOBED_PACKET(11014, AcknowledgeNoticePacket, false, false);
+
+    AcknowledgeNoticePacket() {}
+    AcknowledgeNoticePacket(int64_t id) : id(id) {}
+
+    int64_t id;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AcknowledgeNoticePacket, (id));
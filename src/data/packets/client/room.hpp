@@ -90,3 +90,15 @@ class CloseRoomPacket : public Packet {
     uint32_t roomId;
 };
 GLOBED_SERIALIZABLE_STRUCT(CloseRoomPacket, (roomId));
+
+// 13014 - RoomInviteResponsePacket
+class RoomInviteResponsePacket : public Packet {
+    GLOBED_PACKET(13014, RoomInviteResponsePacket, false, false)
+
+    RoomInviteResponsePacket() {}
+    RoomInviteResponsePacket(uint32_t inviteId, bool accept) : inviteId(inviteId), accept(accept) {}
+
+    uint32_t inviteId;
+    bool accept;
+};
+GLOBED_SERIALIZABLE_STRUCT(RoomInviteResponsePacket, (inviteId, accept));
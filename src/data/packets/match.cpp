@@ -20,6 +20,7 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(ServerNoticePacket);
         PACKET(ServerBannedPacket);
         PACKET(ServerMutedPacket);
+        PACKET(StoredNoticePacket);
         PACKET(ConnectionTestResponsePacket);
 
         // general
@@ -29,6 +30,9 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(LevelPlayerCountPacket);
         PACKET(RolesUpdatedPacket);
         PACKET(LinkCodeResponsePacket);
+        PACKET(FriendStatusChangedPacket);
+        PACKET(PrivateMessageReceivedPacket);
+        PACKET(PrivateMessageStatusPacket);
 
         // game related
 
@@ -37,6 +41,7 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(LevelPlayerMetadataPacket);
         PACKET(VoiceBroadcastPacket);
         PACKET(ChatMessageBroadcastPacket);
+        PACKET(ChatCommandResponsePacket);
 
         // room related
 
@@ -48,6 +53,8 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(RoomInvitePacket);
         PACKET(RoomListPacket);
         PACKET(RoomCreateFailedPacket);
+        PACKET(RoomTeamChangedPacket);
+        PACKET(RoomInviteStatusPacket);
 
         // admin related
 
@@ -58,6 +65,7 @@ std::shared_ptr<Packet> matchPacket(packetid_t packetId) {
         PACKET(AdminAuthFailedPacket);
         PACKET(AdminPunishmentHistoryPacket);
         PACKET(AdminSuccessfulUpdatePacket);
+        PACKET(AdminTotpRequiredPacket);
 
         default:
             return std::shared_ptr<Packet>(nullptr);
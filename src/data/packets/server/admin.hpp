@@ -80,3 +80,12 @@ class AdminSuccessfulUpdatePacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(AdminSuccessfulUpdatePacket, (userEntry));
+
+// 29008 - AdminTotpRequiredPacket
+class AdminTotpRequiredPacket : public Packet {
+    GLOBED_PACKET(29008, AdminTotpRequiredPacket, true, true)
+
+    AdminTotpRequiredPacket() {}
+};
+
+GLOBED_SERIALIZABLE_STRUCT(AdminTotpRequiredPacket, ());
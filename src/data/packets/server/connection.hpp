@@ -105,7 +105,7 @@ GLOBED_SERIALIZABLE_STRUCT(LoginRecoveryFailecPacket, ());
 
 // 20100 - ServerNoticePacket
 class ServerNoticePacket : public Packet {
-    GLOBED_PACKET(20100, ServerNoticePacket, false, false)
+    GLOBED_PACKET(20100, ServerNoticePacket, false, true)
 
     ServerNoticePacket() {}
 
@@ -135,6 +135,19 @@ class ServerMutedPacket : public Packet {
 };
 GLOBED_SERIALIZABLE_STRUCT(ServerMutedPacket, (reason, timestamp));
 
+// 20103 - StoredNoticePacket
+class StoredNoticePacket : public Packet {
+    GLOBED_PACKET(20103, StoredNoticePacket, false, true)
+
+    StoredNoticePacket() {}
+
+    int64_t id;
+    std::string message;
+    bool requireAck;
+    int64_t sentAt;
+};
+GLOBED_SERIALIZABLE_STRUCT(StoredNoticePacket, (id, message, requireAck, sentAt));
+
 // 20200 - ConnectionTestResponsePacket
 class ConnectionTestResponsePacket : public Packet {
     GLOBED_PACKET(20200, ConnectionTestResponsePacket, false, false)
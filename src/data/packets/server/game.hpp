@@ -65,7 +65,19 @@ class ChatMessageBroadcastPacket : public Packet {
     ChatMessageBroadcastPacket() {}
 
     int sender;
+    ChatChannel channel;
     std::string message;
 };
 
-GLOBED_SERIALIZABLE_STRUCT(ChatMessageBroadcastPacket, (sender, message));
+GLOBED_SERIALIZABLE_STRUCT(ChatMessageBroadcastPacket, (sender, channel, message));
+
+// 22012 - ChatCommandResponsePacket
+class ChatCommandResponsePacket : public Packet {
+    GLOBED_PACKET(22012, ChatCommandResponsePacket, false, true)
+
+    ChatCommandResponsePacket() {}
+
+    std::string message;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(ChatCommandResponsePacket, (message));
@@ -57,3 +57,39 @@ class LinkCodeResponsePacket : public Packet {
 };
 
 GLOBED_SERIALIZABLE_STRUCT(LinkCodeResponsePacket, (linkCode));
+
+// 21007 - FriendStatusChangedPacket
+class FriendStatusChangedPacket : public Packet {
+    GLOBED_PACKET(21007, FriendStatusChangedPacket, false, false)
+
+    FriendStatusChangedPacket() {}
+
+    int32_t player;
+    FriendStatus status;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(FriendStatusChangedPacket, (player, status));
+
+// 21009 - PrivateMessageReceivedPacket
+class PrivateMessageReceivedPacket : public Packet {
+    GLOBED_PACKET(21009, PrivateMessageReceivedPacket, true, true)
+
+    PrivateMessageReceivedPacket() {}
+
+    PlayerPreviewAccountData sender;
+    std::string message;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PrivateMessageReceivedPacket, (sender, message));
+
+// 21010 - PrivateMessageStatusPacket
+class PrivateMessageStatusPacket : public Packet {
+    GLOBED_PACKET(21010, PrivateMessageStatusPacket, false, false)
+
+    PrivateMessageStatusPacket() {}
+
+    int32_t receiver;
+    PrivateMessageStatus status;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(PrivateMessageStatusPacket, (receiver, status));
@@ -63,9 +63,9 @@ class RoomInvitePacket : public Packet {
 
     PlayerPreviewAccountData playerData;
     uint32_t roomID;
-    std::string password;
+    uint32_t inviteId;
 };
-GLOBED_SERIALIZABLE_STRUCT(RoomInvitePacket, (playerData, roomID, password));
+GLOBED_SERIALIZABLE_STRUCT(RoomInvitePacket, (playerData, roomID, inviteId));
 
 // 23006 - RoomListPacket
 class RoomListPacket : public Packet {
@@ -85,3 +85,25 @@ class RoomCreateFailedPacket : public Packet {
     std::string reason;
 };
 GLOBED_SERIALIZABLE_STRUCT(RoomCreateFailedPacket, (reason));
+
+// 23008 - RoomTeamChangedPacket
+class RoomTeamChangedPacket : public Packet {
+    GLOBED_PACKET(23008, RoomTeamChangedPacket, false, true)
+
+    RoomTeamChangedPacket() {}
+
+    int32_t playerId;
+    std::optional<uint8_t> teamId;
+};
+GLOBED_SERIALIZABLE_STRUCT(RoomTeamChangedPacket, (playerId, teamId));
+
+// 23010 - RoomInviteStatusPacket
+class RoomInviteStatusPacket : public Packet {
+    GLOBED_PACKET(23010, RoomInviteStatusPacket, false, true)
+
+    RoomInviteStatusPacket() {}
+
+    int32_t playerId;
+    RoomInviteStatus status;
+};
+GLOBED_SERIALIZABLE_STRUCT(RoomInviteStatusPacket, (playerId, status));
@@ -16,6 +16,14 @@ enum class PlayerIconType : uint8_t {
 
 GLOBED_SERIALIZABLE_ENUM(PlayerIconType, Unknown, Cube, Ship, Ball, Ufo, Wave, Robot, Spider, Swing, Jetpack);
 
+enum class ChatChannel : uint8_t {
+    Level = 0,
+    Room = 1,
+    Global = 2,
+};
+
+GLOBED_SERIALIZABLE_ENUM(ChatChannel, Level, Room, Global);
+
 struct SpiderTeleportData {
     cocos2d::CCPoint from, to;
 };
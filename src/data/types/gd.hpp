@@ -90,10 +90,11 @@ public:
     PlayerIconDataSimple icons;
     LevelId levelId;
     SpecialUserData specialUserData;
+    std::optional<uint8_t> teamId;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(PlayerRoomPreviewAccountData, (
-    accountId, userId, name, icons, levelId, specialUserData
+    accountId, userId, name, icons, levelId, specialUserData, teamId
 ));
 
 class PlayerPreviewAccountData {
@@ -9,15 +9,16 @@ struct RoomSettingsFlags : BitfieldBase {
     bool collision;
     bool twoPlayerMode;
     bool deathlink;
+    bool lockedTeams;
 
     // we need the struct to be 2 bytes
-    bool _pad1, _pad2, _pad3, _pad4, _pad5;
+    bool _pad1, _pad2, _pad3, _pad4;
 };
 
 static_assert((sizeof(RoomSettingsFlags) + 7) / 8 == 2);
 
 GLOBED_SERIALIZABLE_BITFIELD(RoomSettingsFlags, (
-    isHidden, publicInvites, collision, twoPlayerMode, deathlink
+    isHidden, publicInvites, collision, twoPlayerMode, deathlink, lockedTeams
 ))
 
 struct RoomSettings {
@@ -31,16 +32,27 @@ GLOBED_SERIALIZABLE_STRUCT(RoomSettings, (
     flags, playerLimit, levelId, fasterReset
 ))
 
+struct RoomTeam {
+    std::string name;
+    cocos2d::ccColor3B color;
+};
+
+GLOBED_SERIALIZABLE_STRUCT(RoomTeam, (
+    name, color
+));
+
 struct RoomInfo {
     uint32_t id;
     PlayerPreviewAccountData owner;
     std::string name;
     std::string password;
     RoomSettings settings;
+    std::vector<int32_t> coHosts;
+    std::vector<RoomTeam> teams;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(RoomInfo, (
-    id, owner, name, password, settings
+    id, owner, name, password, settings, coHosts, teams
 ));
 
 struct RoomListingInfo {
@@ -55,3 +67,14 @@ struct RoomListingInfo {
 GLOBED_SERIALIZABLE_STRUCT(RoomListingInfo, (
     id, playerCount, owner, name, hasPassword, settings
 ));
+
+enum class RoomInviteStatus : uint8_t {
+    Sent = 0,
+    Accepted = 1,
+    Declined = 2,
+    Expired = 3,
+    Unavailable = 4,
+    RateLimited = 5,
+};
+
+GLOBED_SERIALIZABLE_ENUM(RoomInviteStatus, Sent, Accepted, Declined, Expired, Unavailable, RateLimited);
@@ -17,10 +17,18 @@ struct ServerRole {
     bool editRole;
     bool editFeaturedLevels;
     bool admin;
+
+    uint64_t maxBanDuration, maxMuteDuration;
+    bool permanentPunishments;
+    bool editHigherPunishments;
+
+    std::vector<std::string> inherits;
+    std::vector<std::string> permissions;
 };
 
 GLOBED_SERIALIZABLE_STRUCT(ServerRole, (
-    id, priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, editFeaturedLevels, admin
+    id, priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, editFeaturedLevels, admin,
+    maxBanDuration, maxMuteDuration, permanentPunishments, editHigherPunishments, inherits, permissions
 ));
 
 struct GameServerRole {
@@ -46,13 +54,23 @@ struct ComputedRole {
     bool editFeaturedLevels = false;
     bool admin = false;
 
+    std::vector<std::string> permissions;
+
+    // same as `ComputedRole::can_moderate` on the server, any permission counts
     bool canModerate() {
-        return notices || noticesToEveryone || kick || kickEveryone || mute || ban || editRole || editFeaturedLevels || admin;
+        return !permissions.empty();
+    }
+
+    // same as `ComputedRole::has_permission` on the server, `permissions` includes inherited and custom permissions
+    bool hasPermission(std::string_view perm) {
+        return std::any_of(permissions.begin(), permissions.end(), [&](const std::string& x) {
+            return x == "admin" || x == perm;
+        });
     }
 };
 
 GLOBED_SERIALIZABLE_STRUCT(ComputedRole, (
-    priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, editFeaturedLevels, admin
+    priority, badgeIcon, nameColor, chatColor, notices, noticesToEveryone, kick, kickEveryone, mute, ban, editRole, editFeaturedLevels, admin, permissions
 ));
 
 struct UserPrivacyFlags : BitfieldBase {
@@ -66,6 +84,26 @@ GLOBED_SERIALIZABLE_BITFIELD(UserPrivacyFlags, (
     hideFromLists, noInvites, hideInGame, hideRoles
 ));
 
+enum class FriendStatus : uint8_t {
+    None = 0,
+    Friends = 1,
+    RequestSent = 2,
+    RequestReceived = 3,
+};
+
+GLOBED_SERIALIZABLE_ENUM(FriendStatus, None, Friends, RequestSent, RequestReceived);
+
+enum class PrivateMessageStatus : uint8_t {
+    Delivered = 0,
+    Offline = 1,
+    Unavailable = 2,
+    Muted = 3,
+    Filtered = 4,
+    RateLimited = 5,
+};
+
+GLOBED_SERIALIZABLE_ENUM(PrivateMessageStatus, Delivered, Offline, Unavailable, Muted, Filtered, RateLimited);
+
 enum class PunishmentType : uint8_t {
     Ban = 0, Mute = 1
 };
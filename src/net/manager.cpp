@@ -23,8 +23,10 @@
 #include <managers/role.hpp>
 #include <util/cocos.hpp>
 #include <util/format.hpp>
+#include <util/misc.hpp>
 #include <util/time.hpp>
 #include <util/net.hpp>
+#include <ui/general/ask_input_popup.hpp>
 #include <ui/notification/panel.hpp>
 
 using namespace asp;
@@ -32,9 +34,9 @@ using namespace asp::time;
 using namespace geode::prelude;
 using ConnectionState = NetworkManager::ConnectionState;
 
-static constexpr uint16_t MIN_PROTOCOL_VERSION = 13;
-static constexpr uint16_t MAX_PROTOCOL_VERSION = 13;
-static constexpr std::array SUPPORTED_PROTOCOLS = std::to_array<uint16_t>({13});
+static constexpr uint16_t MIN_PROTOCOL_VERSION = 14;
+static constexpr uint16_t MAX_PROTOCOL_VERSION = 14;
+static constexpr std::array SUPPORTED_PROTOCOLS = std::to_array<uint16_t>({14});
 
 static bool isProtocolSupported(uint16_t proto) {
 #ifdef GLOBED_DEBUG
@@ -544,6 +546,14 @@ protected:
             ErrorQueues::get().notice(packet->message);
         });
 
+        addGlobalListener<StoredNoticePacket>([this](auto packet) {
+            ErrorQueues::get().notice(packet->message);
+
+            if (packet->requireAck) {
+                this->send(AcknowledgeNoticePacket::create(packet->id));
+            }
+        });
+
         addGlobalListener<ServerBannedPacket>([this](auto packet) {
             using namespace std::chrono;
 
@@ -585,6 +595,37 @@ protected:
             pcm.setOwnSpecialData(packet->specialUserData);
         });
 
+        addGlobalListener<FriendStatusChangedPacket>([](auto packet) {
+            switch (packet->status) {
+                case FriendStatus::RequestReceived: ErrorQueues::get().notice("You received a new friend request"); break;
+                case FriendStatus::Friends: ErrorQueues::get().success("You have a new friend"); break;
+                default: break;
+            }
+        });
+
+        addGlobalListener<PrivateMessageReceivedPacket>([](auto packet) {
+            if (FriendListManager::get().isBlocked(packet->sender.accountId)) return;
+
+            ErrorQueues::get().notice(fmt::format("<cy>{}</c> sent you a message:\n{}", packet->sender.name, packet->message));
+        });
+
+        addGlobalListener<PrivateMessageStatusPacket>([](auto packet) {
+            switch (packet->status) {
+                case PrivateMessageStatus::Delivered: ErrorQueues::get().success("Message sent"); break;
+                case PrivateMessageStatus::Offline: ErrorQueues::get().warn("This player is not online"); break;
+                case PrivateMessageStatus::Unavailable: ErrorQueues::get().warn("This player does not accept messages from you"); break;
+                case PrivateMessageStatus::Muted: ErrorQueues::get().warn("You cannot send messages while muted"); break;
+                case PrivateMessageStatus::Filtered: ErrorQueues::get().warn("Your message was blocked by the filter"); break;
+                case PrivateMessageStatus::RateLimited: ErrorQueues::get().warn("You are sending messages too quickly"); break;
+            }
+        });
+
+        // Game packets
+
+        addGlobalListener<ChatCommandResponsePacket>([](auto packet) {
+            ErrorQueues::get().notice(packet->message);
+        });
+
         // Room packets
 
         addGlobalListener<RoomInvitePacket>([](auto packet) {
@@ -608,7 +649,18 @@ protected:
                 return;
             }
 
-            GlobedNotificationPanel::get()->addInviteNotification(packet->roomID, packet->password, packet->playerData);
+            GlobedNotificationPanel::get()->addInviteNotification(packet->roomID, packet->inviteId, packet->playerData);
+        });
+
+        addGlobalListener<RoomInviteStatusPacket>([](auto packet) {
+            switch (packet->status) {
+                case RoomInviteStatus::Sent: break;
+                case RoomInviteStatus::Accepted: ErrorQueues::get().success("Room invite accepted"); break;
+                case RoomInviteStatus::Declined: ErrorQueues::get().warn("Room invite declined"); break;
+                case RoomInviteStatus::Expired: ErrorQueues::get().warn("Room invite expired"); break;
+                case RoomInviteStatus::Unavailable: ErrorQueues::get().warn("This player cannot be invited right now"); break;
+                case RoomInviteStatus::RateLimited: ErrorQueues::get().warn("You are sending invites too quickly"); break;
+            }
         });
 
         addGlobalListener<RoomInfoPacket>([](auto packet) {
@@ -619,6 +671,8 @@ protected:
 
         addGlobalListener<RoomJoinedPacket>([](auto packet) {});
 
+        addGlobalListener<RoomTeamChangedPacket>([](auto packet) {});
+
         addGlobalListener<RoomJoinFailedPacket>([](auto packet) {
             std::string reason = "N/A";
             if (packet->wasInvalid) reason = "Room doesn't exist";
@@ -644,6 +698,13 @@ protected:
             am.clearAdminPassword();
         });
 
+        addGlobalListener<AdminTotpRequiredPacket>([this](auto packet) {
+            // the password was correct, but the account has two-factor authentication enabled
+            AskInputPopup::create("Two-factor Code", [this](std::string_view code) {
+                this->send(AdminTotpCodePacket::create(code));
+            }, 6, "Code", util::misc::STRING_DIGITS, 1.f)->show();
+        });
+
         addGlobalListener<AdminSuccessMessagePacket>([](auto packet) {
             ErrorQueues::get().success(packet->message);
         });
@@ -84,9 +84,27 @@ bool AdminSendNoticePopup::setup(std::string_view message) {
         .layout(RowLayout::create())
         .parent(rootLayout);
 
+    // require the user to acknowledge the notice
+    auto* ackLayout = Build<CCMenu>::create()
+        .layout(RowLayout::create()->setGap(5.f)->setAutoScale(false))
+        .contentSize(m_size.width * 0.8f, 0.f)
+        .parent(rootLayout)
+        .collect();
+
+    auto* ackToggler = CCMenuItemToggler::createWithStandardSprites(this, menu_selector(AdminSendNoticePopup::onRequireAck), 0.6f);
+    ackLayout->addChild(ackToggler);
+
+    Build<CCLabelBMFont>::create("Require acknowledgement", "bigFont.fnt")
+        .scale(0.4f)
+        .parent(ackLayout);
+
+    ackLayout->setContentHeight(ackToggler->getScaledContentSize().height);
+    ackLayout->updateLayout();
+
     rootLayout->setContentSize(CCPoint{
         m_size.width * 0.8f,
-        10.f + userLayout->getScaledContentSize().height + rlLayout->getScaledContentSize().height + everyoneBtn->getScaledContentSize().height
+        15.f + userLayout->getScaledContentSize().height + rlLayout->getScaledContentSize().height + everyoneBtn->getScaledContentSize().height
+            + ackLayout->getScaledContentSize().height
     });
 
     rootLayout->updateLayout();
@@ -103,10 +121,14 @@ void AdminSendNoticePopup::commonSend(AdminSendNoticeType type) {
         roomId = util::format::parse<uint32_t>(roomInput->getString()).value_or(0);
     }
 
-    auto packet = AdminSendNoticePacket::create(type, roomId, levelId, userInput->getString(), message);
+    auto packet = AdminSendNoticePacket::create(type, roomId, levelId, userInput->getString(), message, requireAck);
     NetworkManager::get().send(packet);
 }
 
+void AdminSendNoticePopup::onRequireAck(cocos2d::CCObject* sender) {
+    requireAck = !static_cast<CCMenuItemToggler*>(sender)->isOn();
+}
+
 AdminSendNoticePopup* AdminSendNoticePopup::create(std::string_view message) {
     auto* ret = new AdminSendNoticePopup;
     if (ret->initAnchored(POPUP_WIDTH, POPUP_HEIGHT, message)) {
@@ -8,14 +8,16 @@
 class AdminSendNoticePopup : public geode::Popup<std::string_view> {
 public:
     static constexpr float POPUP_WIDTH = 320.f;
-    static constexpr float POPUP_HEIGHT = 160.f;
+    static constexpr float POPUP_HEIGHT = 185.f;
 
     static AdminSendNoticePopup* create(std::string_view message);
 
 private:
     std::string message;
     geode::TextInput *userInput, *roomInput, *levelInput;
+    bool requireAck = false;
 
     bool setup(std::string_view message);
     void commonSend(AdminSendNoticeType type);
+    void onRequireAck(cocos2d::CCObject* sender);
 };
@@ -7,7 +7,7 @@
 
 using namespace geode::prelude;
 
-bool GlobedInviteNotification::init(uint32_t roomID, std::string_view password, const PlayerPreviewAccountData& player) {
+bool GlobedInviteNotification::init(uint32_t roomID, uint32_t inviteId, const PlayerPreviewAccountData& player) {
     if (!CCLayer::init()) return false;
 
     this->setID(fmt::format("room-invite-{}", roomID));
@@ -73,10 +73,10 @@ bool GlobedInviteNotification::init(uint32_t roomID, std::string_view password,
     menu->setTouchPriority(-1000); // this is stupid x2
 
     Build<ButtonSprite>::create("Accept", "bigFont.fnt", "GJ_button_01.png", 0.8f)
-        .intoMenuItem([this, roomID, password = std::string(password)](auto) {
+        .intoMenuItem([this, inviteId](auto) {
             auto& nm = NetworkManager::get();
             if (nm.established()) {
-                nm.send(JoinRoomPacket::create(roomID, password));
+                nm.send(RoomInviteResponsePacket::create(inviteId, true));
             }
 
             this->removeFromParent();
@@ -86,7 +86,12 @@ bool GlobedInviteNotification::init(uint32_t roomID, std::string_view password,
         ;
 
     Build<ButtonSprite>::create("Reject", "bigFont.fnt", "GJ_button_06.png", 0.8f)
-        .intoMenuItem([this, roomID](auto) {
+        .intoMenuItem([this, inviteId](auto) {
+            auto& nm = NetworkManager::get();
+            if (nm.established()) {
+                nm.send(RoomInviteResponsePacket::create(inviteId, false));
+            }
+
             this->removeFromParent();
         })
         .id("btn-reject")
@@ -107,9 +112,9 @@ void GlobedInviteNotification::removeFromParent() {
     GlobedNotificationPanel::get()->updateLayout();
 }
 
-GlobedInviteNotification* GlobedInviteNotification::create(uint32_t roomID, std::string_view password, const PlayerPreviewAccountData& player) {
+GlobedInviteNotification* GlobedInviteNotification::create(uint32_t roomID, uint32_t inviteId, const PlayerPreviewAccountData& player) {
     auto ret = new GlobedInviteNotification;
-    if (ret->init(roomID, password, player)) {
+    if (ret->init(roomID, inviteId, player)) {
         ret->autorelease();
         return ret;
     }
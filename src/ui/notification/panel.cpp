@@ -31,8 +31,8 @@ void GlobedNotificationPanel::persist() {
     instance = this;
 }
 
-void GlobedNotificationPanel::addInviteNotification(uint32_t roomID, std::string_view password, const PlayerPreviewAccountData& player) {
-    auto* notif = GlobedInviteNotification::create(roomID, password, player);
+void GlobedNotificationPanel::addInviteNotification(uint32_t roomID, uint32_t inviteId, const PlayerPreviewAccountData& player) {
+    auto* notif = GlobedInviteNotification::create(roomID, inviteId, player);
     this->slideInNotification(notif);
 
 #if GLOBED_HAS_FMOD
@@ -15,7 +15,7 @@ public:
 
     void persist();
 
-    void addInviteNotification(uint32_t roomID, std::string_view password, const PlayerPreviewAccountData& player);
+    void addInviteNotification(uint32_t roomID, uint32_t inviteId, const PlayerPreviewAccountData& player);
     void slideInNotification(cocos2d::CCNode* node);
     void queueNotification(cocos2d::CCNode* node);
 